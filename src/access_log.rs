@@ -0,0 +1,139 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use clap::ValueEnum;
+use log::info;
+use std::io::Write;
+use std::time::Instant;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+// Target of the access log lines, which the JSON formatter merges as fields
+const ACCESS_TARGET: &str = "access";
+
+// Incoming ids longer than this are replaced rather than echoed back
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+// Id of the request, honoring the client's `X-Request-Id`
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+// Path template of the endpoint that answered, attached to the response by
+// the handler so the access log can report it
+#[derive(Debug, Clone)]
+pub struct MatchedEndpoint(pub String);
+
+// The filter set by `--quiet` or `--verbose`, `None` leaving it to `RUST_LOG`
+pub fn log_filter(quiet: bool, verbose: u8) -> Option<&'static str> {
+    match (quiet, verbose) {
+        (true, _) => Some("error"),
+        (false, 0) => None,
+        (false, 1) => Some("info,rusty_stub_api=debug"),
+        (false, 2) => Some("debug"),
+        (false, _) => Some("trace"),
+    }
+}
+
+// Sets up `env_logger`, with `filter` when given and otherwise `RUST_LOG`,
+// defaulting to `info` when it is not set
+pub fn init_logger(format: LogFormat, filter: Option<&str>) {
+    let mut builder = match filter {
+        Some(filter) => {
+            let mut builder = env_logger::Builder::new();
+            builder.parse_filters(filter);
+            builder
+        }
+        None => env_logger::Builder::from_env(env_logger::Env::new().default_filter_or("info")),
+    };
+
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let message = record.args().to_string();
+            let mut line = match serde_json::from_str(&message) {
+                Ok(serde_json::Value::Object(fields)) if record.target() == ACCESS_TARGET => fields,
+                _ => {
+                    let mut fields = serde_json::Map::new();
+                    fields.insert("message".to_string(), message.into());
+                    fields
+                }
+            };
+            line.insert("timestamp".to_string(), buf.timestamp().to_string().into());
+            line.insert("level".to_string(), record.level().as_str().into());
+            line.insert("target".to_string(), record.target().into());
+            writeln!(buf, "{}", serde_json::Value::Object(line))
+        });
+    }
+    builder.init();
+}
+
+// Middleware assigning every request an id, echoed in `X-Request-Id`, and
+// logging one line per request once it's answered
+pub async fn access_log(
+    format: LogFormat,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let started = Instant::now();
+    let request_id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let method = req.method().to_string();
+    let path = req.path().to_string();
+
+    let mut response = next.call(req).await?;
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER.clone(), value);
+    }
+
+    let status = response.status().as_u16();
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let endpoint = response
+        .response()
+        .extensions()
+        .get::<MatchedEndpoint>()
+        .map(|matched| matched.0.clone());
+
+    match format {
+        LogFormat::Text => info!(
+            target: ACCESS_TARGET,
+            "request_id={} method={} path={} status={} latency_ms={:.3} endpoint={}",
+            request_id,
+            method,
+            path,
+            status,
+            latency_ms,
+            endpoint.as_deref().unwrap_or("-")
+        ),
+        LogFormat::Json => info!(
+            target: ACCESS_TARGET,
+            "{}",
+            serde_json::json!({
+                "request_id": request_id,
+                "method": method,
+                "path": path,
+                "status": status,
+                "latency_ms": latency_ms,
+                "endpoint": endpoint,
+            })
+        ),
+    }
+    Ok(response)
+}
@@ -0,0 +1,132 @@
+use crate::data::app::AppState;
+use crate::data::settings::Settings;
+use crate::errors::AppError;
+use crate::reload::reload_spec;
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+use std::sync::Arc;
+
+// Header carrying the `--admin-token` on admin requests
+pub const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+// The admin routes, as reported by `/api/endpoints`. Those of the store are
+// only there in `--stateful` mode, the shutdown one with `--enable-admin`
+pub fn admin_routes(settings: &Settings) -> Vec<(&'static str, &'static str)> {
+    let mut routes = vec![("POST", "/admin/reload")];
+    if settings.stateful {
+        routes.push(("GET", "/admin/state"));
+        routes.push(("DELETE", "/admin/state"));
+    }
+    if settings.enable_admin {
+        routes.push(("POST", "/admin/shutdown"));
+    }
+    routes
+}
+
+// Admin routes are disabled unless `--admin-token` is given, and then only
+// answer requests presenting it. `None` lets the request through
+fn reject_unauthorized(req: &HttpRequest, app_state: &AppState) -> Option<HttpResponse> {
+    let Some(token) = &app_state.settings.admin_token else {
+        return Some(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Admin routes are disabled, start the server with --admin-token",
+        })));
+    };
+
+    let presented = req
+        .headers()
+        .get(ADMIN_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if presented != Some(token.as_str()) {
+        return Some(HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Missing or invalid admin token",
+            "header": ADMIN_TOKEN_HEADER,
+        })));
+    }
+    None
+}
+
+// Re-reads the spec and swaps in its endpoints. A spec that doesn't parse, or
+// fails the checks run at startup, is reported with 422 and the previous
+// endpoints keep being served
+pub async fn reload(req: HttpRequest, app_state: web::Data<Arc<AppState>>) -> HttpResponse {
+    if let Some(rejection) = reject_unauthorized(&req, &app_state) {
+        return rejection;
+    }
+    if app_state.sources.is_empty() {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "The server was not started from a spec source that can be re-read",
+        }));
+    }
+
+    // Reading a remote spec blocks, so it's kept off the async workers.
+    // `AppError` can't leave the thread, hence the status is decided there
+    let state = app_state.get_ref().clone();
+    let result = web::block(move || {
+        reload_spec(&state, &state.sources, &state.settings).map_err(|e| {
+            let status = match e {
+                AppError::YamlError(_)
+                | AppError::JsonError(_)
+                | AppError::ParseError(_)
+                | AppError::RefError(_)
+                | AppError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, e.to_string())
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(summary)) => {
+            info!(
+                "Reloaded {} endpoints through the admin route, {} added, {} removed",
+                summary.total,
+                summary.added.len(),
+                summary.removed.len()
+            );
+            HttpResponse::Ok().json(summary)
+        }
+        Ok(Err((status, message))) => {
+            error!(
+                "Failed to reload spec, keeping the previous one: {}",
+                message
+            );
+            HttpResponse::build(status).json(serde_json::json!({ "error": message }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string(),
+        })),
+    }
+}
+
+// Everything `--stateful` mode has stored, by collection and then by id
+pub async fn state(req: HttpRequest, app_state: web::Data<Arc<AppState>>) -> HttpResponse {
+    if let Some(rejection) = reject_unauthorized(&req, &app_state) {
+        return rejection;
+    }
+    HttpResponse::Ok().json(app_state.store.snapshot())
+}
+
+// Empties the store, e.g. between the test cases sharing a server
+pub async fn reset_state(req: HttpRequest, app_state: web::Data<Arc<AppState>>) -> HttpResponse {
+    if let Some(rejection) = reject_unauthorized(&req, &app_state) {
+        return rejection;
+    }
+    app_state.store.clear();
+    info!("Cleared the stateful store through the admin route");
+    HttpResponse::NoContent().finish()
+}
+
+// Stops the server for test harnesses that can't easily send it a signal.
+// The response goes out first, as the server drains in-flight requests
+// before stopping. Recordings are flushed as they are written, so there is
+// nothing left to save
+pub async fn shutdown(req: HttpRequest, app_state: web::Data<Arc<AppState>>) -> HttpResponse {
+    if let Some(rejection) = reject_unauthorized(&req, &app_state) {
+        return rejection;
+    }
+    info!("Shutdown requested through the admin route");
+    app_state.request_shutdown();
+    HttpResponse::Accepted().json(serde_json::json!({ "status": "shutting down" }))
+}
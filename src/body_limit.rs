@@ -0,0 +1,27 @@
+use crate::data::app::AppState;
+use actix_web::dev::ServiceResponse;
+use actix_web::middleware::ErrorHandlerResponse;
+use actix_web::{web, HttpResponse, Result};
+use std::sync::Arc;
+
+// Bodies over `--max-body-size` fail to extract before the handler runs, with
+// a plain text 413 this turns into a JSON one. A 413 served by a stub carries
+// no error and is left as it is
+pub fn payload_too_large<B>(res: ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> {
+    if res.response().error().is_none() {
+        return Ok(ErrorHandlerResponse::Response(res.map_into_left_body()));
+    }
+
+    let limit = res
+        .request()
+        .app_data::<web::Data<Arc<AppState>>>()
+        .map(|app_state| app_state.settings.max_body_size);
+    let (req, _) = res.into_parts();
+    let response = HttpResponse::PayloadTooLarge().json(serde_json::json!({
+        "error": "Payload too large",
+        "limit": limit,
+    }));
+    Ok(ErrorHandlerResponse::Response(
+        ServiceResponse::new(req, response).map_into_right_body(),
+    ))
+}
@@ -0,0 +1,59 @@
+use actix_web::http::StatusCode;
+use log::warn;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// A circuit breaker to trip, from the `x-mock-circuit` extension of an
+// operation: its first `success_count` calls are served as usual, the next
+// `fail_count` are answered with `fail_status`, 503 by default, and the ones
+// after that are served as usual again. The count starts over when the spec
+// is reloaded
+#[derive(Debug)]
+pub struct Circuit {
+    pub success_count: u64,
+    pub fail_count: u64,
+    pub fail_status: StatusCode,
+    calls: AtomicU64,
+}
+
+impl Circuit {
+    pub fn new(success_count: u64, fail_count: u64, fail_status: StatusCode) -> Self {
+        Circuit {
+            success_count,
+            fail_count,
+            fail_status,
+            calls: AtomicU64::new(0),
+        }
+    }
+
+    // Both counts are required, a malformed extension being ignored
+    pub fn from_extension(extension: &Value) -> Option<Self> {
+        let count = |name: &str| extension.get(name).and_then(Value::as_u64);
+        let (Some(success_count), Some(fail_count)) = (count("success_count"), count("fail_count"))
+        else {
+            warn!("x-mock-circuit needs success_count and fail_count, ignoring it");
+            return None;
+        };
+        let fail_status = match extension.get("fail_status") {
+            None => StatusCode::SERVICE_UNAVAILABLE,
+            Some(status) => match status
+                .as_u64()
+                .and_then(|status| u16::try_from(status).ok())
+                .and_then(|status| StatusCode::from_u16(status).ok())
+            {
+                Some(status) => status,
+                None => {
+                    warn!("x-mock-circuit has an invalid fail_status, ignoring it");
+                    return None;
+                }
+            },
+        };
+        Some(Circuit::new(success_count, fail_count, fail_status))
+    }
+
+    // Counts the call, and tells whether it falls while the circuit is open
+    pub fn trip(&self) -> bool {
+        let call = self.calls.fetch_add(1, Ordering::Relaxed);
+        call >= self.success_count && call - self.success_count < self.fail_count
+    }
+}
@@ -1,44 +1,321 @@
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock, RwLockReadGuard};
 
-use openapiv3::OpenAPI;
+use actix_web::cookie::Cookie;
+use actix_web::http::header::HeaderMap;
+use actix_web::http::StatusCode;
+use openapiv3::{OpenAPI, ReferenceOr, Schema};
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+use tokio::sync::Notify;
+
+use crate::circuit::Circuit;
+use crate::data::metrics::Metrics;
+use crate::data::rate_limiter::RateLimiter;
+use crate::data::recorder::Recorder;
+use crate::data::settings::Settings;
+use crate::data::store::ResourceStore;
+use crate::errors::AppError;
+use crate::formats::xml::XmlShape;
+use crate::pagination::Pagination;
+use crate::proxy::Upstream;
+use crate::spec_source::SpecSource;
+use crate::transactions::{parse_openapi_spec, BuiltEndpoints, SkippedOperation};
+
+// A credential that has to be present for a security scheme to be satisfied
+#[derive(Debug, Clone, PartialEq)]
+pub enum Credential {
+    Header(String),
+    Query(String),
+    Cookie(String),
+}
 
 pub struct EndpointHandler {
     pub path: String,
     pub method: String,
+    // Invoked through `POST /api/op/{operationId}` when the spec names the operation
+    pub operation_id: Option<String>,
+    // The response key as declared in the spec, e.g. `200` or `2XX`
     pub response_code: String,
-    pub response_body: String,
+    pub status: StatusCode,
+    pub response_body: Value,
+    // The schema of the JSON body, which `--self-check` holds `response_body` to
+    pub response_schema: Option<ReferenceOr<Schema>>,
+    // How the body is laid out when served as XML, from the `xml` annotations
+    // of its schema. `None` when the response declares no XML media type
+    pub response_xml: Option<XmlShape>,
     pub path_params: Vec<String>,
+    // The trailing path parameter marked `x-mock-greedy`, which matches
+    // across slashes
+    pub greedy_param: Option<String>,
+    // From `x-mock-delay`, in place of the `--latency-ms` of the status served
+    pub delay_ms: Option<u64>,
+    pub headers: Vec<(String, String)>,
+    pub content_types: Vec<String>,
+    // Named examples, selectable through `Prefer: example=<name>`
+    pub examples: Vec<(String, Value)>,
+    pub request_schema: Option<ReferenceOr<Schema>>,
+    pub request_body_required: bool,
+    // How an XML request body is read back, its scalars taking the types of
+    // the schema. `None` when the operation accepts no XML body
+    pub request_xml: Option<XmlShape>,
+    // Query parameters the operation or its path item declare as required,
+    // checked under `--validate-requests`
+    pub required_query: Vec<String>,
+    // Schemas of the path parameters, which the captured values are held to
+    // under `--validate-requests`
+    pub path_param_schemas: Vec<(String, ReferenceOr<Schema>)>,
+    // Alternative sets of credentials, any one of which authorizes a request.
+    // Empty when the operation is unprotected
+    pub security: Vec<Vec<Credential>>,
+    // Set by the `x-no-chaos` extension, which keeps `--error-rate` away from the operation
+    pub chaos_exempt: bool,
+    // Fails a run of calls after a run of successes, from `x-mock-circuit`.
+    // Only set on the successful responses, which the calls are counted on
+    pub circuit: Option<Circuit>,
+    pub pagination: Option<Pagination>,
+    // Milliseconds between the messages pushed over `/ws`, `None` unless the
+    // operation has the `x-websocket` extension
+    pub websocket_interval_ms: Option<u64>,
+    // Set when the response declares `text/event-stream`, its body then being
+    // streamed as server-sent events to the clients negotiating that type
+    pub event_stream: bool,
+    // Bodies served in turn by successive calls, from `x-mock-sequence`
+    pub sequence: Vec<Value>,
+    pub sequence_position: AtomicUsize,
+    // Bodies cycled through under `--enum-strategy round-robin`, each taking
+    // the next value of every enum. Empty when the body has no enum to cycle
+    pub enum_variants: Vec<Value>,
+    pub enum_position: AtomicUsize,
+    // Query parameter values a request has to carry for this response to be
+    // preferred, from `x-mock-match`
+    pub query_match: Vec<(String, String)>,
+    // Header values a request has to carry for this response to be
+    // preferred, from `x-mock-match-headers`
+    pub header_match: Vec<(String, String)>,
+    // Set for 204 and 304 responses, which are sent without a body
+    pub empty_body: bool,
+    // Body read from `--responses-dir`, served in place of the stub
+    pub response_override: Option<Value>,
+    // Bytes served when an `application/octet-stream` or `image/*` content
+    // type is negotiated, `None` when the response declares neither
+    pub binary_body: Option<Vec<u8>>,
+    // Set on every response, from `x-mock-set-cookie`
+    pub cookies: Vec<Cookie<'static>>,
+}
+
+impl EndpointHandler {
+    // Advances through the `x-mock-sequence` bodies, starting over after the last
+    pub fn next_in_sequence(&self) -> Option<&Value> {
+        if self.sequence.is_empty() {
+            return None;
+        }
+        let position = self.sequence_position.fetch_add(1, Ordering::Relaxed);
+        self.sequence.get(position % self.sequence.len())
+    }
+
+    // Advances through the `--enum-strategy round-robin` bodies
+    pub fn next_enum_variant(&self) -> Option<&Value> {
+        if self.enum_variants.is_empty() {
+            return None;
+        }
+        let position = self.enum_position.fetch_add(1, Ordering::Relaxed);
+        self.enum_variants.get(position % self.enum_variants.len())
+    }
+
+    // Whether the query satisfies every `x-mock-match` condition. Always the
+    // case for responses without any
+    pub fn matches_query(&self, query: &HashMap<String, String>) -> bool {
+        self.query_match
+            .iter()
+            .all(|(name, value)| query.get(name) == Some(value))
+    }
+
+    // Whether the headers satisfy every `x-mock-match-headers` condition.
+    // Names are compared without case, values as they are sent
+    pub fn matches_headers(&self, headers: &HeaderMap) -> bool {
+        self.header_match.iter().all(|(name, value)| {
+            headers
+                .get_all(name.as_str())
+                .any(|sent| sent.to_str().is_ok_and(|sent| sent.trim() == value))
+        })
+    }
+
+    // Whether the response is only served to requests meeting conditions
+    pub fn is_conditional(&self) -> bool {
+        !self.query_match.is_empty() || !self.header_match.is_empty()
+    }
+}
+
+// Progress of the latest spec reload, reported by `/health/ready`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReloadState {
+    Idle,
+    InProgress,
+    Failed(String),
 }
 
 pub struct AppState {
-    pub endpoints: Vec<EndpointHandler>,
-    pub openapi_spec: OpenAPI,
+    // All three are swapped out together when the spec is reloaded
+    pub endpoints: RwLock<Vec<EndpointHandler>>,
+    pub openapi_spec: RwLock<OpenAPI>,
+    // What of the spec no endpoint was built for, reported by `/api/diagnostics`
+    pub skipped: RwLock<Vec<SkippedOperation>>,
+    pub settings: Settings,
+    pub store: ResourceStore,
+    pub recorder: Option<Recorder>,
+    pub upstream: Option<Upstream>,
+    pub metrics: Metrics,
+    pub rate_limiter: Option<RateLimiter>,
+    // Where the specs were loaded from, re-read by `POST /admin/reload`
+    pub sources: Vec<SpecSource>,
+    // Served to requests matching no endpoint, from `--not-found-body`
+    pub not_found_body: Option<Value>,
+    reload_state: Mutex<ReloadState>,
+    // Notified by `POST /admin/shutdown`, which `run_server` waits on along
+    // with the signals
+    shutdown: Notify,
+    // Source of the latency jitter, seeded from `--seed` when given
+    rng: Mutex<StdRng>,
 }
 
 impl AppState {
-    pub fn new(endpoints: Vec<EndpointHandler>, openapi_spec: OpenAPI) -> Self {
+    pub fn new(mut endpoints: Vec<EndpointHandler>, openapi_spec: OpenAPI) -> Self {
+        sort_by_specificity(&mut endpoints);
         AppState {
-            endpoints,
-            openapi_spec,
+            endpoints: RwLock::new(endpoints),
+            openapi_spec: RwLock::new(openapi_spec),
+            skipped: RwLock::new(Vec::new()),
+            settings: Settings::default(),
+            store: ResourceStore::default(),
+            recorder: None,
+            upstream: None,
+            metrics: Metrics::default(),
+            rate_limiter: None,
+            sources: Vec::new(),
+            not_found_body: None,
+            reload_state: Mutex::new(ReloadState::Idle),
+            shutdown: Notify::new(),
+            rng: Mutex::new(StdRng::from_os_rng()),
         }
     }
 
-    pub fn new_with_spec_path(endpoints: Vec<EndpointHandler>, openapi_spec_file: &Path) -> Self {
-        let openapi_spec = Self::get_openapi_spec(openapi_spec_file);
-        AppState {
-            endpoints,
-            openapi_spec,
+    // The state for the given configuration and what was built from the spec,
+    // the optional parts being added through the `with_*` setters
+    pub fn from_config(settings: Settings, built: BuiltEndpoints, openapi_spec: OpenAPI) -> Self {
+        AppState::new(built.endpoints, openapi_spec)
+            .with_settings(settings)
+            .with_skipped(built.skipped)
+    }
+
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        if let Some(seed) = settings.seed {
+            self.rng = Mutex::new(StdRng::seed_from_u64(seed));
+        }
+        self.rate_limiter = settings.rate_limit.map(RateLimiter::new);
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_skipped(self, skipped: Vec<SkippedOperation>) -> Self {
+        *self.skipped.write().unwrap() = skipped;
+        self
+    }
+
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn with_upstream(mut self, upstream: Upstream) -> Self {
+        self.upstream = Some(upstream);
+        self
+    }
+
+    pub fn with_sources(mut self, sources: Vec<SpecSource>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    pub fn with_not_found_body(mut self, body: Value) -> Self {
+        self.not_found_body = Some(body);
+        self
+    }
+
+    pub fn new_with_spec_path(
+        endpoints: Vec<EndpointHandler>,
+        openapi_spec_file: &Path,
+    ) -> Result<Self, AppError> {
+        let source = SpecSource::File(openapi_spec_file.to_path_buf());
+        let content = source.read()?;
+        let openapi_spec = parse_openapi_spec(&content, source.format(&content))?;
+        Ok(Self::new(endpoints, openapi_spec))
+    }
+
+    // Adds up to `--latency-jitter-ms` of random delay to a response's base delay
+    pub fn jittered_delay(&self, delay_ms: u64) -> u64 {
+        let jitter_ms = self.settings.latency_jitter_ms;
+        if jitter_ms == 0 {
+            return delay_ms;
         }
+        let jitter = self.rng.lock().unwrap().random_range(0..=jitter_ms);
+        delay_ms.saturating_add(jitter)
+    }
+
+    // Decides, with the `--error-rate` probability, whether a request fails
+    pub fn inject_failure(&self) -> bool {
+        let error_rate = self.settings.error_rate;
+        error_rate > 0.0 && self.rng.lock().unwrap().random_bool(error_rate.min(1.0))
     }
 
-    fn get_openapi_spec(path: &Path) -> OpenAPI {
-        let yaml_content = std::fs::read_to_string(path).expect("Failed to read spec file");
-        let openapi_spec: OpenAPI =
-            serde_yaml::from_str(&yaml_content).expect("Failed to parse spec");
-        openapi_spec
+    pub fn choose<'a, T>(&self, items: &'a [T]) -> Option<&'a T> {
+        items.choose(&mut *self.rng.lock().unwrap())
     }
 
-    pub fn get_spec(&self) -> &OpenAPI {
-        &self.openapi_spec
+    pub fn get_spec(&self) -> RwLockReadGuard<'_, OpenAPI> {
+        self.openapi_spec.read().unwrap()
     }
+
+    pub fn reload_state(&self) -> ReloadState {
+        self.reload_state.lock().unwrap().clone()
+    }
+
+    pub fn set_reload_state(&self, state: ReloadState) {
+        *self.reload_state.lock().unwrap() = state;
+    }
+
+    // The request is kept until `shutdown_requested` is waited on, so one
+    // made before the server started waiting isn't lost
+    pub fn request_shutdown(&self) {
+        self.shutdown.notify_one();
+    }
+
+    pub async fn shutdown_requested(&self) {
+        self.shutdown.notified().await;
+    }
+
+    pub fn replace(&self, built: BuiltEndpoints, openapi_spec: OpenAPI) {
+        let mut endpoints = built.endpoints;
+        sort_by_specificity(&mut endpoints);
+        *self.endpoints.write().unwrap() = endpoints;
+        *self.openapi_spec.write().unwrap() = openapi_spec;
+        *self.skipped.write().unwrap() = built.skipped;
+    }
+}
+
+// Requests are served by the first matching endpoint, so paths with literal
+// segments have to come before templated ones, e.g. `/users/me` before
+// `/users/{id}`. The sort is stable, which keeps the spec order otherwise
+fn sort_by_specificity(endpoints: &mut [EndpointHandler]) {
+    endpoints.sort_by_cached_key(|endpoint| {
+        endpoint
+            .path
+            .split('/')
+            .map(|segment| segment.contains('{'))
+            .collect::<Vec<bool>>()
+    });
 }
@@ -1,4 +1,11 @@
-use clap::Parser;
+use crate::access_log::LogFormat;
+use crate::data::config_file::ConfigFile;
+use crate::data::latency::LatencyProfile;
+use crate::endpoint_list::ListFormat;
+use crate::errors::AppError;
+use crate::generator::EnumStrategy;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use std::path::PathBuf;
 
 // Command line arguments for the server
 #[derive(Parser, Debug)]
@@ -7,15 +14,357 @@ use clap::Parser;
     about = "Generates a server from an OpenAPI spec"
 )]
 pub struct Args {
-    /// Path to the OpenAPI YAML specification file
+    /// TOML or YAML file providing defaults for the flags below. Flags given
+    /// on the command line take precedence over it, and it over the built-in
+    /// defaults
+    #[clap(long)]
+    pub config: Option<PathBuf>,
+
+    /// Path or http(s) URL of the OpenAPI specification, in YAML or JSON.
+    /// Can be repeated to serve several specs as one
     #[clap(long, default_value = "api-spec.yaml")]
-    pub spec: String,
+    pub spec: Vec<String>,
 
     /// Port to listen on
     #[clap(short, long, default_value = "8080")]
     pub port: u16,
 
-    /// Host to bind to
-    #[clap(short = 's', long = "server", default_value = "127.0.0.1")]
-    pub host: String,
+    /// Host to bind to, IPv4 or IPv6. Repeat it, or give a comma separated
+    /// list, to listen on several, as in `--server 127.0.0.1,::1`
+    #[clap(
+        short = 's',
+        long = "server",
+        alias = "host",
+        default_value = "127.0.0.1",
+        value_delimiter = ','
+    )]
+    pub host: Vec<String>,
+
+    /// Unix domain socket to listen on instead of `--server` and `--port`.
+    /// A stale socket left at the path is replaced
+    #[clap(long = "unix-socket", conflicts_with_all = ["tls_cert", "tls_key"])]
+    pub unix_socket: Option<PathBuf>,
+
+    /// Delay in milliseconds applied to every response, or to the responses
+    /// of each status class as in `2xx=10,5xx=2000`. Overridden per operation
+    /// by the `x-mock-delay` extension
+    #[clap(long = "latency-ms", default_value = "0")]
+    pub latency_ms: LatencyProfile,
+
+    /// Milliseconds between the events streamed by `text/event-stream`
+    /// responses, which repeat their stub body
+    #[clap(long = "sse-interval", default_value = "1000")]
+    pub sse_interval_ms: u64,
+
+    /// Events sent before a `text/event-stream` response ends. Streams go on
+    /// until the client disconnects when omitted
+    #[clap(long = "sse-max-events", value_parser = clap::value_parser!(u64).range(1..))]
+    pub sse_max_events: Option<u64>,
+
+    /// Upper bound in milliseconds of a random delay added to every response
+    #[clap(long = "latency-jitter-ms", default_value = "0")]
+    pub latency_jitter_ms: u64,
+
+    /// Seed for the random jitter and the fake names, emails and dates of
+    /// generated responses, to reproduce them across runs
+    #[clap(long)]
+    pub seed: Option<u64>,
+
+    /// Probability, between 0.0 and 1.0, of answering a request with an injected
+    /// server error. Operations with the `x-no-chaos` extension are never affected
+    #[clap(long = "error-rate", default_value = "0.0", value_parser = parse_probability)]
+    pub error_rate: f64,
+
+    /// Path every route is mounted under, e.g. `/mock` serves `/mock/api/users`
+    /// and `/mock/docs`
+    #[clap(long = "base-path", alias = "prefix", default_value = "", value_parser = parse_base_path)]
+    pub base_path: String,
+
+    /// Token enabling the admin routes such as `POST /admin/reload`, which
+    /// have to send it in the `X-Admin-Token` header
+    #[clap(long = "admin-token")]
+    pub admin_token: Option<String>,
+
+    /// Add `POST /admin/shutdown`, which stops the server once the requests
+    /// in flight are served. Like the other admin routes it needs `--admin-token`
+    #[clap(long = "enable-admin")]
+    pub enable_admin: bool,
+
+    /// Reload the spec whenever the file changes
+    #[clap(long)]
+    pub watch: bool,
+
+    /// Treat spec validation warnings, such as examples contradicting their
+    /// schema, and operations declared by more than one spec, as errors
+    #[clap(long)]
+    pub strict: bool,
+
+    /// Check every generated stub against its response schema on boot and
+    /// report the mismatches, which stop the server under `--strict`
+    #[clap(long = "self-check")]
+    pub self_check: bool,
+
+    /// Persist resources created through POST/PUT and serve them back. The
+    /// store can be read and emptied through `/admin/state`
+    #[clap(long)]
+    pub stateful: bool,
+
+    /// Reject requests that don't conform to the operation's declared inputs:
+    /// its request body and required query parameters
+    #[clap(long = "validate-requests")]
+    pub validate_requests: bool,
+
+    /// Return 401 when a protected operation is called without credentials.
+    /// Only their presence is checked, not their validity
+    #[clap(long = "enforce-auth")]
+    pub enforce_auth: bool,
+
+    /// Append every handled request to this file, one JSON object per line
+    #[clap(long)]
+    pub record: Option<PathBuf>,
+
+    /// Replay a `--record` file against `--replay-target` and report whether
+    /// every request got its recorded status, instead of starting the server
+    #[clap(long)]
+    pub replay: Option<PathBuf>,
+
+    /// Base URL the replayed paths are appended to. Defaults to the `/api`
+    /// prefix of the server at `--server` and `--port`
+    #[clap(long = "replay-target")]
+    pub replay_target: Option<String>,
+
+    /// Forward requests that match no endpoint to this http(s) base URL
+    #[clap(long = "proxy-upstream")]
+    pub proxy_upstream: Option<String>,
+
+    /// Fill `{{id}}`, `{{query.name}}` and `{{body.user.id}}` placeholders in
+    /// response bodies from the request's path parameters, query string and
+    /// body
+    #[clap(long)]
+    pub templates: bool,
+
+    /// Load the Swagger UI of the docs page from unpkg.com instead of the
+    /// copy built into the server
+    #[clap(long = "swagger-cdn")]
+    pub swagger_cdn: bool,
+
+    /// Show the bodies the stubs serve as the examples of the served OpenAPI
+    /// document, for the Swagger UI to display what the mock returns
+    #[clap(long = "sync-examples")]
+    pub sync_examples: bool,
+
+    /// Requests per second allowed to each client, over which they get 429
+    #[clap(long = "rate-limit", value_parser = clap::value_parser!(u32).range(1..))]
+    pub rate_limit: Option<u32>,
+
+    /// Tell `--rate-limit` clients apart by their `X-Forwarded-For` address,
+    /// for when the server is behind a proxy
+    #[clap(long = "trust-forwarded")]
+    pub trust_forwarded: bool,
+
+    /// Compress responses with gzip, deflate, brotli or zstd, as the
+    /// client's `Accept-Encoding` allows
+    #[clap(long)]
+    pub compress: bool,
+
+    /// Largest request body accepted, in bytes. Bigger ones are answered
+    /// with 413 without reaching any endpoint
+    #[clap(
+        long = "max-body-size",
+        default_value = "262144",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub max_body_size: u64,
+
+    /// Directory of JSON bodies served instead of the generated stubs,
+    /// named `<method>_<path>_<status>.json`, e.g. `get_users_id_200.json`
+    /// for `GET /users/{id}`
+    #[clap(long = "responses-dir")]
+    pub responses_dir: Option<PathBuf>,
+
+    /// Size in bytes of the zeros served for `application/octet-stream` and
+    /// `image/*` responses, unless `--responses-dir` has a
+    /// `<method>_<path>_<status>.bin` file for them
+    #[clap(long = "binary-size", default_value = "1024")]
+    pub binary_size: usize,
+
+    /// Comma separated tags of the operations to serve, the others being left
+    /// out. Every operation is served when omitted
+    #[clap(long = "tags", value_delimiter = ',')]
+    pub tags: Vec<String>,
+
+    /// JSON file with the body answering requests that match no endpoint,
+    /// instead of `{"error": "Endpoint not found", ...}`
+    #[clap(long = "not-found-body")]
+    pub not_found_body: Option<PathBuf>,
+
+    /// Status of the responses to requests that match no endpoint
+    #[clap(
+        long = "not-found-status",
+        default_value = "404",
+        value_parser = clap::value_parser!(u16).range(100..600)
+    )]
+    pub not_found_status: u16,
+
+    /// Number of items in the collections served by paginated operations,
+    /// those taking a `page` or `limit` query parameter
+    #[clap(long = "array-size", default_value = "10")]
+    pub array_size: usize,
+
+    /// How deep generated objects and arrays nest before being served empty.
+    /// Schemas referring back to themselves stop at the first repetition anyway
+    #[clap(long = "max-depth", default_value = "16")]
+    pub max_depth: usize,
+
+    /// Which value of an `enum` generated bodies take: the `first` declared,
+    /// a `random` one (reproducible with `--seed`), or the next one on every
+    /// call to the endpoint with `round-robin`
+    #[clap(long = "enum-strategy", value_enum, default_value = "first")]
+    pub enum_strategy: EnumStrategy,
+
+    /// Origin allowed by CORS, e.g. `https://app.example.com`. Can be repeated.
+    /// Any origin is allowed when omitted
+    #[clap(long = "cors-origin")]
+    pub cors_origins: Vec<String>,
+
+    /// Comma separated methods allowed by CORS. Any method is allowed when omitted
+    #[clap(long = "cors-methods", value_delimiter = ',')]
+    pub cors_methods: Vec<String>,
+
+    /// Comma separated request headers allowed by CORS. Any header is allowed when omitted
+    #[clap(long = "cors-headers", value_delimiter = ',')]
+    pub cors_headers: Vec<String>,
+
+    /// Allow credentialed CORS requests, sending `Access-Control-Allow-Credentials`.
+    /// The request's `Origin` is then echoed back rather than `*`, which
+    /// browsers refuse for requests carrying cookies
+    #[clap(long = "cors-credentials")]
+    pub cors_credentials: bool,
+
+    /// Format of the log lines, including the access log written per request
+    #[clap(long = "log-format", value_enum, default_value = "text")]
+    pub log_format: LogFormat,
+
+    /// Only log errors. Takes precedence over `RUST_LOG`
+    #[clap(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Log more: `-v` this server's debug messages, `-vv` every crate's and
+    /// `-vvv` everything down to trace. Takes precedence over `RUST_LOG`
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Add `X-Mock-Matched-Path`, `X-Mock-Matched-Method` and `X-Mock-Status-Source`
+    /// headers to stub responses, telling which endpoint answered and why
+    #[clap(long = "debug-headers")]
+    pub debug_headers: bool,
+
+    /// Indent the JSON of stub responses, which are compact otherwise
+    #[clap(long = "pretty")]
+    pub pretty: bool,
+
+    /// Send this `Server` header on every response
+    #[clap(long = "server-header")]
+    pub server_header: Option<String>,
+
+    /// Send this fixed `Date` on every response instead of the current time,
+    /// e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+    #[clap(long = "freeze-date", value_parser = parse_http_date)]
+    pub freeze_date: Option<String>,
+
+    /// Print the endpoints built from the spec and exit without serving them
+    #[clap(long = "list")]
+    pub list: bool,
+
+    /// Format of the `--list` output
+    #[clap(
+        long = "format",
+        value_enum,
+        default_value = "table",
+        requires = "list"
+    )]
+    pub format: ListFormat,
+
+    /// Write a Postman v2.1 collection of the endpoints, with their stub
+    /// responses as examples, to this file and exit without serving them
+    #[clap(long = "export-postman")]
+    pub export_postman: Option<PathBuf>,
+
+    /// Seconds to wait for in-flight requests to finish when shutting down
+    #[clap(long = "shutdown-timeout", default_value = "30")]
+    pub shutdown_timeout: u64,
+
+    /// Number of worker threads, one per CPU when omitted
+    #[clap(long = "workers", value_parser = clap::value_parser!(u64).range(1..))]
+    pub workers: Option<u64>,
+
+    /// Seconds an idle connection is kept open for its next request, 0
+    /// closing every connection after its response
+    #[clap(long = "keep-alive", default_value = "5")]
+    pub keep_alive: u64,
+
+    /// PEM certificate chain to serve HTTPS with, requires `--tls-key`
+    #[clap(long = "tls-cert")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM private key matching `--tls-cert`
+    #[clap(long = "tls-key")]
+    pub tls_key: Option<PathBuf>,
+
+    /// Accept cleartext HTTP/2 (h2c) next to HTTP/1.1. HTTPS connections
+    /// negotiate HTTP/2 through ALPN whether or not this is set
+    #[clap(long = "http2", conflicts_with = "unix_socket")]
+    pub http2: bool,
+}
+
+impl Args {
+    // Parses the command line, layered over the `--config` file if there is one
+    pub fn load() -> Result<Self, AppError> {
+        Self::load_from(std::env::args_os())
+    }
+
+    pub fn load_from<I, T>(args: I) -> Result<Self, AppError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let matches = Self::command().get_matches_from(args);
+        let mut args = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        if let Some(path) = args.config.clone() {
+            ConfigFile::load(&path)?.apply(&mut args, &matches)?;
+        }
+        Ok(args)
+    }
+}
+
+pub(crate) fn parse_probability(value: &str) -> Result<f64, String> {
+    let probability: f64 = value
+        .parse()
+        .map_err(|_| format!("{} is not a number", value))?;
+    if !(0.0..=1.0).contains(&probability) {
+        return Err(format!("{} is not between 0.0 and 1.0", value));
+    }
+    Ok(probability)
+}
+
+// An RFC 1123 date, written back the way HTTP dates are, in GMT
+fn parse_http_date(value: &str) -> Result<String, String> {
+    let date = chrono::DateTime::parse_from_rfc2822(value.trim())
+        .map_err(|_| format!("{} is not an RFC 1123 date", value))?;
+    Ok(date
+        .with_timezone(&chrono::Utc)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string())
+}
+
+// Normalizes to a leading slash and no trailing one, `/` meaning no prefix
+fn parse_base_path(value: &str) -> Result<String, String> {
+    let path = value.trim().trim_matches('/');
+    if path.contains(['{', '}', '?', '#']) {
+        return Err(format!("{} is not a plain path", value));
+    }
+    if path.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(format!("/{}", path))
 }
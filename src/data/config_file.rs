@@ -0,0 +1,117 @@
+use crate::data::cli_args::{parse_probability, Args};
+use crate::data::latency::LatencyProfile;
+use crate::errors::AppError;
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::Deserialize;
+use std::path::Path;
+
+// Settings read from `--config`, named as the flags they stand for. Every
+// field is optional, and one given on the command line wins over the file
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ConfigFile {
+    pub spec: Option<OneOrMany>,
+    pub port: Option<u16>,
+    #[serde(alias = "server")]
+    pub host: Option<OneOrMany>,
+    pub latency_ms: Option<Latency>,
+    pub latency_jitter_ms: Option<u64>,
+    pub error_rate: Option<f64>,
+    pub cors_origins: Option<Vec<String>>,
+    pub cors_methods: Option<Vec<String>>,
+    pub cors_headers: Option<Vec<String>>,
+    pub cors_credentials: Option<bool>,
+}
+
+// `spec` and `host` take one value or a list of them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(value) => vec![value],
+            OneOrMany::Many(values) => values,
+        }
+    }
+}
+
+// `latency-ms` takes a number, or the delays of status classes as on the
+// command line
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Latency {
+    Uniform(u64),
+    ByClass(String),
+}
+
+impl ConfigFile {
+    // TOML for `.toml` files, YAML (or JSON) for anything else
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let content = std::fs::read_to_string(path)?;
+        let is_toml = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+
+        let config = if is_toml {
+            toml::from_str(&content).map_err(|e| {
+                AppError::ConfigError(format!("{}: {}", path.display(), e.message()))
+            })?
+        } else {
+            serde_yaml::from_str(&content)
+                .map_err(|e| AppError::ConfigError(format!("{}: {}", path.display(), e)))?
+        };
+        Ok(config)
+    }
+
+    // Fills in the arguments that weren't given on the command line
+    pub fn apply(self, args: &mut Args, matches: &ArgMatches) -> Result<(), AppError> {
+        let from_file = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+
+        if let (Some(specs), true) = (self.spec, from_file("spec")) {
+            args.spec = specs.into_vec();
+        }
+        if let (Some(port), true) = (self.port, from_file("port")) {
+            args.port = port;
+        }
+        if let (Some(host), true) = (self.host, from_file("host")) {
+            args.host = host.into_vec();
+            if args.host.is_empty() {
+                return Err(AppError::ConfigError("host: no address given".to_string()));
+            }
+        }
+        if let (Some(latency_ms), true) = (self.latency_ms, from_file("latency_ms")) {
+            args.latency_ms = match latency_ms {
+                Latency::Uniform(delay_ms) => LatencyProfile::uniform(delay_ms),
+                Latency::ByClass(profile) => profile
+                    .parse()
+                    .map_err(|e| AppError::ConfigError(format!("latency-ms: {}", e)))?,
+            };
+        }
+        if let (Some(jitter_ms), true) = (self.latency_jitter_ms, from_file("latency_jitter_ms")) {
+            args.latency_jitter_ms = jitter_ms;
+        }
+        if let (Some(error_rate), true) = (self.error_rate, from_file("error_rate")) {
+            args.error_rate = parse_probability(&error_rate.to_string())
+                .map_err(|e| AppError::ConfigError(format!("error-rate: {}", e)))?;
+        }
+        if let (Some(origins), true) = (self.cors_origins, from_file("cors_origins")) {
+            args.cors_origins = origins;
+        }
+        if let (Some(methods), true) = (self.cors_methods, from_file("cors_methods")) {
+            args.cors_methods = methods;
+        }
+        if let (Some(headers), true) = (self.cors_headers, from_file("cors_headers")) {
+            args.cors_headers = headers;
+        }
+        if let (Some(credentials), true) = (self.cors_credentials, from_file("cors_credentials")) {
+            args.cors_credentials = credentials;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use actix_cors::Cors;
+use actix_web::http::header::HeaderName;
+use actix_web::http::{Method, Uri};
+
+use crate::data::cli_args::Args;
+use crate::errors::AppError;
+
+// The CORS policy requested on the command line, validated up front because
+// `Cors` only reports bad values once the server is already running.
+// Each list left empty allows anything, as the server did before these flags
+#[derive(Debug, Clone, Default)]
+pub struct CorsConfig {
+    pub origins: Vec<String>,
+    pub methods: Vec<Method>,
+    pub headers: Vec<HeaderName>,
+    pub credentials: bool,
+}
+
+impl CorsConfig {
+    pub fn from_args(args: &Args) -> Result<Self, AppError> {
+        for origin in &args.cors_origins {
+            validate_origin(origin)?;
+        }
+
+        let methods = args
+            .cors_methods
+            .iter()
+            .map(|method| {
+                Method::from_bytes(method.trim().to_uppercase().as_bytes())
+                    .map_err(|_| AppError::ConfigError(format!("invalid CORS method: {}", method)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let headers = args
+            .cors_headers
+            .iter()
+            .map(|header| {
+                HeaderName::from_bytes(header.trim().as_bytes())
+                    .map_err(|_| AppError::ConfigError(format!("invalid CORS header: {}", header)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(CorsConfig {
+            origins: args.cors_origins.clone(),
+            methods,
+            headers,
+            credentials: args.cors_credentials,
+        })
+    }
+
+    pub fn build(&self) -> Cors {
+        let mut cors = Cors::default();
+
+        // Every origin allowed is answered with the request's own, as
+        // credentialed requests need and as the server always did
+        if self.credentials {
+            cors = cors.supports_credentials();
+        }
+        if self.origins.is_empty() {
+            cors = cors.allow_any_origin();
+        }
+        for origin in &self.origins {
+            cors = cors.allowed_origin(origin);
+        }
+
+        cors = if self.methods.is_empty() {
+            cors.allow_any_method()
+        } else {
+            cors.allowed_methods(self.methods.clone())
+        };
+
+        // Preflights allowing any header echo the requested ones, as `*`
+        // doesn't cover credentialed requests either
+        if self.headers.is_empty() {
+            cors.allow_any_header()
+        } else {
+            cors.allowed_headers(self.headers.clone())
+        }
+    }
+}
+
+// Origins are compared verbatim, so anything but `scheme://host[:port]` can never match
+fn validate_origin(origin: &str) -> Result<(), AppError> {
+    let invalid = || AppError::ConfigError(format!("invalid CORS origin: {}", origin));
+
+    let uri: Uri = origin.parse().map_err(|_| invalid())?;
+    let has_path = uri
+        .path_and_query()
+        .is_some_and(|path| path.as_str() != "/");
+    if uri.scheme().is_none() || uri.host().is_none() || has_path || origin.ends_with('/') {
+        return Err(invalid());
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use crate::circuit::Circuit;
+use crate::data::app::{Credential, EndpointHandler};
+use crate::formats::xml::XmlShape;
+use crate::pagination::Pagination;
+use actix_web::cookie::Cookie;
+use actix_web::http::StatusCode;
+use openapiv3::{ReferenceOr, Schema};
+use serde_json::Value;
+use std::sync::atomic::AtomicUsize;
+
+// Builds an endpoint one part at a time, everything but what identifies it
+// starting out empty: no body, schema, parameter, header or matcher, and no
+// extension applied
+pub struct EndpointHandlerBuilder {
+    endpoint: EndpointHandler,
+}
+
+impl EndpointHandler {
+    pub fn builder(
+        path: &str,
+        method: &str,
+        response_code: &str,
+        status: StatusCode,
+    ) -> EndpointHandlerBuilder {
+        EndpointHandlerBuilder {
+            endpoint: EndpointHandler {
+                path: path.to_string(),
+                method: method.to_string(),
+                response_code: response_code.to_string(),
+                status,
+                operation_id: None,
+                response_body: Value::Null,
+                response_schema: None,
+                response_xml: None,
+                path_params: Vec::new(),
+                greedy_param: None,
+                delay_ms: None,
+                headers: Vec::new(),
+                content_types: Vec::new(),
+                examples: Vec::new(),
+                request_schema: None,
+                request_body_required: false,
+                request_xml: None,
+                required_query: Vec::new(),
+                path_param_schemas: Vec::new(),
+                security: Vec::new(),
+                chaos_exempt: false,
+                circuit: None,
+                pagination: None,
+                websocket_interval_ms: None,
+                event_stream: false,
+                sequence: Vec::new(),
+                sequence_position: AtomicUsize::new(0),
+                enum_variants: Vec::new(),
+                enum_position: AtomicUsize::new(0),
+                query_match: Vec::new(),
+                header_match: Vec::new(),
+                empty_body: false,
+                response_override: None,
+                binary_body: None,
+                cookies: Vec::new(),
+            },
+        }
+    }
+}
+
+impl EndpointHandlerBuilder {
+    pub fn with_operation_id(mut self, operation_id: Option<String>) -> Self {
+        self.endpoint.operation_id = operation_id;
+        self
+    }
+
+    pub fn with_response_body(mut self, response_body: Value) -> Self {
+        self.endpoint.response_body = response_body;
+        self
+    }
+
+    pub fn with_response_schema(mut self, response_schema: Option<ReferenceOr<Schema>>) -> Self {
+        self.endpoint.response_schema = response_schema;
+        self
+    }
+
+    pub fn with_response_xml(mut self, response_xml: Option<XmlShape>) -> Self {
+        self.endpoint.response_xml = response_xml;
+        self
+    }
+
+    pub fn with_path_params(mut self, path_params: Vec<String>) -> Self {
+        self.endpoint.path_params = path_params;
+        self
+    }
+
+    pub fn with_greedy_param(mut self, greedy_param: Option<String>) -> Self {
+        self.endpoint.greedy_param = greedy_param;
+        self
+    }
+
+    pub fn with_delay_ms(mut self, delay_ms: Option<u64>) -> Self {
+        self.endpoint.delay_ms = delay_ms;
+        self
+    }
+
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.endpoint.headers = headers;
+        self
+    }
+
+    pub fn with_content_types(mut self, content_types: Vec<String>) -> Self {
+        self.endpoint.content_types = content_types;
+        self
+    }
+
+    pub fn with_examples(mut self, examples: Vec<(String, Value)>) -> Self {
+        self.endpoint.examples = examples;
+        self
+    }
+
+    pub fn with_request_schema(mut self, request_schema: Option<ReferenceOr<Schema>>) -> Self {
+        self.endpoint.request_schema = request_schema;
+        self
+    }
+
+    pub fn with_request_body_required(mut self, request_body_required: bool) -> Self {
+        self.endpoint.request_body_required = request_body_required;
+        self
+    }
+
+    pub fn with_request_xml(mut self, request_xml: Option<XmlShape>) -> Self {
+        self.endpoint.request_xml = request_xml;
+        self
+    }
+
+    pub fn with_required_query(mut self, required_query: Vec<String>) -> Self {
+        self.endpoint.required_query = required_query;
+        self
+    }
+
+    pub fn with_path_param_schemas(
+        mut self,
+        path_param_schemas: Vec<(String, ReferenceOr<Schema>)>,
+    ) -> Self {
+        self.endpoint.path_param_schemas = path_param_schemas;
+        self
+    }
+
+    pub fn with_security(mut self, security: Vec<Vec<Credential>>) -> Self {
+        self.endpoint.security = security;
+        self
+    }
+
+    pub fn with_chaos_exempt(mut self, chaos_exempt: bool) -> Self {
+        self.endpoint.chaos_exempt = chaos_exempt;
+        self
+    }
+
+    pub fn with_circuit(mut self, circuit: Option<Circuit>) -> Self {
+        self.endpoint.circuit = circuit;
+        self
+    }
+
+    pub fn with_pagination(mut self, pagination: Option<Pagination>) -> Self {
+        self.endpoint.pagination = pagination;
+        self
+    }
+
+    pub fn with_websocket_interval_ms(mut self, websocket_interval_ms: Option<u64>) -> Self {
+        self.endpoint.websocket_interval_ms = websocket_interval_ms;
+        self
+    }
+
+    pub fn with_event_stream(mut self, event_stream: bool) -> Self {
+        self.endpoint.event_stream = event_stream;
+        self
+    }
+
+    pub fn with_sequence(mut self, sequence: Vec<Value>) -> Self {
+        self.endpoint.sequence = sequence;
+        self
+    }
+
+    pub fn with_enum_variants(mut self, enum_variants: Vec<Value>) -> Self {
+        self.endpoint.enum_variants = enum_variants;
+        self
+    }
+
+    pub fn with_query_match(mut self, query_match: Vec<(String, String)>) -> Self {
+        self.endpoint.query_match = query_match;
+        self
+    }
+
+    pub fn with_header_match(mut self, header_match: Vec<(String, String)>) -> Self {
+        self.endpoint.header_match = header_match;
+        self
+    }
+
+    pub fn with_empty_body(mut self, empty_body: bool) -> Self {
+        self.endpoint.empty_body = empty_body;
+        self
+    }
+
+    pub fn with_response_override(mut self, response_override: Option<Value>) -> Self {
+        self.endpoint.response_override = response_override;
+        self
+    }
+
+    pub fn with_binary_body(mut self, binary_body: Option<Vec<u8>>) -> Self {
+        self.endpoint.binary_body = binary_body;
+        self
+    }
+
+    pub fn with_cookies(mut self, cookies: Vec<Cookie<'static>>) -> Self {
+        self.endpoint.cookies = cookies;
+        self
+    }
+
+    pub fn build(self) -> EndpointHandler {
+        self.endpoint
+    }
+}
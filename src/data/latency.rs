@@ -0,0 +1,66 @@
+use actix_web::http::StatusCode;
+use std::str::FromStr;
+
+// The delay of `--latency-ms`, either one for every response, as in `100`,
+// or one per status class, as in `2xx=10,5xx=2000`. Classes left out are
+// served without delay, unless a bare number is listed along, as in
+// `50,5xx=2000`, to stand for them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyProfile {
+    pub default_ms: u64,
+    // Delays by the first digit of the status
+    pub classes: Vec<(u16, u64)>,
+}
+
+impl LatencyProfile {
+    pub fn uniform(delay_ms: u64) -> Self {
+        LatencyProfile {
+            default_ms: delay_ms,
+            classes: Vec::new(),
+        }
+    }
+
+    pub fn delay_for(&self, status: StatusCode) -> u64 {
+        let class = status.as_u16() / 100;
+        self.classes
+            .iter()
+            .find(|(listed, _)| *listed == class)
+            .map_or(self.default_ms, |(_, delay_ms)| *delay_ms)
+    }
+}
+
+impl FromStr for LatencyProfile {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parse_delay = |delay: &str| {
+            delay
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("{} is not a delay in milliseconds", delay.trim()))
+        };
+
+        let mut profile = LatencyProfile::default();
+        for entry in value.split(',') {
+            let Some((class, delay)) = entry.split_once('=') else {
+                profile.default_ms = parse_delay(entry)?;
+                continue;
+            };
+            let class = class.trim().to_ascii_lowercase();
+            let digit = match class.strip_suffix("xx").map(str::parse::<u16>) {
+                Some(Ok(digit)) if (1..=5).contains(&digit) => digit,
+                _ => return Err(format!("{} is not a status class such as 2xx", class)),
+            };
+            let delay_ms = parse_delay(delay)?;
+            match profile
+                .classes
+                .iter_mut()
+                .find(|(listed, _)| *listed == digit)
+            {
+                Some(listed) => listed.1 = delay_ms,
+                None => profile.classes.push((digit, delay_ms)),
+            }
+        }
+        Ok(profile)
+    }
+}
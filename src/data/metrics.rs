@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Upper bounds of the latency histogram buckets, in seconds
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Default)]
+struct Histogram {
+    // Cumulative counts, one per entry of `LATENCY_BUCKETS`
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+struct Registry {
+    // Keyed by (method, path, status)
+    requests: BTreeMap<(String, String, u16), u64>,
+    // Keyed by (method, path)
+    latencies: BTreeMap<(String, String), Histogram>,
+}
+
+// Request counters and latencies served at `/metrics` in the Prometheus text format
+#[derive(Default)]
+pub struct Metrics {
+    registry: Mutex<Registry>,
+}
+
+impl Metrics {
+    // `path` is the matched path template, so that `/users/1` and `/users/2`
+    // are counted together
+    pub fn observe(&self, method: &str, path: &str, status: u16, elapsed: Duration) {
+        let mut registry = self.registry.lock().unwrap();
+        let method = method.to_uppercase();
+
+        *registry
+            .requests
+            .entry((method.clone(), path.to_string(), status))
+            .or_default() += 1;
+
+        let seconds = elapsed.as_secs_f64();
+        let histogram = registry
+            .latencies
+            .entry((method, path.to_string()))
+            .or_default();
+        for (bucket, bound) in histogram.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+
+    pub fn render(&self) -> String {
+        let registry = self.registry.lock().unwrap();
+        let mut output = String::new();
+
+        output.push_str("# HELP stub_requests_total Requests handled by the stub server.\n");
+        output.push_str("# TYPE stub_requests_total counter\n");
+        for ((method, path, status), count) in &registry.requests {
+            let _ = writeln!(
+                output,
+                "stub_requests_total{{method=\"{}\",path=\"{}\",status=\"{}\"}} {}",
+                escape_label(method),
+                escape_label(path),
+                status,
+                count
+            );
+        }
+
+        output.push_str(
+            "# HELP stub_request_duration_seconds Time taken to answer a request, simulated latency included.\n",
+        );
+        output.push_str("# TYPE stub_request_duration_seconds histogram\n");
+        for ((method, path), histogram) in &registry.latencies {
+            let labels = format!(
+                "method=\"{}\",path=\"{}\"",
+                escape_label(method),
+                escape_label(path)
+            );
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                let _ = writeln!(
+                    output,
+                    "stub_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, bound, count
+                );
+            }
+            let _ = writeln!(
+                output,
+                "stub_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, histogram.count
+            );
+            let _ = writeln!(
+                output,
+                "stub_request_duration_seconds_sum{{{}}} {}",
+                labels, histogram.sum
+            );
+            let _ = writeln!(
+                output,
+                "stub_request_duration_seconds_count{{{}}} {}",
+                labels, histogram.count
+            );
+        }
+
+        output
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
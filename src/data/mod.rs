@@ -1,2 +1,11 @@
 pub mod app;
 pub mod cli_args;
+pub mod config_file;
+pub mod cors;
+pub mod endpoint_builder;
+pub mod latency;
+pub mod metrics;
+pub mod rate_limiter;
+pub mod recorder;
+pub mod settings;
+pub mod store;
@@ -0,0 +1,37 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use log::warn;
+use serde_json::Value;
+
+use crate::errors::AppError;
+
+// Appends handled requests to a JSON Lines file for `--record`
+pub struct Recorder {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl Recorder {
+    pub fn open(path: &Path) -> Result<Self, AppError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Recorder {
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    // Flushes after every entry so the file can be followed with `tail -f`
+    pub fn record(&self, entry: &Value) {
+        let mut writer = self.writer.lock().unwrap();
+        let written = serde_json::to_writer(&mut *writer, entry)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writer.write_all(b"\n"))
+            .and_then(|_| writer.flush());
+
+        // A broken recording must not fail the request being served
+        if let Err(e) = written {
+            warn!("Failed to record request: {}", e);
+        }
+    }
+}
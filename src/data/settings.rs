@@ -0,0 +1,118 @@
+use crate::data::cli_args::Args;
+use crate::data::latency::LatencyProfile;
+use crate::generator::{EnumStrategy, DEFAULT_MAX_DEPTH};
+use std::path::PathBuf;
+
+// Options that shape how endpoints are built and served, derived from the CLI
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub latency_ms: LatencyProfile,
+    pub latency_jitter_ms: u64,
+    pub sse_interval_ms: u64,
+    pub sse_max_events: Option<u64>,
+    pub seed: Option<u64>,
+    pub error_rate: f64,
+    pub stateful: bool,
+    pub validate_requests: bool,
+    pub enforce_auth: bool,
+    pub templates: bool,
+    pub array_size: usize,
+    pub max_depth: usize,
+    pub enum_strategy: EnumStrategy,
+    pub swagger_cdn: bool,
+    pub sync_examples: bool,
+    pub base_path: String,
+    pub admin_token: Option<String>,
+    pub enable_admin: bool,
+    pub strict: bool,
+    pub rate_limit: Option<u32>,
+    pub trust_forwarded: bool,
+    pub compress: bool,
+    pub max_body_size: usize,
+    pub debug_headers: bool,
+    pub pretty: bool,
+    pub server_header: Option<String>,
+    pub freeze_date: Option<String>,
+    pub responses_dir: Option<PathBuf>,
+    pub binary_size: usize,
+    pub not_found_status: u16,
+    pub tags: Vec<String>,
+}
+
+// Matches the defaults of the command line flags
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            latency_ms: LatencyProfile::default(),
+            latency_jitter_ms: 0,
+            sse_interval_ms: 1000,
+            sse_max_events: None,
+            seed: None,
+            error_rate: 0.0,
+            stateful: false,
+            validate_requests: false,
+            enforce_auth: false,
+            templates: false,
+            array_size: 10,
+            max_depth: DEFAULT_MAX_DEPTH,
+            enum_strategy: EnumStrategy::First,
+            swagger_cdn: false,
+            sync_examples: false,
+            base_path: String::new(),
+            admin_token: None,
+            enable_admin: false,
+            strict: false,
+            rate_limit: None,
+            trust_forwarded: false,
+            compress: false,
+            // The default of actix-web
+            max_body_size: 262_144,
+            debug_headers: false,
+            pretty: false,
+            server_header: None,
+            freeze_date: None,
+            responses_dir: None,
+            binary_size: 1024,
+            not_found_status: 404,
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl From<&Args> for Settings {
+    fn from(args: &Args) -> Self {
+        Settings {
+            latency_ms: args.latency_ms.clone(),
+            latency_jitter_ms: args.latency_jitter_ms,
+            sse_interval_ms: args.sse_interval_ms,
+            sse_max_events: args.sse_max_events,
+            seed: args.seed,
+            error_rate: args.error_rate,
+            stateful: args.stateful,
+            validate_requests: args.validate_requests,
+            enforce_auth: args.enforce_auth,
+            templates: args.templates,
+            array_size: args.array_size,
+            max_depth: args.max_depth,
+            enum_strategy: args.enum_strategy,
+            swagger_cdn: args.swagger_cdn,
+            sync_examples: args.sync_examples,
+            base_path: args.base_path.clone(),
+            admin_token: args.admin_token.clone(),
+            enable_admin: args.enable_admin,
+            strict: args.strict,
+            rate_limit: args.rate_limit,
+            trust_forwarded: args.trust_forwarded,
+            compress: args.compress,
+            max_body_size: args.max_body_size as usize,
+            debug_headers: args.debug_headers,
+            pretty: args.pretty,
+            server_header: args.server_header.clone(),
+            freeze_date: args.freeze_date.clone(),
+            responses_dir: args.responses_dir.clone(),
+            binary_size: args.binary_size,
+            not_found_status: args.not_found_status,
+            tags: args.tags.iter().map(|tag| tag.trim().to_string()).collect(),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+// In-memory resources created through the mock in `--stateful` mode,
+// keyed by collection path (e.g. `/users`) and then by id
+#[derive(Default)]
+pub struct ResourceStore {
+    pub collections: Mutex<HashMap<String, HashMap<String, Value>>>,
+    next_id: AtomicU64,
+}
+
+impl ResourceStore {
+    // Stores a new resource, assigning an `id` when the body has none. The
+    // counter skips any id already taken, e.g. one sent by a client
+    pub fn create(&self, collection: &str, mut resource: Value) -> Value {
+        let mut collections = self.collections.lock().unwrap();
+        let resources = collections.entry(collection.to_string()).or_default();
+
+        let id = match resource.get("id") {
+            Some(Value::String(id)) => id.clone(),
+            Some(Value::Number(id)) => id.to_string(),
+            _ => {
+                let id = loop {
+                    let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+                    if !resources.contains_key(&id.to_string()) {
+                        break id;
+                    }
+                };
+                if let Value::Object(fields) = &mut resource {
+                    fields.insert("id".to_string(), Value::from(id));
+                }
+                id.to_string()
+            }
+        };
+
+        resources.insert(id, resource.clone());
+        resource
+    }
+
+    // Lists a collection ordered by id, `None` when nothing was ever stored in it
+    pub fn list(&self, collection: &str) -> Option<Vec<Value>> {
+        let collections = self.collections.lock().unwrap();
+        let resources = collections.get(collection)?;
+
+        let mut ids: Vec<&String> = resources.keys().collect();
+        ids.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        Some(ids.into_iter().map(|id| resources[id].clone()).collect())
+    }
+
+    // Whether anything was ever stored in the collection, even if since deleted
+    pub fn contains(&self, collection: &str) -> bool {
+        self.collections.lock().unwrap().contains_key(collection)
+    }
+
+    pub fn get(&self, collection: &str, id: &str) -> Option<Value> {
+        let collections = self.collections.lock().unwrap();
+        collections.get(collection)?.get(id).cloned()
+    }
+
+    pub fn put(&self, collection: &str, id: &str, resource: Value) -> Value {
+        self.collections
+            .lock()
+            .unwrap()
+            .entry(collection.to_string())
+            .or_default()
+            .insert(id.to_string(), resource.clone());
+        resource
+    }
+
+    pub fn delete(&self, collection: &str, id: &str) -> Option<Value> {
+        let mut collections = self.collections.lock().unwrap();
+        collections.get_mut(collection)?.remove(id)
+    }
+
+    // Every collection and its resources, keyed by id
+    pub fn snapshot(&self) -> Value {
+        let collections = self.collections.lock().unwrap();
+        serde_json::to_value(&*collections).unwrap_or_default()
+    }
+
+    // Forgets every resource, and starts assigning ids from 1 again
+    pub fn clear(&self) {
+        self.collections.lock().unwrap().clear();
+        self.next_id.store(0, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::data::app::EndpointHandler;
+use clap::ValueEnum;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    Table,
+    Json,
+}
+
+// How an endpoint is listed, by `GET /api/endpoints` as well as `--list`
+pub fn describe(endpoint: &EndpointHandler) -> Value {
+    serde_json::json!({
+        "path": endpoint.path,
+        "method": endpoint.method,
+        "status_code": endpoint.response_code,
+        "content_types": endpoint.content_types,
+    })
+}
+
+// The endpoints printed by `--list`, in the order the spec declares them
+pub fn render(endpoints: &[EndpointHandler], format: ListFormat) -> String {
+    match format {
+        ListFormat::Json => {
+            let endpoints: Vec<Value> = endpoints.iter().map(describe).collect();
+            let list = serde_json::json!({
+                "endpoints": endpoints,
+                "count": endpoints.len(),
+            });
+            format!("{:#}\n", list)
+        }
+        ListFormat::Table => table(endpoints),
+    }
+}
+
+fn table(endpoints: &[EndpointHandler]) -> String {
+    let mut rows = vec![[
+        "METHOD".to_string(),
+        "PATH".to_string(),
+        "STATUS".to_string(),
+        "CONTENT TYPES".to_string(),
+    ]];
+    rows.extend(endpoints.iter().map(|endpoint| {
+        [
+            endpoint.method.to_uppercase(),
+            endpoint.path.clone(),
+            endpoint.response_code.clone(),
+            if endpoint.empty_body {
+                "(no body)".to_string()
+            } else {
+                endpoint.content_types.join(", ")
+            },
+        ]
+    }));
+
+    let mut widths = [0; 3];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut table = String::new();
+    for [method, path, status, content_types] in &rows {
+        let line = format!(
+            "{:<method_width$}  {:<path_width$}  {:<status_width$}  {}",
+            method,
+            path,
+            status,
+            content_types,
+            method_width = widths[0],
+            path_width = widths[1],
+            status_width = widths[2],
+        );
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table.push_str(&format!("{} endpoints\n", endpoints.len()));
+    table
+}
@@ -11,6 +11,27 @@ pub enum AppError {
     #[error("YAML parsing error: {0}")]
     YamlError(#[from] serde_yaml::Error),
 
+    #[error("JSON parsing error: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("Failed to resolve reference: {0}")]
+    RefError(String),
+
+    #[error("Failed to fetch spec: {0}")]
+    FetchError(String),
+
+    #[error("Invalid OpenAPI spec: {0}")]
+    ValidationError(String),
+
+    #[error("Invalid configuration: {0}")]
+    ConfigError(String),
+
+    #[error("Failed to load TLS configuration: {0}")]
+    TlsError(String),
+
+    #[error("Failed to watch spec file: {0}")]
+    WatchError(#[from] notify::Error),
+
     #[error("Internal server error: {0}")]
     InternalSeverError(#[from] actix_web::Error),
 }
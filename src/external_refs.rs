@@ -0,0 +1,159 @@
+use crate::errors::AppError;
+use crate::resolver;
+use crate::spec_compat::{self, LITERAL_KEYS, SCHEMA_MAPS};
+use crate::spec_source::SpecFormat;
+use log::{debug, warn};
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// Inlines the `$ref`s pointing into other files, such as
+// `./schemas/user.yaml#/User`, for the spec to stand on its own. Paths are
+// relative to the file holding the reference, and the local references of an
+// external file point into that file. Remote references are left as they are
+pub fn inline(document: &mut Value, spec_path: &Path) -> Result<(), AppError> {
+    let mut inliner = Inliner::default();
+    inliner.walk(document, &Origin::spec(spec_path))
+}
+
+// The file a node was read from
+struct Origin {
+    dir: PathBuf,
+    // `None` for the spec itself, whose local references stay as they are
+    file: Option<PathBuf>,
+    name: String,
+}
+
+impl Origin {
+    fn spec(spec_path: &Path) -> Self {
+        Origin {
+            dir: spec_path.parent().unwrap_or(Path::new("")).to_path_buf(),
+            file: None,
+            name: spec_path.display().to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inliner {
+    // Files already read, by their canonical path
+    files: HashMap<PathBuf, Value>,
+    // The references being inlined, the innermost last
+    chain: Vec<String>,
+}
+
+impl Inliner {
+    fn walk(&mut self, node: &mut Value, origin: &Origin) -> Result<(), AppError> {
+        match node {
+            Value::Mapping(mapping) => {
+                if let Some(reference) = mapping.get("$ref").and_then(Value::as_str) {
+                    if let Some(target) = self.inline_reference(reference, origin)? {
+                        *node = target;
+                    }
+                    return Ok(());
+                }
+                for (key, value) in mapping.iter_mut() {
+                    let key = key.as_str().unwrap_or_default();
+                    if LITERAL_KEYS.contains(&key) || key.starts_with("x-") {
+                        continue;
+                    }
+                    match value.as_mapping_mut() {
+                        Some(schemas) if SCHEMA_MAPS.contains(&key) => {
+                            for (_, schema) in schemas.iter_mut() {
+                                self.walk(schema, origin)?;
+                            }
+                        }
+                        _ => self.walk(value, origin)?,
+                    }
+                }
+                Ok(())
+            }
+            Value::Sequence(items) => items
+                .iter_mut()
+                .try_for_each(|item| self.walk(item, origin)),
+            _ => Ok(()),
+        }
+    }
+
+    // What the reference points to, with its own external references inlined.
+    // `None` when it is left in place
+    fn inline_reference(
+        &mut self,
+        reference: &str,
+        origin: &Origin,
+    ) -> Result<Option<Value>, AppError> {
+        let (file, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        if file.contains("://") {
+            warn!(
+                "Remote reference {} in {} is not supported",
+                reference, origin.name
+            );
+            return Ok(None);
+        }
+        let path = match (file, &origin.file) {
+            ("", None) => return Ok(None),
+            ("", Some(current)) => current.clone(),
+            (file, _) => origin.dir.join(file),
+        };
+
+        let canonical = path.canonicalize().map_err(|e| {
+            AppError::RefError(format!(
+                "{} in {}: can't read {}: {}",
+                reference,
+                origin.name,
+                path.display(),
+                e
+            ))
+        })?;
+        let key = format!("{}#{}", canonical.display(), fragment);
+        if self.chain.contains(&key) {
+            let mut cycle = self.chain.clone();
+            cycle.push(key);
+            return Err(AppError::RefError(format!(
+                "{} in {} is circular: {}",
+                reference,
+                origin.name,
+                cycle.join(" -> ")
+            )));
+        }
+
+        let document = self.load(&canonical)?;
+        let target = match fragment {
+            "" | "/" => Some(document),
+            fragment => resolver::lookup_pointer(document, &format!("#{}", fragment)),
+        }
+        .cloned()
+        .ok_or_else(|| {
+            AppError::RefError(format!(
+                "{} in {}: {} has nothing at #{}",
+                reference,
+                origin.name,
+                path.display(),
+                fragment
+            ))
+        })?;
+        debug!("Inlining {} from {}", reference, origin.name);
+
+        let mut target = target;
+        let target_origin = Origin {
+            dir: canonical.parent().unwrap_or(Path::new("")).to_path_buf(),
+            file: Some(canonical.clone()),
+            name: path.display().to_string(),
+        };
+        self.chain.push(key);
+        let result = self.walk(&mut target, &target_origin);
+        self.chain.pop();
+        result?;
+        Ok(Some(target))
+    }
+
+    fn load(&mut self, path: &Path) -> Result<&Value, AppError> {
+        if !self.files.contains_key(path) {
+            let content = std::fs::read_to_string(path)?;
+            let format = SpecFormat::detect(&path.to_string_lossy(), &content);
+            let document = spec_compat::read_document(&content, format)?;
+            self.files.insert(path.to_path_buf(), document);
+        }
+        Ok(&self.files[path])
+    }
+}
@@ -0,0 +1,115 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use fake::faker::address::en::{CityName, CountryName, StreetName, ZipCode};
+use fake::faker::chrono::en::DateTimeBetween;
+use fake::faker::company::en::CompanyName;
+use fake::faker::internet::en::{DomainSuffix, IPv4, IPv6, Password, SafeEmail, Username};
+use fake::faker::lorem::en::{Sentence, Word};
+use fake::faker::name::en::{FirstName, LastName, Name};
+use fake::faker::phone_number::en::PhoneNumber;
+use fake::Fake;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+// A realistic value for a string schema, from its `format` or else from the
+// name of the field holding it. `None` when neither says what the value is
+pub fn fake_string(format: Option<&str>, field: Option<&str>, rng: &mut StdRng) -> Option<String> {
+    if let Some(value) = format.and_then(|format| fake_format(format, rng)) {
+        return Some(value);
+    }
+    fake_field(field?, rng)
+}
+
+fn fake_format(format: &str, rng: &mut StdRng) -> Option<String> {
+    let value = match format {
+        "date-time" => datetime(rng).to_rfc3339_opts(SecondsFormat::Secs, true),
+        "date" => datetime(rng).format("%Y-%m-%d").to_string(),
+        "time" => datetime(rng).format("%H:%M:%S").to_string(),
+        "email" => SafeEmail().fake_with_rng(rng),
+        "uuid" => uuid(rng),
+        "uri" | "url" => url(rng),
+        "hostname" => hostname(rng),
+        "ipv4" => IPv4().fake_with_rng(rng),
+        "ipv6" => IPv6().fake_with_rng(rng),
+        "password" => Password(12..16).fake_with_rng(rng),
+        // Bytes can only be carried by a JSON string once encoded, a whole
+        // `binary` body being served as raw bytes instead
+        "byte" | "binary" => STANDARD.encode(rng.random::<[u8; 12]>()),
+        _ => return None,
+    };
+    Some(value)
+}
+
+// Names are compared without case or separators, so `created_at`,
+// `createdAt` and `CreatedAt` are the same field
+fn fake_field(field: &str, rng: &mut StdRng) -> Option<String> {
+    let field: String = field
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+
+    let value = match field.as_str() {
+        "email" | "emailaddress" | "mail" => SafeEmail().fake_with_rng(rng),
+        "name" | "fullname" | "displayname" => Name().fake_with_rng(rng),
+        "firstname" | "givenname" => FirstName().fake_with_rng(rng),
+        "lastname" | "surname" | "familyname" => LastName().fake_with_rng(rng),
+        "username" | "login" | "handle" => Username().fake_with_rng(rng),
+        "uuid" | "guid" => uuid(rng),
+        "phone" | "phonenumber" | "mobile" => PhoneNumber().fake_with_rng(rng),
+        "city" => CityName().fake_with_rng(rng),
+        "country" => CountryName().fake_with_rng(rng),
+        "street" | "address" | "streetaddress" => StreetName().fake_with_rng(rng),
+        "zip" | "zipcode" | "postcode" | "postalcode" => ZipCode().fake_with_rng(rng),
+        "company" | "companyname" | "organization" => CompanyName().fake_with_rng(rng),
+        "url" | "website" | "homepage" => url(rng),
+        "title" | "summary" | "description" | "bio" => Sentence(3..8).fake_with_rng(rng),
+        "dateofbirth" => datetime(rng).format("%Y-%m-%d").to_string(),
+        _ if field.ends_with("at") && is_timestamp(&field) => {
+            datetime(rng).to_rfc3339_opts(SecondsFormat::Secs, true)
+        }
+        _ if field.ends_with("date") => datetime(rng).format("%Y-%m-%d").to_string(),
+        _ if field.ends_with("email") => SafeEmail().fake_with_rng(rng),
+        _ => return None,
+    };
+    Some(value)
+}
+
+// `createdAt` and `deletedAt`, but not `format` or `chat`
+fn is_timestamp(field: &str) -> bool {
+    [
+        "created",
+        "updated",
+        "deleted",
+        "modified",
+        "published",
+        "expires",
+        "started",
+    ]
+    .iter()
+    .any(|prefix| field.starts_with(prefix))
+}
+
+// Recent dates look more plausible than ones spread over millennia
+fn datetime(rng: &mut StdRng) -> DateTime<Utc> {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2025, 12, 31, 23, 59, 59).unwrap();
+    DateTimeBetween(start, end).fake_with_rng(rng)
+}
+
+fn uuid(rng: &mut StdRng) -> String {
+    uuid::Builder::from_random_bytes(rng.random())
+        .into_uuid()
+        .to_string()
+}
+
+fn hostname(rng: &mut StdRng) -> String {
+    let word: String = Word().fake_with_rng(rng);
+    let suffix: String = DomainSuffix().fake_with_rng(rng);
+    format!("{}.{}", word, suffix)
+}
+
+fn url(rng: &mut StdRng) -> String {
+    format!("https://{}", hostname(rng))
+}
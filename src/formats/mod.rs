@@ -0,0 +1,28 @@
+pub mod xml;
+
+use serde_json::Value;
+use xml::XmlShape;
+
+// Serializes a stub body for the negotiated content type, XML being laid out
+// by the shape of its schema when there is one, and JSON indented under
+// `--pretty`. Object keys always come out sorted, as `serde_json` is built
+// without `preserve_order` and its maps are ordered ones
+pub fn render_body(
+    content_type: &str,
+    body: &Value,
+    xml: Option<&XmlShape>,
+    pretty: bool,
+) -> String {
+    if content_type.contains("xml") {
+        xml::to_xml("response", body, xml)
+    } else if content_type.starts_with("text/") {
+        match body {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        }
+    } else if pretty {
+        format!("{:#}", body)
+    } else {
+        body.to_string()
+    }
+}
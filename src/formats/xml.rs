@@ -0,0 +1,524 @@
+use crate::resolver;
+use openapiv3::{OpenAPI, ReferenceOr, Schema, SchemaKind, Type};
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+
+// How a schema lays its value out in XML, from its `xml` objects, which
+// `spec_compat` keeps as `x-xml` extensions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XmlShape {
+    // The element or attribute name, the property name being used otherwise
+    pub name: Option<String>,
+    pub attribute: bool,
+    // Arrays are written as repeated elements unless wrapped in one
+    pub wrapped: bool,
+    // What the text of a scalar is read back as, strings staying strings
+    pub scalar: Option<Scalar>,
+    pub properties: BTreeMap<String, XmlShape>,
+    pub items: Option<Box<XmlShape>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalar {
+    Integer,
+    Number,
+    Boolean,
+}
+
+impl XmlShape {
+    // The root element of a schema referring to a component is named after it
+    pub fn of(schema: &ReferenceOr<Schema>, spec: &OpenAPI) -> XmlShape {
+        let mut visiting = Vec::new();
+        match schema {
+            ReferenceOr::Item(schema) => shape_of(schema, spec, &mut visiting),
+            ReferenceOr::Reference { reference } => {
+                let mut shape = shape_of_reference(reference, spec, &mut visiting);
+                if shape.name.is_none() {
+                    shape.name = reference.rsplit('/').next().map(str::to_string);
+                }
+                shape
+            }
+        }
+    }
+
+    // The element a property is written as, by its name or the one of its items
+    // when they are repeated without a wrapper
+    fn property_for(&self, element: &str) -> Option<(&String, &XmlShape)> {
+        self.properties.iter().find(|(key, property)| {
+            !property.attribute
+                && (property.name.as_deref().unwrap_or(key) == element
+                    || (!property.wrapped
+                        && property
+                            .items
+                            .as_ref()
+                            .and_then(|items| items.name.as_deref())
+                            == Some(element)))
+        })
+    }
+
+    fn attribute_for(&self, attribute: &str) -> Option<(&String, &XmlShape)> {
+        self.properties.iter().find(|(key, property)| {
+            property.attribute && property.name.as_deref().unwrap_or(key) == attribute
+        })
+    }
+}
+
+fn shape_of_reference(reference: &str, spec: &OpenAPI, visiting: &mut Vec<String>) -> XmlShape {
+    // A schema containing itself is only laid out once
+    if visiting.iter().any(|visited| visited == reference) {
+        return XmlShape::default();
+    }
+    let Some(schema) = resolver::resolve_schema(spec, reference) else {
+        return XmlShape::default();
+    };
+    visiting.push(reference.to_string());
+    let shape = shape_of(schema, spec, visiting);
+    visiting.pop();
+    shape
+}
+
+fn shape_of_boxed(
+    schema: &ReferenceOr<Box<Schema>>,
+    spec: &OpenAPI,
+    visiting: &mut Vec<String>,
+) -> XmlShape {
+    match schema {
+        ReferenceOr::Item(schema) => shape_of(schema, spec, visiting),
+        ReferenceOr::Reference { reference } => shape_of_reference(reference, spec, visiting),
+    }
+}
+
+fn shape_of(schema: &Schema, spec: &OpenAPI, visiting: &mut Vec<String>) -> XmlShape {
+    let annotation = schema.schema_data.extensions.get("x-xml");
+    let mut shape = XmlShape {
+        name: annotation
+            .and_then(|xml| xml.get("name"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        attribute: annotation
+            .and_then(|xml| xml.get("attribute"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        wrapped: annotation
+            .and_then(|xml| xml.get("wrapped"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        ..XmlShape::default()
+    };
+
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Integer(_)) => shape.scalar = Some(Scalar::Integer),
+        SchemaKind::Type(Type::Number(_)) => shape.scalar = Some(Scalar::Number),
+        SchemaKind::Type(Type::Boolean {}) => shape.scalar = Some(Scalar::Boolean),
+        SchemaKind::Type(Type::Object(object)) => {
+            add_properties(&mut shape, &object.properties, spec, visiting)
+        }
+        SchemaKind::Type(Type::Array(array)) => {
+            shape.items = array
+                .items
+                .as_ref()
+                .map(|items| Box::new(shape_of_boxed(items, spec, visiting)));
+        }
+        SchemaKind::Any(any) => {
+            add_properties(&mut shape, &any.properties, spec, visiting);
+            shape.items = any
+                .items
+                .as_ref()
+                .map(|items| Box::new(shape_of_boxed(items, spec, visiting)));
+        }
+        // The properties of every subschema can show up in the value
+        SchemaKind::AllOf { all_of: schemas }
+        | SchemaKind::OneOf { one_of: schemas }
+        | SchemaKind::AnyOf { any_of: schemas } => {
+            for schema in schemas {
+                let subschema = match schema {
+                    ReferenceOr::Item(schema) => shape_of(schema, spec, visiting),
+                    ReferenceOr::Reference { reference } => {
+                        shape_of_reference(reference, spec, visiting)
+                    }
+                };
+                shape.properties.extend(subschema.properties);
+                shape.scalar = shape.scalar.or(subschema.scalar);
+                shape.items = shape.items.take().or(subschema.items);
+            }
+        }
+        _ => {}
+    }
+    shape
+}
+
+fn add_properties<'s>(
+    shape: &mut XmlShape,
+    properties: impl IntoIterator<Item = (&'s String, &'s ReferenceOr<Box<Schema>>)>,
+    spec: &OpenAPI,
+    visiting: &mut Vec<String>,
+) {
+    for (name, property) in properties {
+        let property = shape_of_boxed(property, spec, visiting);
+        shape.properties.insert(name.clone(), property);
+    }
+}
+
+// Renders a JSON value as an XML document. Without a shape, array items
+// repeat their parent's tag
+pub fn to_xml(root: &str, value: &Value, shape: Option<&XmlShape>) -> String {
+    let mut out = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let root = shape
+        .and_then(|shape| shape.name.as_deref())
+        .unwrap_or(root);
+
+    match value {
+        // A document needs a single root, so top-level arrays get wrapped
+        Value::Array(items) => {
+            let items_shape = shape.and_then(|shape| shape.items.as_deref());
+            let item_name = items_shape
+                .and_then(|items| items.name.as_deref())
+                .unwrap_or("item");
+            out.push_str(&format!("<{}>", root));
+            for item in items {
+                write_element(&mut out, item_name, item, items_shape);
+            }
+            out.push_str(&format!("</{}>", root));
+        }
+        value => write_element(&mut out, root, value, shape),
+    }
+
+    out
+}
+
+fn write_element(out: &mut String, name: &str, value: &Value, shape: Option<&XmlShape>) {
+    match value {
+        Value::Object(map) => {
+            out.push_str(&format!("<{}", name));
+            let mut children = Vec::new();
+            for (key, child) in map {
+                let property = shape.and_then(|shape| shape.properties.get(key));
+                let child_name = property
+                    .and_then(|property| property.name.as_deref())
+                    .unwrap_or(key);
+                match child {
+                    Value::String(_) | Value::Number(_) | Value::Bool(_)
+                        if property.is_some_and(|property| property.attribute) =>
+                    {
+                        out.push_str(&format!(r#" {}="{}""#, child_name, escape(&text(child))));
+                    }
+                    child => children.push((child_name, child, property)),
+                }
+            }
+            out.push('>');
+            for (child_name, child, property) in children {
+                write_element(out, child_name, child, property);
+            }
+            out.push_str(&format!("</{}>", name));
+        }
+        Value::Array(items) => {
+            let items_shape = shape.and_then(|shape| shape.items.as_deref());
+            let item_name = items_shape
+                .and_then(|items| items.name.as_deref())
+                .unwrap_or(name);
+            let wrapped = shape.is_some_and(|shape| shape.wrapped);
+            if wrapped {
+                out.push_str(&format!("<{}>", name));
+            }
+            for item in items {
+                write_element(out, item_name, item, items_shape);
+            }
+            if wrapped {
+                out.push_str(&format!("</{}>", name));
+            }
+        }
+        Value::Null => out.push_str(&format!("<{}/>", name)),
+        other => out.push_str(&format!("<{0}>{1}</{0}>", name, escape(&text(other)))),
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// An element of a parsed document. Namespace prefixes are dropped from names
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+    self_closing: bool,
+}
+
+// Reads an XML document back into the value `to_xml` would render it from,
+// the root element standing for the whole value. Without a shape, scalars
+// are strings and repeated elements become arrays
+pub fn from_xml(document: &str, shape: Option<&XmlShape>) -> Option<Value> {
+    let mut reader = Reader {
+        input: document,
+        position: 0,
+    };
+    reader.skip_misc();
+    let root = reader.element()?;
+    reader.skip_misc();
+    if reader.position < document.len() {
+        return None;
+    }
+    Some(element_value(&root, shape))
+}
+
+fn element_value(element: &Element, shape: Option<&XmlShape>) -> Value {
+    // A wrapper, or the root of a top-level array, holds nothing but the items
+    if let Some(items) = shape.and_then(|shape| shape.items.as_deref()) {
+        return Value::Array(
+            element
+                .children
+                .iter()
+                .map(|child| element_value(child, Some(items)))
+                .collect(),
+        );
+    }
+
+    let is_object = shape.is_some_and(|shape| !shape.properties.is_empty());
+    if element.children.is_empty() && element.attributes.is_empty() && !is_object {
+        if element.self_closing {
+            return Value::Null;
+        }
+        return scalar(&element.text, shape.and_then(|shape| shape.scalar));
+    }
+
+    let mut map = Map::new();
+    for (name, value) in &element.attributes {
+        match shape.and_then(|shape| shape.attribute_for(name)) {
+            Some((key, property)) => {
+                map.insert(key.clone(), scalar(value, property.scalar));
+            }
+            None => {
+                map.insert(name.clone(), Value::String(value.clone()));
+            }
+        }
+    }
+    for child in &element.children {
+        match shape.and_then(|shape| shape.property_for(&child.name)) {
+            // Items repeated without a wrapper are gathered into their array
+            Some((key, property)) if !property.wrapped && property.items.is_some() => {
+                let value = element_value(child, property.items.as_deref());
+                match map
+                    .entry(key.clone())
+                    .or_insert_with(|| Value::Array(Vec::new()))
+                {
+                    Value::Array(items) => items.push(value),
+                    other => *other = Value::Array(vec![other.take(), value]),
+                }
+            }
+            Some((key, property)) => {
+                map.insert(key.clone(), element_value(child, Some(property)));
+            }
+            None => {
+                let value = element_value(child, None);
+                match map.get_mut(&child.name) {
+                    Some(Value::Array(items)) => items.push(value),
+                    Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+                    None => {
+                        map.insert(child.name.clone(), value);
+                    }
+                }
+            }
+        }
+    }
+    Value::Object(map)
+}
+
+// Text that doesn't read as the schema's type is kept as it is, for
+// validation to report it
+fn scalar(text: &str, scalar: Option<Scalar>) -> Value {
+    let trimmed = text.trim();
+    let value = match scalar {
+        Some(Scalar::Integer) => trimmed.parse::<i64>().ok().map(Value::from),
+        Some(Scalar::Number) => trimmed
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number),
+        Some(Scalar::Boolean) => trimmed.parse::<bool>().ok().map(Value::Bool),
+        None => None,
+    };
+    value.unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+struct Reader<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    // Moves past `end`, failing when it never comes
+    fn skip_past(&mut self, end: &str) -> Option<()> {
+        let offset = self.rest().find(end)?;
+        self.position += offset + end.len();
+        Some(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    // The declaration, comments, processing instructions and the doctype
+    // around the root element
+    fn skip_misc(&mut self) {
+        loop {
+            self.skip_whitespace();
+            let skipped = if self.rest().starts_with("<?") {
+                self.skip_past("?>")
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                self.skip_past(">")
+            } else {
+                return;
+            };
+            if skipped.is_none() {
+                self.position = self.input.len();
+                return;
+            }
+        }
+    }
+
+    fn name(&mut self) -> Option<String> {
+        let rest = self.rest();
+        let length = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '='))
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return None;
+        }
+        self.position += length;
+        Some(rest[..length].to_string())
+    }
+
+    fn element(&mut self) -> Option<Element> {
+        self.position += self.rest().strip_prefix('<').map(|_| 1)?;
+        let raw_name = self.name()?;
+        let mut element = Element {
+            name: local_name(&raw_name).to_string(),
+            ..Element::default()
+        };
+
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.position += 2;
+                element.self_closing = true;
+                return Some(element);
+            }
+            if self.rest().starts_with('>') {
+                self.position += 1;
+                break;
+            }
+            let attribute = self.name()?;
+            self.skip_whitespace();
+            self.position += self.rest().strip_prefix('=').map(|_| 1)?;
+            self.skip_whitespace();
+            let quote = self
+                .rest()
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '"' | '\''))?;
+            self.position += 1;
+            let length = self.rest().find(quote)?;
+            let value = unescape(&self.rest()[..length]);
+            self.position += length + 1;
+            // Namespace declarations aren't part of the value
+            if attribute != "xmlns" && !attribute.starts_with("xmlns:") {
+                element
+                    .attributes
+                    .push((local_name(&attribute).to_string(), value));
+            }
+        }
+
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return None;
+            }
+            if let Some(closing) = rest.strip_prefix("</") {
+                let length = closing.find('>')?;
+                if closing[..length].trim() != raw_name {
+                    return None;
+                }
+                self.position += 2 + length + 1;
+                return Some(element);
+            }
+            if let Some(data) = rest.strip_prefix("<![CDATA[") {
+                let length = data.find("]]>")?;
+                element.text.push_str(&data[..length]);
+                self.position += "<![CDATA[".len() + length + "]]>".len();
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with('<') {
+                element.children.push(self.element()?);
+            } else {
+                let length = rest.find('<').unwrap_or(rest.len());
+                element.text.push_str(&unescape(&rest[..length]));
+                self.position += length;
+            }
+        }
+    }
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+// The predefined entities and character references
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
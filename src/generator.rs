@@ -0,0 +1,460 @@
+use crate::fake_data;
+use crate::resolver;
+use clap::ValueEnum;
+use log::warn;
+use openapiv3::{
+    ArrayType, Discriminator, IntegerType, NumberType, ObjectType, OpenAPI, ReferenceOr, Schema,
+    SchemaKind, StringFormat, StringType, Type, VariantOrUnknownOrEmpty,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{Map, Value};
+use std::cell::{Cell, RefCell};
+
+// How a value is picked out of an `enum`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EnumStrategy {
+    // The first declared value
+    First,
+    // Any value, reproducible from `--seed`
+    Random,
+    // The value at the generator's position, wrapping around, so that
+    // successive positions go through every value
+    RoundRobin,
+}
+
+// Builds stub JSON values by walking the schemas declared in an OpenAPI spec
+pub struct SchemaGenerator<'a> {
+    spec: &'a OpenAPI,
+    // Source of the fake emails, names and dates, seeded from `--seed`
+    rng: RefCell<StdRng>,
+    // Objects and arrays nested deeper than this are served empty
+    max_depth: usize,
+    depth: Cell<usize>,
+    // The references being expanded, a schema met again among its own
+    // descendants being a cycle
+    visiting: RefCell<Vec<String>>,
+    enum_strategy: EnumStrategy,
+    enum_position: usize,
+    // Length of the longest enum met, after which round-robin positions
+    // repeat the same values
+    enum_cycle: Cell<usize>,
+}
+
+impl<'a> SchemaGenerator<'a> {
+    pub fn new(spec: &'a OpenAPI) -> Self {
+        SchemaGenerator {
+            spec,
+            rng: RefCell::new(StdRng::seed_from_u64(FAKE_SEED)),
+            max_depth: DEFAULT_MAX_DEPTH,
+            depth: Cell::new(0),
+            visiting: RefCell::new(Vec::new()),
+            enum_strategy: EnumStrategy::First,
+            enum_position: 0,
+            enum_cycle: Cell::new(1),
+        }
+    }
+
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        if let Some(seed) = seed {
+            self.rng = RefCell::new(StdRng::seed_from_u64(seed));
+        }
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_enum_strategy(mut self, enum_strategy: EnumStrategy) -> Self {
+        self.enum_strategy = enum_strategy;
+        self
+    }
+
+    // Which value `EnumStrategy::RoundRobin` picks, modulo each enum's length
+    pub fn with_enum_position(mut self, enum_position: usize) -> Self {
+        self.enum_position = enum_position;
+        self
+    }
+
+    // How many positions there are before the generated values repeat, as
+    // far as the values generated so far go
+    pub fn enum_cycle(&self) -> usize {
+        self.enum_cycle.get()
+    }
+
+    pub fn generate(&self, schema: &ReferenceOr<Schema>) -> Value {
+        self.generate_field(schema, None)
+    }
+
+    // `field` is the name of the property holding the value, if any
+    fn generate_field(&self, schema: &ReferenceOr<Schema>, field: Option<&str>) -> Value {
+        match schema {
+            ReferenceOr::Item(schema) => self.generate_schema(schema, field),
+            ReferenceOr::Reference { reference } => self.generate_reference(reference, field),
+        }
+    }
+
+    fn generate_boxed(&self, schema: &ReferenceOr<Box<Schema>>, field: Option<&str>) -> Value {
+        match schema {
+            ReferenceOr::Item(schema) => self.generate_schema(schema, field),
+            ReferenceOr::Reference { reference } => self.generate_reference(reference, field),
+        }
+    }
+
+    // One of the values of an enum, following `enum_strategy`
+    fn pick<'v, T>(&self, values: Vec<&'v T>) -> Option<&'v T> {
+        if values.is_empty() {
+            return None;
+        }
+        self.enum_cycle.set(self.enum_cycle.get().max(values.len()));
+        let index = match self.enum_strategy {
+            EnumStrategy::First => 0,
+            EnumStrategy::Random => self.rng.borrow_mut().random_range(0..values.len()),
+            EnumStrategy::RoundRobin => self.enum_position % values.len(),
+        };
+        Some(values[index])
+    }
+
+    // A reference to a schema already being expanded would never end, so the
+    // cycle is cut with a null
+    fn generate_reference(&self, reference: &str, field: Option<&str>) -> Value {
+        if self.is_visiting(reference) {
+            return Value::Null;
+        }
+        let Some(schema) = resolver::resolve_schema(self.spec, reference) else {
+            warn!("Could not resolve schema reference: {}", reference);
+            return Value::Null;
+        };
+
+        self.visiting.borrow_mut().push(reference.to_string());
+        let value = self.generate_schema(schema, field);
+        self.visiting.borrow_mut().pop();
+        value
+    }
+
+    fn is_visiting(&self, reference: &str) -> bool {
+        self.visiting
+            .borrow()
+            .iter()
+            .any(|visiting| visiting == reference)
+    }
+
+    // Runs `generate` one level deeper, `None` past `max_depth`
+    fn nested<T>(&self, generate: impl FnOnce() -> T) -> Option<T> {
+        if self.depth.get() >= self.max_depth {
+            return None;
+        }
+        self.depth.set(self.depth.get() + 1);
+        let value = generate();
+        self.depth.set(self.depth.get() - 1);
+        Some(value)
+    }
+
+    fn generate_schema(&self, schema: &Schema, field: Option<&str>) -> Value {
+        // Values provided by the spec author always beat generated ones
+        if let Some(example) = &schema.schema_data.example {
+            return example.clone();
+        }
+        if let Some(default) = &schema.schema_data.default {
+            return default.clone();
+        }
+
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::String(string)) => {
+                match self.pick(string.enumeration.iter().flatten().collect()) {
+                    Some(value) => Value::String(value.clone()),
+                    None => Value::String(self.generate_string(string, field)),
+                }
+            }
+            SchemaKind::Type(Type::Integer(integer)) => {
+                match self.pick(integer.enumeration.iter().flatten().collect()) {
+                    Some(value) => Value::from(*value),
+                    None => Value::from(generate_integer(integer)),
+                }
+            }
+            SchemaKind::Type(Type::Number(number)) => {
+                match self.pick(number.enumeration.iter().flatten().collect()) {
+                    Some(value) => Value::from(*value),
+                    None => Value::from(generate_number(number)),
+                }
+            }
+            SchemaKind::Type(Type::Boolean {}) => Value::Bool(true),
+            SchemaKind::Type(Type::Object(object)) => self.generate_object(object),
+            SchemaKind::Type(Type::Array(array)) => self.generate_array(array),
+            SchemaKind::Any(any) => {
+                if let Some(value) = self.pick(any.enumeration.iter().collect()) {
+                    return value.clone();
+                }
+                // Schemas without an explicit `type` are still common, guess from their shape
+                if !any.properties.is_empty() || any.typ.as_deref() == Some("object") {
+                    return Value::Object(
+                        self.generate_properties(&any.properties)
+                            .unwrap_or_default(),
+                    );
+                }
+                match &any.items {
+                    Some(items) => Value::Array(self.generate_items(items).into_iter().collect()),
+                    None => Value::Null,
+                }
+            }
+            SchemaKind::AllOf { all_of } => self.generate_all_of(all_of, field),
+            SchemaKind::OneOf { one_of } => {
+                self.generate_choice(one_of, schema.schema_data.discriminator.as_ref(), field)
+            }
+            SchemaKind::AnyOf { any_of } => {
+                self.generate_choice(any_of, schema.schema_data.discriminator.as_ref(), field)
+            }
+            SchemaKind::Not { .. } => Value::Null,
+        }
+    }
+
+    // Inheritance is modeled as the base schema followed by the additions, so
+    // the objects of every subschema are merged into one
+    fn generate_all_of(&self, schemas: &[ReferenceOr<Schema>], field: Option<&str>) -> Value {
+        let mut merged: Option<Map<String, Value>> = None;
+        let mut scalar = None;
+        for schema in schemas {
+            match self.generate_field(schema, field) {
+                Value::Object(object) => merged.get_or_insert_with(Map::new).extend(object),
+                Value::Null => {}
+                value => {
+                    scalar.get_or_insert(value);
+                }
+            }
+        }
+        merged.map(Value::Object).or(scalar).unwrap_or(Value::Null)
+    }
+
+    // `oneOf` and `anyOf` are served as one of their options: the first one the
+    // discriminator maps, or else the first declared. The discriminator property
+    // is set to the value naming the option
+    fn generate_choice(
+        &self,
+        options: &[ReferenceOr<Schema>],
+        discriminator: Option<&Discriminator>,
+        field: Option<&str>,
+    ) -> Value {
+        let mapped = discriminator.and_then(|discriminator| {
+            discriminator.mapping.iter().find_map(|(tag, target)| {
+                let option = options.iter().find(|option| refers_to(option, target))?;
+                Some((tag.clone(), option))
+            })
+        });
+        let (tag, option) = match mapped {
+            Some((tag, option)) => (Some(tag), option),
+            None => match options.first() {
+                Some(option) => (schema_name(option).map(str::to_string), option),
+                None => return Value::Null,
+            },
+        };
+
+        let mut value = self.generate_field(option, field);
+        if let (Some(discriminator), Some(tag), Value::Object(object)) =
+            (discriminator, tag, &mut value)
+        {
+            object.insert(discriminator.property_name.clone(), Value::String(tag));
+        }
+        value
+    }
+
+    fn generate_object(&self, object: &ObjectType) -> Value {
+        let Some(mut result) = self.generate_properties(&object.properties) else {
+            return Value::Object(Map::new());
+        };
+
+        // A required field without a declared schema still has to be present
+        for name in &object.required {
+            if !result.contains_key(name) {
+                result.insert(name.clone(), Value::Null);
+            }
+        }
+
+        Value::Object(result)
+    }
+
+    // `None` past `max_depth`, the object then being left empty
+    fn generate_properties<'s>(
+        &self,
+        properties: impl IntoIterator<Item = (&'s String, &'s ReferenceOr<Box<Schema>>)>,
+    ) -> Option<Map<String, Value>> {
+        self.nested(|| {
+            properties
+                .into_iter()
+                .map(|(name, property)| (name.clone(), self.generate_boxed(property, Some(name))))
+                .collect()
+        })
+    }
+
+    // The value of one item, `None` when the array has to stay empty: past
+    // `max_depth`, or when the items cycle back to an enclosing schema, as
+    // the leaves of a tree do
+    fn generate_items(&self, items: &ReferenceOr<Box<Schema>>) -> Option<Value> {
+        if let ReferenceOr::Reference { reference } = items {
+            if self.is_visiting(reference) {
+                return None;
+            }
+        }
+        self.nested(|| self.generate_boxed(items, None))
+    }
+
+    fn generate_array(&self, array: &ArrayType) -> Value {
+        let Some(item) = array
+            .items
+            .as_ref()
+            .and_then(|items| self.generate_items(items))
+        else {
+            return Value::Array(Vec::new());
+        };
+
+        let count = array
+            .min_items
+            .unwrap_or(0)
+            .max(1)
+            .min(array.max_items.unwrap_or(usize::MAX));
+        Value::Array(vec![item; count])
+    }
+
+    // A declared pattern wins, then a fake value matching the format or the
+    // field's name, as long as it fits the length constraints
+    fn generate_string(&self, string: &StringType, field: Option<&str>) -> String {
+        if let Some(pattern) = &string.pattern {
+            match sample_pattern(pattern) {
+                Ok(value) => return value,
+                Err(e) => warn!("Cannot generate a value for pattern {}: {}", pattern, e),
+            }
+        }
+
+        let format = match &string.format {
+            VariantOrUnknownOrEmpty::Item(StringFormat::DateTime) => Some("date-time"),
+            VariantOrUnknownOrEmpty::Item(StringFormat::Date) => Some("date"),
+            VariantOrUnknownOrEmpty::Item(StringFormat::Password) => Some("password"),
+            VariantOrUnknownOrEmpty::Item(StringFormat::Byte) => Some("byte"),
+            VariantOrUnknownOrEmpty::Item(StringFormat::Binary) => Some("binary"),
+            VariantOrUnknownOrEmpty::Unknown(format) => Some(format.as_str()),
+            _ => None,
+        };
+        let min_length = string.min_length.unwrap_or(0);
+        let max_length = string.max_length.unwrap_or(usize::MAX);
+
+        let fake = fake_data::fake_string(format, field, &mut self.rng.borrow_mut());
+        match fake {
+            Some(value) if (min_length..=max_length).contains(&value.chars().count()) => value,
+            _ => placeholder_string(min_length, max_length),
+        }
+    }
+}
+
+// Mapping targets are either references or bare schema names
+fn refers_to(option: &ReferenceOr<Schema>, target: &str) -> bool {
+    match option {
+        ReferenceOr::Reference { reference } => {
+            reference == target || schema_name(option) == Some(target)
+        }
+        ReferenceOr::Item(_) => false,
+    }
+}
+
+// The name of a referenced schema, which is its discriminator value unless
+// the mapping says otherwise
+fn schema_name(option: &ReferenceOr<Schema>) -> Option<&str> {
+    match option {
+        ReferenceOr::Reference { reference } => reference.rsplit('/').next(),
+        ReferenceOr::Item(_) => None,
+    }
+}
+
+// Deep enough for any hand-written schema, shallow enough for the stack
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+// Fake values don't change between runs either, unless `--seed` picks
+// another set of them
+const FAKE_SEED: u64 = 0;
+
+// Generated values must not change between runs, so patterns are
+// expanded with a fixed seed
+const PATTERN_SEED: u64 = 0;
+
+// Longest run a `*` or `+` in a pattern expands to
+const PATTERN_MAX_REPEAT: u32 = 8;
+
+fn placeholder_string(min_length: usize, max_length: usize) -> String {
+    let length = "string".len().max(min_length).min(max_length);
+    "string".chars().cycle().take(length).collect()
+}
+
+fn sample_pattern(pattern: &str) -> Result<String, String> {
+    // Patterns usually match the whole value, and the generator can't
+    // handle anchors, which would be satisfied anyway
+    let pattern = pattern.strip_prefix('^').unwrap_or(pattern);
+    let pattern = pattern.strip_suffix('$').unwrap_or(pattern);
+
+    // Without Unicode classes `\d` and `\w` stay ASCII, as in the ECMA regexes
+    // of JSON Schema. Patterns that could then match invalid UTF-8, like `.`,
+    // are given Unicode back
+    let regex = match compile_pattern(pattern, false) {
+        Ok(regex) if regex.is_utf8() => regex,
+        _ => compile_pattern(pattern, true)?,
+    };
+    Ok(StdRng::seed_from_u64(PATTERN_SEED).sample(&regex))
+}
+
+fn compile_pattern(pattern: &str, unicode: bool) -> Result<rand_regex::Regex, String> {
+    let hir = regex_syntax::ParserBuilder::new()
+        .unicode(unicode)
+        .utf8(unicode)
+        .build()
+        .parse(pattern)
+        .map_err(|e| e.to_string())?;
+    rand_regex::Regex::with_hir(hir, PATTERN_MAX_REPEAT).map_err(|e| e.to_string())
+}
+
+fn generate_integer(integer: &IntegerType) -> i64 {
+    let minimum = integer.minimum.map(|min| {
+        if integer.exclusive_minimum {
+            min.saturating_add(1)
+        } else {
+            min
+        }
+    });
+    let maximum = integer.maximum.map(|max| {
+        if integer.exclusive_maximum {
+            max.saturating_sub(1)
+        } else {
+            max
+        }
+    });
+
+    let mut value = 0;
+    if let Some(minimum) = minimum {
+        value = value.max(minimum);
+    }
+    if let Some(maximum) = maximum {
+        value = value.min(maximum);
+    }
+    value
+}
+
+fn generate_number(number: &NumberType) -> f64 {
+    let mut value: f64 = 0.0;
+    if let Some(minimum) = number.minimum {
+        value = value.max(minimum);
+    }
+    if let Some(maximum) = number.maximum {
+        value = value.min(maximum);
+    }
+
+    // An exclusive bound rules out the bound itself, so step inside the range
+    let on_exclusive_bound = (number.exclusive_minimum && Some(value) == number.minimum)
+        || (number.exclusive_maximum && Some(value) == number.maximum);
+    if on_exclusive_bound {
+        value = match (number.minimum, number.maximum) {
+            (Some(minimum), Some(maximum)) => (minimum + maximum) / 2.0,
+            (Some(minimum), None) => minimum + 1.0,
+            (None, Some(maximum)) => maximum - 1.0,
+            (None, None) => value,
+        };
+    }
+    value
+}
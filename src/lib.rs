@@ -1,3 +1,37 @@
+pub mod access_log;
+pub mod admin;
+pub mod body_limit;
+pub mod circuit;
 pub mod data;
+pub mod endpoint_list;
 pub mod errors;
+pub mod external_refs;
+pub mod fake_data;
+pub mod formats;
+pub mod generator;
+pub mod pagination;
+pub mod postman;
+pub mod proxy;
+pub mod rate_limit;
+pub mod reload;
+pub mod replay;
+pub mod request_body;
+pub mod resolver;
+pub mod routes;
+pub mod schema_validator;
+pub mod self_check;
+pub mod server;
+pub mod server_headers;
+pub mod shutdown;
+pub mod spec_compat;
+pub mod spec_merge;
+pub mod spec_source;
+pub mod sse;
+pub mod startup_summary;
+pub mod swagger_assets;
+pub mod sync_examples;
+pub mod templating;
+pub mod tls;
 pub mod transactions;
+pub mod validation;
+pub mod websocket;
@@ -1,71 +1,306 @@
-use actix_cors::Cors;
-use actix_web::{web, App, HttpServer};
-use clap::Parser;
 use log::info;
+use rusty_stub_api::access_log::{init_logger, log_filter};
 use rusty_stub_api::data::app::AppState;
 use rusty_stub_api::data::cli_args::Args;
+use rusty_stub_api::data::cors::CorsConfig;
+use rusty_stub_api::data::recorder::Recorder;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::endpoint_list;
+use rusty_stub_api::errors::AppError;
+use rusty_stub_api::postman;
+use rusty_stub_api::proxy::Upstream;
+use rusty_stub_api::reload::watch_spec;
+use rusty_stub_api::replay::replay;
+use rusty_stub_api::self_check;
+use rusty_stub_api::server::{run_server, socket_address, ServerConfig};
+use rusty_stub_api::spec_merge::load_merged;
+use rusty_stub_api::spec_source::SpecSource;
+use rusty_stub_api::startup_summary;
+use rusty_stub_api::sync_examples;
+use rusty_stub_api::tls::load_server_config;
 use rusty_stub_api::transactions::{
-    api_redirect, build_endpoints_from_spec, dynamic_handler, health_check, list_endpoints,
-    show_openapi_spec, swagger_ui,
+    build_endpoints_from_document, parse_openapi_document, BuiltEndpoints,
 };
-use std::path::Path;
+use rusty_stub_api::validation::validate_spec;
 use std::sync::Arc;
 #[actix_web::main]
 
 async fn main() -> std::io::Result<()> {
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    let args = match Args::load() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                e.to_string(),
+            ));
+        }
+    };
+    init_logger(args.log_format, log_filter(args.quiet, args.verbose));
+
+    if let Some(recording) = &args.replay {
+        let target = args
+            .replay_target
+            .clone()
+            .unwrap_or_else(|| format!("http://{}/api", socket_address(&args.host[0], args.port)));
+        let summary = match replay(recording, &target).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("Error replaying {}: {}", recording.display(), e);
+                return Err(std::io::Error::other(e.to_string()));
+            }
+        };
+
+        for mismatch in &summary.mismatches {
+            eprintln!("mismatch: {}", mismatch);
+        }
+        println!(
+            "Replayed {} requests against {}: {} matched, {} mismatched",
+            summary.matched + summary.mismatches.len(),
+            target,
+            summary.matched,
+            summary.mismatches.len()
+        );
+        if !summary.is_success() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let sources: Vec<SpecSource> = args
+        .spec
+        .iter()
+        .map(|spec| SpecSource::from(spec.as_str()))
+        .collect();
+
+    for source in &sources {
+        if let SpecSource::File(spec_path) = source {
+            if !(spec_path.exists()) {
+                eprintln!("Spec file not found: {}", source);
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("OpenAPI spec file not found: {}", source),
+                ));
+            }
+        }
+    }
 
-    let args = Args::parse();
+    // Several specs are served as the one document merging them
+    let document = match load_merged(&sources, args.strict) {
+        Ok(document) => document,
+        Err(e) => {
+            eprintln!("Error reading spec {}", e);
+            return Err(std::io::Error::other(e.to_string()));
+        }
+    };
 
-    let spec_path = Path::new(&args.spec);
+    let mut openapi_spec = match parse_openapi_document(&document) {
+        Ok(spec) => spec,
+        Err(e) => {
+            eprintln!("Error loading spec {}", e);
+            return Err(std::io::Error::other(e.to_string()));
+        }
+    };
 
-    if !(spec_path.exists()) {
-        eprintln!("Spec file not found: {}", args.spec);
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("OpenAPI spec file not found: {}", args.spec),
-        ));
+    // Report every problem at once rather than failing on the first one
+    let report = validate_spec(&openapi_spec);
+    for warning in &report.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    for error in &report.errors {
+        eprintln!("error: {}", error);
+    }
+    if report.is_fatal(args.strict) {
+        return Err(std::io::Error::other(format!(
+            "OpenAPI spec is invalid: {} errors, {} warnings",
+            report.errors.len(),
+            report.warnings.len()
+        )));
     }
 
-    let endpoints = match build_endpoints_from_spec(spec_path) {
-        Ok(eps) => eps,
+    let settings = Settings::from(&args);
+
+    let cors_config = match CorsConfig::from_args(&args) {
+        Ok(config) => config,
         Err(e) => {
-            eprintln!("Error building endpoints {}", e);
+            eprintln!("{}", e);
             return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
+                std::io::ErrorKind::InvalidInput,
                 e.to_string(),
             ));
         }
     };
 
+    let BuiltEndpoints { endpoints, skipped } =
+        match build_endpoints_from_document(&document, &settings) {
+            Ok(built) => built,
+            Err(e) => {
+                eprintln!("Error building endpoints {}", e);
+                return Err(std::io::Error::other(e.to_string()));
+            }
+        };
+
     info!("Loaded {} endpoints from OpenAPI spec", endpoints.len());
 
-    let app_state = Arc::new(AppState::new_with_spec_path(endpoints, spec_path));
-
-    let bind_addr = format!("{}:{}", args.host, args.port);
-    info!("Starting server on {}", bind_addr);
-
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header();
-
-        App::new()
-            .wrap(cors)
-            .app_data(web::Data::new(app_state.clone()))
-            // API documentation routes
-            .route("/", web::get().to(swagger_ui))
-            .route("/docs", web::get().to(swagger_ui))
-            .route("api/openapi.json", web::get().to(show_openapi_spec))
-            .route("api/endpoints", web::get().to(list_endpoints))
-            .route("/health", web::get().to(health_check))
-            // Direct API routes (for swagger UI to use)
-            .route("/api/{path:.*}", web::to(api_redirect))
-            // Route everything else to the dynamic handler
-            .route("/{method}/{path:.*}", web::to(dynamic_handler))
-    })
-    .bind(bind_addr)?
-    .run()
-    .await
+    if args.self_check {
+        let report = self_check::run(&openapi_spec, &endpoints);
+        if report.is_fatal(args.strict) {
+            return Err(std::io::Error::other(format!(
+                "Self-check failed for {} endpoints",
+                report.failed.len()
+            )));
+        }
+    }
+    let addresses = listen_addresses(&args);
+    let summary =
+        startup_summary::render(&openapi_spec, &addresses.join(", "), &endpoints, &skipped);
+
+    if args.list {
+        print!("{}", endpoint_list::render(&endpoints, args.format));
+        return Ok(());
+    }
+    if let Some(export_path) = &args.export_postman {
+        let base_url = format!("{}/api", addresses[0]);
+        let collection = postman::collection(&openapi_spec, &endpoints, &base_url);
+        if let Err(e) = std::fs::write(export_path, format!("{:#}\n", collection)) {
+            eprintln!("Error writing {}: {}", export_path.display(), e);
+            return Err(e);
+        }
+        println!(
+            "Wrote a Postman collection of {} endpoints to {}",
+            endpoints.len(),
+            export_path.display()
+        );
+        return Ok(());
+    }
+
+    if settings.sync_examples {
+        sync_examples::apply(&mut openapi_spec, &endpoints);
+    }
+    let mut app_state = AppState::from_config(
+        settings.clone(),
+        BuiltEndpoints { endpoints, skipped },
+        openapi_spec,
+    )
+    .with_sources(sources.clone());
+    if let Some(body_path) = &args.not_found_body {
+        let body = std::fs::read_to_string(body_path)
+            .map_err(AppError::from)
+            .and_then(|content| Ok(serde_json::from_str(&content)?));
+        match body {
+            Ok(body) => app_state = app_state.with_not_found_body(body),
+            Err(e) => {
+                eprintln!("Error reading {}: {}", body_path.display(), e);
+                return Err(std::io::Error::other(e.to_string()));
+            }
+        }
+    }
+    if let Some(record_path) = &args.record {
+        match Recorder::open(record_path) {
+            Ok(recorder) => app_state = app_state.with_recorder(recorder),
+            Err(e) => {
+                eprintln!("Error opening record file {}", e);
+                return Err(std::io::Error::other(e.to_string()));
+            }
+        }
+    }
+    if let Some(upstream_url) = &args.proxy_upstream {
+        match Upstream::new(upstream_url) {
+            Ok(upstream) => app_state = app_state.with_upstream(upstream),
+            Err(e) => {
+                eprintln!("{}", e);
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    e.to_string(),
+                ));
+            }
+        }
+    }
+    let app_state = Arc::new(app_state);
+
+    // Keep the watchers alive for as long as the server runs
+    let mut _watchers = Vec::new();
+    if args.watch {
+        for source in &sources {
+            let SpecSource::File(spec_path) = source else {
+                eprintln!("--watch requires local spec files");
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "--watch requires local spec files",
+                ));
+            };
+            match watch_spec(
+                spec_path,
+                sources.clone(),
+                settings.clone(),
+                app_state.clone(),
+            ) {
+                Ok(watcher) => _watchers.push(watcher),
+                Err(e) => {
+                    eprintln!("Error watching spec file {}", e);
+                    return Err(std::io::Error::other(e.to_string()));
+                }
+            }
+        }
+    }
+
+    let tls_config = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => match load_server_config(cert, key) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                eprintln!("{}", e);
+                return Err(std::io::Error::other(e.to_string()));
+            }
+        },
+        (None, None) => None,
+        _ => {
+            eprintln!("--tls-cert and --tls-key must be given together");
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "--tls-cert and --tls-key must be given together",
+            ));
+        }
+    };
+
+    let mut config = ServerConfig::new(app_state)
+        .with_addresses(&args.host, args.port)
+        .with_cors(cors_config)
+        .with_log_format(args.log_format)
+        .with_shutdown_timeout(args.shutdown_timeout)
+        .with_keep_alive(args.keep_alive)
+        .with_http2(args.http2);
+    if let Some(workers) = args.workers {
+        config = config.with_workers(workers as usize);
+    }
+    if let Some(path) = &args.unix_socket {
+        config = config.with_unix_socket(path.clone());
+    }
+    if let Some(tls_config) = tls_config {
+        config = config.with_tls(tls_config);
+    }
+    print!("{}", summary);
+    run_server(config).await
+}
+
+// Where the server can be reached, as shown by the startup summary
+fn listen_addresses(args: &Args) -> Vec<String> {
+    if let Some(path) = &args.unix_socket {
+        return vec![format!("unix:{}{}", path.display(), args.base_path)];
+    }
+    let scheme = if args.tls_cert.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    args.host
+        .iter()
+        .map(|host| {
+            format!(
+                "{}://{}{}",
+                scheme,
+                socket_address(host, args.port),
+                args.base_path
+            )
+        })
+        .collect()
 }
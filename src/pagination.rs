@@ -0,0 +1,94 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+// How a paginated operation's response is laid out. Detected when the operation
+// takes a `page` or `limit` query parameter and answers with a list
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pagination {
+    // The field holding the items when the list is wrapped in an object,
+    // `None` when the body is the list itself
+    pub items_field: Option<String>,
+    // The `default` of the operation's `limit` parameter
+    pub default_limit: Option<usize>,
+}
+
+impl Pagination {
+    pub fn detect(body: &Value, default_limit: Option<usize>) -> Option<Self> {
+        let items_field = match body {
+            Value::Array(_) => None,
+            Value::Object(fields) => Some(
+                fields
+                    .iter()
+                    .find(|(_, field)| field.is_array())
+                    .map(|(name, _)| name.clone())?,
+            ),
+            _ => return None,
+        };
+
+        Some(Pagination {
+            items_field,
+            default_limit,
+        })
+    }
+}
+
+// Expands the stub's items into a collection of `size` items and returns the
+// page the query asks for. `page` starts at 1 and `limit` defaults to the
+// whole collection. A wrapper's `total`, `page` and `limit` fields are filled in
+pub fn paginate(
+    body: &Value,
+    pagination: &Pagination,
+    query: &HashMap<String, String>,
+    size: usize,
+) -> Value {
+    let template = match &pagination.items_field {
+        Some(field) => body.get(field),
+        None => Some(body),
+    };
+    let Some(template) = template
+        .and_then(Value::as_array)
+        .filter(|items| !items.is_empty())
+    else {
+        return body.clone();
+    };
+
+    let page = query
+        .get("page")
+        .and_then(|page| page.parse::<usize>().ok())
+        .filter(|page| *page > 0)
+        .unwrap_or(1);
+    let limit = query
+        .get("limit")
+        .and_then(|limit| limit.parse::<usize>().ok())
+        .or(pagination.default_limit)
+        .unwrap_or(size);
+
+    let start = (page - 1).saturating_mul(limit);
+    let items: Vec<Value> = (start..size)
+        .take(limit)
+        .map(|index| numbered_item(&template[index % template.len()], index))
+        .collect();
+
+    let Some(field) = &pagination.items_field else {
+        return Value::Array(items);
+    };
+    let mut wrapper = body.clone();
+    if let Value::Object(fields) = &mut wrapper {
+        fields.insert(field.clone(), Value::Array(items));
+        for (name, value) in [("total", size), ("page", page), ("limit", limit)] {
+            if let Some(existing) = fields.get_mut(name) {
+                *existing = Value::from(value);
+            }
+        }
+    }
+    wrapper
+}
+
+// Gives each copy of an item with an integer `id` its own, starting from 1
+fn numbered_item(item: &Value, index: usize) -> Value {
+    let mut item = item.clone();
+    if let Some(id) = item.get_mut("id").filter(|id| id.is_i64() || id.is_u64()) {
+        *id = Value::from(index + 1);
+    }
+    item
+}
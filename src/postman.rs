@@ -0,0 +1,163 @@
+use crate::data::app::EndpointHandler;
+use crate::formats;
+use crate::generator::SchemaGenerator;
+use openapiv3::OpenAPI;
+use serde_json::{json, Value};
+
+const SCHEMA: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+// A Postman v2.1 collection of the endpoints, written by `--export-postman`.
+// Requests go to `{{baseUrl}}`, set to `base_url`, and carry every stub
+// response as a saved example. They are grouped in folders by the first
+// segment of their path, in the order the spec declares them
+pub fn collection(spec: &OpenAPI, endpoints: &[EndpointHandler], base_url: &str) -> Value {
+    let generator = SchemaGenerator::new(spec);
+
+    // Every declared response is its own endpoint, an operation being the
+    // endpoints sharing a method and a path
+    let mut operations: Vec<Vec<&EndpointHandler>> = Vec::new();
+    for endpoint in endpoints {
+        match operations.iter_mut().find(|operation| {
+            operation[0].method == endpoint.method && operation[0].path == endpoint.path
+        }) {
+            Some(operation) => operation.push(endpoint),
+            None => operations.push(vec![endpoint]),
+        }
+    }
+
+    let mut folders: Vec<(String, Vec<Value>)> = Vec::new();
+    let mut items = Vec::new();
+    for operation in operations {
+        let item = operation_item(&operation, &generator);
+        match operation[0]
+            .path
+            .split('/')
+            .find(|segment| !segment.is_empty())
+        {
+            Some(segment) => match folders.iter_mut().find(|(name, _)| name == segment) {
+                Some((_, folder)) => folder.push(item),
+                None => folders.push((segment.to_string(), vec![item])),
+            },
+            // `/` itself stays at the top
+            None => items.push(item),
+        }
+    }
+    items.extend(
+        folders
+            .into_iter()
+            .map(|(name, item)| json!({ "name": name, "item": item })),
+    );
+
+    json!({
+        "info": {
+            "name": spec.info.title,
+            "description": spec.info.description.clone().unwrap_or_default(),
+            "schema": SCHEMA,
+        },
+        "item": items,
+        "variable": [{ "key": "baseUrl", "value": base_url }],
+    })
+}
+
+fn operation_item(operation: &[&EndpointHandler], generator: &SchemaGenerator) -> Value {
+    let endpoint = operation[0];
+    let method = endpoint.method.to_uppercase();
+
+    let mut request = json!({
+        "method": method,
+        "header": [],
+        "url": url(&endpoint.path),
+    });
+    if let Some(schema) = &endpoint.request_schema {
+        request["header"] = json!([{ "key": "Content-Type", "value": "application/json" }]);
+        request["body"] = json!({
+            "mode": "raw",
+            "raw": format!("{:#}", generator.generate(schema)),
+            "options": { "raw": { "language": "json" } },
+        });
+    }
+
+    let responses: Vec<Value> = operation
+        .iter()
+        .map(|endpoint| example_response(endpoint, &request))
+        .collect();
+
+    json!({
+        "name": format!("{} {}", method, endpoint.path),
+        "request": request,
+        "response": responses,
+    })
+}
+
+// Path parameters are written `:name` by Postman, which lists them as variables
+fn url(path: &str) -> Value {
+    let mut variables = Vec::new();
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let mut converted = String::new();
+            let mut rest = segment;
+            while let Some(open) = rest.find('{') {
+                let Some(close) = rest[open..].find('}').map(|offset| open + offset) else {
+                    break;
+                };
+                let name = &rest[open + 1..close];
+                converted.push_str(&rest[..open]);
+                converted.push(':');
+                converted.push_str(name);
+                variables.push(json!({ "key": name, "value": "" }));
+                rest = &rest[close + 1..];
+            }
+            converted.push_str(rest);
+            converted
+        })
+        .collect();
+
+    json!({
+        "raw": format!("{{{{baseUrl}}}}/{}", segments.join("/")),
+        "host": ["{{baseUrl}}"],
+        "path": segments,
+        "variable": variables,
+    })
+}
+
+fn example_response(endpoint: &EndpointHandler, request: &Value) -> Value {
+    let content_type = endpoint
+        .content_types
+        .first()
+        .map(String::as_str)
+        .unwrap_or("application/json");
+    // Files can't be saved as the text of an example
+    let binary =
+        content_type.starts_with("application/octet-stream") || content_type.starts_with("image/");
+    let body = if endpoint.empty_body || binary {
+        String::new()
+    } else if content_type.contains("json") {
+        format!("{:#}", endpoint.response_body)
+    } else {
+        formats::render_body(
+            content_type,
+            &endpoint.response_body,
+            endpoint.response_xml.as_ref(),
+            false,
+        )
+    };
+    let language = if content_type.contains("json") {
+        "json"
+    } else if content_type.contains("xml") {
+        "xml"
+    } else {
+        "text"
+    };
+
+    json!({
+        "name": endpoint.response_code,
+        "originalRequest": request,
+        "status": endpoint.status.canonical_reason().unwrap_or_default(),
+        "code": endpoint.status.as_u16(),
+        "_postman_previewlanguage": language,
+        "header": [{ "key": "Content-Type", "value": content_type }],
+        "body": body,
+    })
+}
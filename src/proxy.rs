@@ -0,0 +1,108 @@
+use crate::errors::AppError;
+use actix_web::http::header::HeaderMap;
+use actix_web::HttpResponse;
+use futures_util::TryStreamExt;
+use log::{info, warn};
+use reqwest::Url;
+
+// Headers that only describe a single connection, so they are never forwarded
+const HOP_BY_HOP_HEADERS: [&str; 9] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "host",
+];
+
+// A live backend answering the requests no stub matches, set with `--proxy-upstream`
+pub struct Upstream {
+    base_url: Url,
+    client: reqwest::Client,
+}
+
+impl Upstream {
+    pub fn new(base_url: &str) -> Result<Self, AppError> {
+        let base_url = Url::parse(base_url)
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .ok_or_else(|| {
+                AppError::ConfigError(format!("invalid proxy upstream: {}", base_url))
+            })?;
+
+        Ok(Upstream {
+            base_url,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    // Sends the request on to the upstream and streams its response back
+    pub async fn forward(
+        &self,
+        method: &str,
+        path: &str,
+        query_string: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> HttpResponse {
+        let mut url = format!("{}{}", self.base_url.as_str().trim_end_matches('/'), path);
+        if !query_string.is_empty() {
+            url = format!("{}?{}", url, query_string);
+        }
+
+        let Ok(method) = reqwest::Method::from_bytes(method.to_uppercase().as_bytes()) else {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid method",
+                "method": method,
+            }));
+        };
+
+        let mut request = self
+            .client
+            .request(method.clone(), &url)
+            .body(body.to_vec());
+        for (name, value) in headers {
+            if !is_hop_by_hop(name.as_str()) && name != "content-length" {
+                request = request.header(name.as_str(), value.as_bytes());
+            }
+        }
+
+        let upstream_response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Upstream request {} {} failed: {}", method, url, e);
+                return HttpResponse::BadGateway().json(serde_json::json!({
+                    "error": "Upstream request failed",
+                    "details": e.to_string(),
+                }));
+            }
+        };
+        info!(
+            "Upstream answered {} {} with {}",
+            method,
+            url,
+            upstream_response.status()
+        );
+
+        let status = actix_web::http::StatusCode::from_u16(upstream_response.status().as_u16())
+            .unwrap_or(actix_web::http::StatusCode::BAD_GATEWAY);
+        let mut builder = HttpResponse::build(status);
+        for (name, value) in upstream_response.headers() {
+            // The body is re-streamed, so its length and encoding are set anew
+            if !is_hop_by_hop(name.as_str()) && name != "content-length" {
+                builder.append_header((name.as_str(), value.as_bytes()));
+            }
+        }
+        builder.streaming(upstream_response.bytes_stream().map_err(|e| {
+            warn!("Upstream response stream failed: {}", e);
+            actix_web::error::ErrorBadGateway(e)
+        }))
+    }
+}
+
+fn is_hop_by_hop(name: &str) -> bool {
+    HOP_BY_HOP_HEADERS.contains(&name)
+}
@@ -0,0 +1,56 @@
+use crate::data::app::AppState;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header;
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpResponse};
+use std::sync::Arc;
+use std::time::Instant;
+
+// Middleware answering 429 to clients over `--rate-limit`. Clients are told
+// apart by their IP, or the `X-Forwarded-For` one under `--trust-forwarded`
+pub async fn rate_limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let retry_after = req
+        .app_data::<web::Data<Arc<AppState>>>()
+        .and_then(|app_state| {
+            let limiter = app_state.rate_limiter.as_ref()?;
+            let client = client_key(&req, app_state.settings.trust_forwarded);
+            limiter.acquire(&client, Instant::now()).err()
+        });
+
+    let Some(retry_after) = retry_after else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+
+    // `Retry-After` takes whole seconds, rounding down could retry too early
+    let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    let response = HttpResponse::TooManyRequests()
+        .insert_header((header::RETRY_AFTER, seconds.to_string()))
+        .json(serde_json::json!({
+            "error": "Too many requests",
+            "retry_after": seconds,
+        }));
+    Ok(req.into_response(response).map_into_right_body())
+}
+
+fn client_key(req: &ServiceRequest, trust_forwarded: bool) -> String {
+    if trust_forwarded {
+        // The first address is the client, the rest are the proxies it went through
+        let forwarded = req
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next())
+            .map(str::trim)
+            .filter(|client| !client.is_empty());
+        if let Some(client) = forwarded {
+            return client.to_string();
+        }
+    }
+    req.peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
@@ -0,0 +1,142 @@
+use crate::data::app::{AppState, EndpointHandler, ReloadState};
+use crate::data::settings::Settings;
+use crate::errors::AppError;
+use crate::spec_merge::load_merged;
+use crate::spec_source::SpecSource;
+use crate::sync_examples;
+use crate::transactions::{build_endpoints_from_document, parse_openapi_document};
+use crate::validation::validate_spec;
+use log::{error, info, warn};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// What a reload changed, each endpoint named as in `GET /users 200`
+#[derive(Debug, Default, Serialize)]
+pub struct ReloadSummary {
+    pub total: usize,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+// Rebuilds the endpoints from the specs on disk and swaps them into the state.
+// On failure nothing is replaced and the previous specs keep being served,
+// though the server reports itself as not ready until a reload succeeds
+pub fn reload_spec(
+    app_state: &AppState,
+    sources: &[SpecSource],
+    settings: &Settings,
+) -> Result<ReloadSummary, AppError> {
+    app_state.set_reload_state(ReloadState::InProgress);
+    let result = rebuild(app_state, sources, settings);
+    app_state.set_reload_state(match &result {
+        Ok(_) => ReloadState::Idle,
+        Err(e) => ReloadState::Failed(e.to_string()),
+    });
+    result
+}
+
+fn rebuild(
+    app_state: &AppState,
+    sources: &[SpecSource],
+    settings: &Settings,
+) -> Result<ReloadSummary, AppError> {
+    let document = load_merged(sources, settings.strict)?;
+    let built = build_endpoints_from_document(&document, settings)?;
+    let mut openapi_spec = parse_openapi_document(&document)?;
+
+    // Held to the same checks as at startup, all problems listed at once
+    let report = validate_spec(&openapi_spec);
+    for warning in &report.warnings {
+        warn!("{}", warning);
+    }
+    if report.is_fatal(settings.strict) {
+        let problems: Vec<String> = report
+            .errors
+            .iter()
+            .chain(&report.warnings)
+            .map(|problem| match problem {
+                AppError::ValidationError(message) => message.clone(),
+                other => other.to_string(),
+            })
+            .collect();
+        return Err(AppError::ValidationError(problems.join("; ")));
+    }
+    if settings.sync_examples {
+        sync_examples::apply(&mut openapi_spec, &built.endpoints);
+    }
+
+    let before = endpoint_names(&app_state.endpoints.read().unwrap());
+    let after = endpoint_names(&built.endpoints);
+
+    app_state.replace(built, openapi_spec);
+    Ok(ReloadSummary {
+        total: after.len(),
+        added: after.difference(&before).cloned().collect(),
+        removed: before.difference(&after).cloned().collect(),
+    })
+}
+
+fn endpoint_names(endpoints: &[EndpointHandler]) -> BTreeSet<String> {
+    endpoints
+        .iter()
+        .map(|endpoint| {
+            format!(
+                "{} {} {}",
+                endpoint.method.to_uppercase(),
+                endpoint.path,
+                endpoint.response_code
+            )
+        })
+        .collect()
+}
+
+// Reloads every spec whenever the one at `spec_path` changes. The watcher
+// stops when dropped, so the caller has to keep it alive for as long as the
+// server runs
+pub fn watch_spec(
+    spec_path: &Path,
+    sources: Vec<SpecSource>,
+    settings: Settings,
+    app_state: Arc<AppState>,
+) -> Result<RecommendedWatcher, AppError> {
+    let spec_path = spec_path.to_path_buf();
+    let file_name = spec_path.file_name().map(|name| name.to_os_string());
+
+    // Editors often replace the file instead of writing to it, so the parent
+    // directory is watched and events are filtered by file name
+    let watch_dir = match spec_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        let event = match res {
+            Ok(event) => event,
+            Err(e) => {
+                error!("Error watching spec file: {}", e);
+                return;
+            }
+        };
+
+        let touches_spec = event
+            .paths
+            .iter()
+            .any(|path| path.file_name().map(|name| name.to_os_string()) == file_name);
+        if !touches_spec || !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+            return;
+        }
+
+        match reload_spec(&app_state, &sources, &settings) {
+            Ok(summary) => info!("Reloaded {} endpoints from OpenAPI spec", summary.total),
+            Err(e) => error!("Failed to reload spec, keeping the previous one: {}", e),
+        }
+    })?;
+
+    watcher.watch(&watch_dir, RecursiveMode::NonRecursive)?;
+    info!("Watching {} for changes", spec_path.display());
+
+    Ok(watcher)
+}
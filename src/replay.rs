@@ -0,0 +1,106 @@
+use crate::errors::AppError;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use log::info;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+// One line of a `--record` file
+#[derive(Debug, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+    #[serde(default)]
+    query: HashMap<String, String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: Option<Value>,
+    // Base64 of the body as it was sent, missing from older recordings
+    #[serde(default)]
+    raw_body: Option<String>,
+    status: u16,
+}
+
+#[derive(Debug, Default)]
+pub struct ReplaySummary {
+    pub matched: usize,
+    // One description per request answered with another status than recorded
+    pub mismatches: Vec<String>,
+}
+
+impl ReplaySummary {
+    pub fn is_success(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+// Sends every request of a recording to `target` and compares the statuses.
+// Recorded paths are relative to the operation, so against this server the
+// target is its `/api` prefix, e.g. `http://127.0.0.1:8080/api`
+pub async fn replay(recording: &Path, target: &str) -> Result<ReplaySummary, AppError> {
+    let content = std::fs::read_to_string(recording)?;
+    let client = reqwest::Client::new();
+    let target = target.trim_end_matches('/');
+    let mut summary = ReplaySummary::default();
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let recorded: RecordedRequest = serde_json::from_str(line).map_err(|e| {
+            AppError::ParseError(format!("line {} of the recording: {}", index + 1, e))
+        })?;
+
+        let method = reqwest::Method::from_bytes(recorded.method.to_uppercase().as_bytes())
+            .map_err(|_| {
+                AppError::ParseError(format!(
+                    "line {} of the recording: invalid method {}",
+                    index + 1,
+                    recorded.method
+                ))
+            })?;
+        let description = format!("{} {}", method, recorded.path);
+
+        let mut request = client
+            .request(method, format!("{}{}", target, recorded.path))
+            .query(&recorded.query);
+        for (name, value) in &recorded.headers {
+            if name != "host" && name != "content-length" {
+                request = request.header(name, value);
+            }
+        }
+        // The recorded headers already carry the content type. Recordings
+        // without the raw body only have it as JSON
+        if let Some(raw_body) = &recorded.raw_body {
+            let raw_body = STANDARD.decode(raw_body).map_err(|e| {
+                AppError::ParseError(format!(
+                    "line {} of the recording: invalid raw_body: {}",
+                    index + 1,
+                    e
+                ))
+            })?;
+            request = request.body(raw_body);
+        } else if let Some(body) = &recorded.body {
+            request = request.body(body.to_string());
+        }
+
+        let status = match request.send().await {
+            Ok(response) => response.status().as_u16().to_string(),
+            Err(e) => format!("no response ({})", e),
+        };
+        if status == recorded.status.to_string() {
+            info!("{}: {} as recorded", description, status);
+            summary.matched += 1;
+        } else {
+            summary.mismatches.push(format!(
+                "{}: expected {}, got {}",
+                description, recorded.status, status
+            ));
+        }
+    }
+
+    Ok(summary)
+}
@@ -0,0 +1,153 @@
+use crate::formats::xml;
+use log::info;
+use serde_json::{Map, Value};
+
+// A field of a form body, `filename` being set for file uploads
+struct FormField {
+    name: String,
+    filename: Option<String>,
+    value: Vec<u8>,
+}
+
+// Reads a request body according to its `Content-Type`. Form fields become an
+// object of strings, an uploaded file standing for its file name. XML is read
+// without a schema, its text staying strings. Anything else is read as JSON,
+// whatever type it claims
+pub fn parse(content_type: Option<&str>, raw: &[u8]) -> Option<Value> {
+    let mime = content_type
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase());
+
+    let fields = match mime.as_deref() {
+        Some("application/x-www-form-urlencoded") => parse_urlencoded(raw)?,
+        Some("multipart/form-data") => parse_multipart(boundary(content_type?)?, raw)?,
+        Some(mime) if is_xml(mime) => {
+            return xml::from_xml(std::str::from_utf8(raw).ok()?, None);
+        }
+        _ => return serde_json::from_slice(raw).ok(),
+    };
+    log_fields(&fields);
+    Some(fields_to_json(fields))
+}
+
+// `application/xml`, `text/xml` and the `+xml` types such as `application/soap+xml`
+pub fn is_xml(mime: &str) -> bool {
+    mime == "application/xml" || mime == "text/xml" || mime.ends_with("+xml")
+}
+
+fn parse_urlencoded(raw: &[u8]) -> Option<Vec<FormField>> {
+    let pairs: Vec<(String, String)> = serde_urlencoded::from_bytes(raw).ok()?;
+    let fields = pairs
+        .into_iter()
+        .map(|(name, value)| FormField {
+            name,
+            filename: None,
+            value: value.into_bytes(),
+        })
+        .collect();
+    Some(fields)
+}
+
+fn boundary(content_type: &str) -> Option<&str> {
+    content_type
+        .split(';')
+        .filter_map(|param| param.trim().strip_prefix("boundary="))
+        .map(|boundary| boundary.trim_matches('"'))
+        .find(|boundary| !boundary.is_empty())
+}
+
+// Parts are separated by `--<boundary>` lines, the last one followed by `--`.
+// Each has its headers, a blank line, then its content
+fn parse_multipart(boundary: &str, raw: &[u8]) -> Option<Vec<FormField>> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut fields = Vec::new();
+
+    let mut position = find(raw, &delimiter, 0)? + delimiter.len();
+    while !raw[position..].starts_with(b"--") {
+        let next = find(raw, &delimiter, position)?;
+        let part = raw[position..next]
+            .strip_prefix(b"\r\n")
+            .unwrap_or(&raw[position..next]);
+        let part = part.strip_suffix(b"\r\n").unwrap_or(part);
+
+        let split = find(part, b"\r\n\r\n", 0)?;
+        let headers = String::from_utf8_lossy(&part[..split]);
+        let (name, filename) = content_disposition(&headers)?;
+        fields.push(FormField {
+            name,
+            filename,
+            value: part[split + 4..].to_vec(),
+        });
+        position = next + delimiter.len();
+    }
+    Some(fields)
+}
+
+// The `name` and `filename` of a part's `Content-Disposition: form-data` header
+fn content_disposition(headers: &str) -> Option<(String, Option<String>)> {
+    let disposition = headers.lines().find_map(|line| {
+        let (header, value) = line.split_once(':')?;
+        header
+            .trim()
+            .eq_ignore_ascii_case("content-disposition")
+            .then_some(value)
+    })?;
+
+    let mut name = None;
+    let mut filename = None;
+    for param in disposition.split(';').skip(1) {
+        let Some((key, value)) = param.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"').to_string();
+        match key.trim() {
+            "name" => name = Some(value),
+            "filename" => filename = Some(value),
+            _ => {}
+        }
+    }
+    Some((name?, filename))
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| from + position)
+}
+
+// Repeated fields, e.g. `tag=a&tag=b`, are collected into a list
+fn fields_to_json(fields: Vec<FormField>) -> Value {
+    let mut object = Map::new();
+    for field in fields {
+        let value = match field.filename {
+            Some(filename) => Value::String(filename),
+            None => Value::String(String::from_utf8_lossy(&field.value).into_owned()),
+        };
+        match object.get_mut(&field.name) {
+            Some(Value::Array(values)) => values.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+            None => {
+                object.insert(field.name, value);
+            }
+        }
+    }
+    Value::Object(object)
+}
+
+fn log_fields(fields: &[FormField]) {
+    let names: Vec<String> = fields
+        .iter()
+        .map(|field| match &field.filename {
+            Some(filename) => format!(
+                "{} (file {}, {} bytes)",
+                field.name,
+                filename,
+                field.value.len()
+            ),
+            None => field.name.clone(),
+        })
+        .collect();
+    info!("Form body with fields: {}", names.join(", "));
+}
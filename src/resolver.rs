@@ -0,0 +1,121 @@
+use openapiv3::{
+    Example, Header, OpenAPI, Parameter, PathItem, ReferenceOr, RequestBody, Response, Schema,
+    SecurityScheme,
+};
+
+// Chained references are followed at most this many times, which also
+// stops a component that refers back to itself
+const MAX_REFERENCE_HOPS: usize = 16;
+
+pub fn resolve_schema<'a>(spec: &'a OpenAPI, reference: &str) -> Option<&'a Schema> {
+    let components = spec.components.as_ref()?;
+    resolve_component(
+        |name| components.schemas.get(name),
+        "#/components/schemas/",
+        reference,
+    )
+}
+
+pub fn resolve_response<'a>(spec: &'a OpenAPI, reference: &str) -> Option<&'a Response> {
+    let components = spec.components.as_ref()?;
+    resolve_component(
+        |name| components.responses.get(name),
+        "#/components/responses/",
+        reference,
+    )
+}
+
+pub fn resolve_header<'a>(spec: &'a OpenAPI, reference: &str) -> Option<&'a Header> {
+    let components = spec.components.as_ref()?;
+    resolve_component(
+        |name| components.headers.get(name),
+        "#/components/headers/",
+        reference,
+    )
+}
+
+pub fn resolve_parameter<'a>(spec: &'a OpenAPI, reference: &str) -> Option<&'a Parameter> {
+    let components = spec.components.as_ref()?;
+    resolve_component(
+        |name| components.parameters.get(name),
+        "#/components/parameters/",
+        reference,
+    )
+}
+
+pub fn resolve_example<'a>(spec: &'a OpenAPI, reference: &str) -> Option<&'a Example> {
+    let components = spec.components.as_ref()?;
+    resolve_component(
+        |name| components.examples.get(name),
+        "#/components/examples/",
+        reference,
+    )
+}
+
+pub fn resolve_request_body<'a>(spec: &'a OpenAPI, reference: &str) -> Option<&'a RequestBody> {
+    let components = spec.components.as_ref()?;
+    resolve_component(
+        |name| components.request_bodies.get(name),
+        "#/components/requestBodies/",
+        reference,
+    )
+}
+
+pub fn resolve_security_scheme<'a>(
+    spec: &'a OpenAPI,
+    reference: &str,
+) -> Option<&'a SecurityScheme> {
+    let components = spec.components.as_ref()?;
+    resolve_component(
+        |name| components.security_schemes.get(name),
+        "#/components/securitySchemes/",
+        reference,
+    )
+}
+
+// `openapiv3` does not model `components.pathItems`, so path item references
+// are looked up in the raw document and deserialized on demand
+pub fn resolve_path_item(raw_spec: &serde_yaml::Value, reference: &str) -> Option<PathItem> {
+    let mut reference = reference.to_string();
+
+    for _ in 0..MAX_REFERENCE_HOPS {
+        let node = lookup_pointer(raw_spec, &reference)?;
+        match node.get("$ref").and_then(|r| r.as_str()) {
+            Some(next) => reference = next.to_string(),
+            None => return serde_yaml::from_value(node.clone()).ok(),
+        }
+    }
+    None
+}
+
+fn resolve_component<'a, T>(
+    lookup: impl Fn(&str) -> Option<&'a ReferenceOr<T>>,
+    prefix: &str,
+    reference: &str,
+) -> Option<&'a T> {
+    let mut reference = reference;
+
+    for _ in 0..MAX_REFERENCE_HOPS {
+        let name = reference.strip_prefix(prefix)?;
+        match lookup(name)? {
+            ReferenceOr::Item(item) => return Some(item),
+            ReferenceOr::Reference { reference: next } => reference = next,
+        }
+    }
+    None
+}
+
+// Walks a local JSON pointer such as `#/components/pathItems/User`
+pub(crate) fn lookup_pointer<'a>(
+    raw_spec: &'a serde_yaml::Value,
+    reference: &str,
+) -> Option<&'a serde_yaml::Value> {
+    let pointer = reference.strip_prefix("#/")?;
+
+    let mut node = raw_spec;
+    for segment in pointer.split('/') {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        node = node.get(segment.as_str())?;
+    }
+    Some(node)
+}
@@ -0,0 +1,70 @@
+use crate::admin;
+use crate::data::app::AppState;
+use crate::swagger_assets;
+use crate::transactions::{
+    api_redirect, diagnostics, dynamic_handler, health_check, invoke_operation, list_endpoints,
+    metrics, readiness_check, serve_openapi_yaml, show_openapi_spec, swagger_ui,
+};
+use crate::websocket::websocket_handler;
+use actix_web::guard::{self, GuardContext};
+use actix_web::{web, Scope};
+use std::sync::Arc;
+
+// Registers every route the server answers. Order matters: the fixed routes
+// have to come before the `/api/{path:.*}` and `/{method}/{path:.*}` catch-alls
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg
+        // API documentation routes
+        .route("/", web::get().to(swagger_ui))
+        .route("/docs", web::get().to(swagger_ui))
+        .route(
+            "/docs/assets/{file}",
+            web::get().to(swagger_assets::serve_asset),
+        )
+        .route("/api/openapi.json", web::get().to(show_openapi_spec))
+        .route("/api/openapi.yaml", web::get().to(serve_openapi_yaml))
+        .route("/api/endpoints", web::get().to(list_endpoints))
+        .route("/api/diagnostics", web::get().to(diagnostics))
+        // Shadows `POST /api/op/...` of the spec, which is still served at `/post/op/...`
+        .route("/api/op/{operation_id}", web::post().to(invoke_operation))
+        // `/health` is kept as an alias of the liveness check
+        .route("/health", web::get().to(health_check))
+        .route("/health/live", web::get().to(health_check))
+        .route("/health/ready", web::get().to(readiness_check))
+        .route("/metrics", web::get().to(metrics))
+        .route("/admin/reload", web::post().to(admin::reload))
+        // Left to the catch-alls unless the store is in use
+        .service(
+            web::resource("/admin/state")
+                .guard(guard::fn_guard(is_stateful))
+                .route(web::get().to(admin::state))
+                .route(web::delete().to(admin::reset_state)),
+        )
+        .service(
+            web::resource("/admin/shutdown")
+                .guard(guard::fn_guard(is_admin_enabled))
+                .route(web::post().to(admin::shutdown)),
+        )
+        // Streams of the operations marked with `x-websocket`
+        .route("/ws/{path:.*}", web::get().to(websocket_handler))
+        // Direct API routes (for swagger UI to use)
+        .route("/api/{path:.*}", web::to(api_redirect))
+        // Route everything else to the dynamic handler
+        .route("/{method}/{path:.*}", web::to(dynamic_handler));
+}
+
+// Mounts the routes of `configure` under `--base-path`, which is empty when
+// they are served from the root
+pub fn scope(base_path: &str) -> Scope {
+    web::scope(base_path).configure(configure)
+}
+
+fn is_stateful(ctx: &GuardContext) -> bool {
+    ctx.app_data::<web::Data<Arc<AppState>>>()
+        .is_some_and(|app_state| app_state.settings.stateful)
+}
+
+fn is_admin_enabled(ctx: &GuardContext) -> bool {
+    ctx.app_data::<web::Data<Arc<AppState>>>()
+        .is_some_and(|app_state| app_state.settings.enable_admin)
+}
@@ -0,0 +1,219 @@
+use crate::resolver;
+use openapiv3::{OpenAPI, ReferenceOr, Schema, SchemaKind, Type};
+use serde_json::Value;
+
+// Checks JSON values against the schemas declared in an OpenAPI spec,
+// collecting one message per failing field
+pub struct SchemaValidator<'a> {
+    spec: &'a OpenAPI,
+}
+
+impl<'a> SchemaValidator<'a> {
+    pub fn new(spec: &'a OpenAPI) -> Self {
+        SchemaValidator { spec }
+    }
+
+    // `path` names the value in error messages, e.g. `body`
+    pub fn validate(&self, schema: &ReferenceOr<Schema>, value: &Value, path: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+        self.validate_ref(schema, value, path, &mut errors);
+        errors
+    }
+
+    fn validate_ref(
+        &self,
+        schema: &ReferenceOr<Schema>,
+        value: &Value,
+        path: &str,
+        errors: &mut Vec<String>,
+    ) {
+        match schema {
+            ReferenceOr::Item(schema) => self.validate_schema(schema, value, path, errors),
+            ReferenceOr::Reference { reference } => {
+                self.validate_reference(reference, value, path, errors)
+            }
+        }
+    }
+
+    fn validate_boxed(
+        &self,
+        schema: &ReferenceOr<Box<Schema>>,
+        value: &Value,
+        path: &str,
+        errors: &mut Vec<String>,
+    ) {
+        match schema {
+            ReferenceOr::Item(schema) => self.validate_schema(schema, value, path, errors),
+            ReferenceOr::Reference { reference } => {
+                self.validate_reference(reference, value, path, errors)
+            }
+        }
+    }
+
+    fn validate_reference(
+        &self,
+        reference: &str,
+        value: &Value,
+        path: &str,
+        errors: &mut Vec<String>,
+    ) {
+        // An unresolvable schema can't be checked, so it accepts anything
+        if let Some(schema) = resolver::resolve_schema(self.spec, reference) {
+            self.validate_schema(schema, value, path, errors);
+        }
+    }
+
+    fn validate_schema(
+        &self,
+        schema: &Schema,
+        value: &Value,
+        path: &str,
+        errors: &mut Vec<String>,
+    ) {
+        if value.is_null() {
+            if !schema.schema_data.nullable && !matches!(schema.schema_kind, SchemaKind::Any(_)) {
+                errors.push(format!("{}: must not be null", path));
+            }
+            return;
+        }
+
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::String(string)) => {
+                let Some(text) = value.as_str() else {
+                    return errors.push(format!("{}: expected a string", path));
+                };
+                let allowed: Vec<&String> = string.enumeration.iter().flatten().collect();
+                if !allowed.is_empty() && !allowed.iter().any(|option| *option == text) {
+                    errors.push(format!("{}: must be one of {:?}", path, allowed));
+                }
+                let length = text.chars().count();
+                if let Some(min_length) = string.min_length.filter(|min| length < *min) {
+                    errors.push(format!(
+                        "{}: must be at least {} characters",
+                        path, min_length
+                    ));
+                }
+                if let Some(max_length) = string.max_length.filter(|max| length > *max) {
+                    errors.push(format!(
+                        "{}: must be at most {} characters",
+                        path, max_length
+                    ));
+                }
+                if let Some(pattern) = &string.pattern {
+                    if let Ok(re) = regex::Regex::new(pattern) {
+                        if !re.is_match(text) {
+                            errors.push(format!("{}: must match pattern {}", path, pattern));
+                        }
+                    }
+                }
+            }
+            SchemaKind::Type(Type::Integer(integer)) => {
+                let Some(number) = value.as_i64() else {
+                    return errors.push(format!("{}: expected an integer", path));
+                };
+                let allowed: Vec<&i64> = integer.enumeration.iter().flatten().collect();
+                if !allowed.is_empty() && !allowed.contains(&&number) {
+                    errors.push(format!("{}: must be one of {:?}", path, allowed));
+                }
+                if let Some(minimum) = integer.minimum.filter(|min| number < *min) {
+                    errors.push(format!("{}: must be at least {}", path, minimum));
+                }
+                if let Some(maximum) = integer.maximum.filter(|max| number > *max) {
+                    errors.push(format!("{}: must be at most {}", path, maximum));
+                }
+            }
+            SchemaKind::Type(Type::Number(number_type)) => {
+                let Some(number) = value.as_f64() else {
+                    return errors.push(format!("{}: expected a number", path));
+                };
+                if let Some(minimum) = number_type.minimum.filter(|min| number < *min) {
+                    errors.push(format!("{}: must be at least {}", path, minimum));
+                }
+                if let Some(maximum) = number_type.maximum.filter(|max| number > *max) {
+                    errors.push(format!("{}: must be at most {}", path, maximum));
+                }
+            }
+            SchemaKind::Type(Type::Boolean {}) => {
+                if !value.is_boolean() {
+                    errors.push(format!("{}: expected a boolean", path));
+                }
+            }
+            SchemaKind::Type(Type::Object(object)) => {
+                let Some(fields) = value.as_object() else {
+                    return errors.push(format!("{}: expected an object", path));
+                };
+                for name in &object.required {
+                    if !fields.contains_key(name) {
+                        errors.push(format!("{}.{}: is required", path, name));
+                    }
+                }
+                for (name, property) in &object.properties {
+                    if let Some(field) = fields.get(name) {
+                        self.validate_boxed(property, field, &format!("{}.{}", path, name), errors);
+                    }
+                }
+            }
+            SchemaKind::Type(Type::Array(array)) => {
+                let Some(items) = value.as_array() else {
+                    return errors.push(format!("{}: expected an array", path));
+                };
+                if let Some(min_items) = array.min_items.filter(|min| items.len() < *min) {
+                    errors.push(format!("{}: must have at least {} items", path, min_items));
+                }
+                if let Some(max_items) = array.max_items.filter(|max| items.len() > *max) {
+                    errors.push(format!("{}: must have at most {} items", path, max_items));
+                }
+                if let Some(item_schema) = &array.items {
+                    for (index, item) in items.iter().enumerate() {
+                        self.validate_boxed(
+                            item_schema,
+                            item,
+                            &format!("{}[{}]", path, index),
+                            errors,
+                        );
+                    }
+                }
+            }
+            SchemaKind::AllOf { all_of } => {
+                for subschema in all_of {
+                    self.validate_ref(subschema, value, path, errors);
+                }
+            }
+            SchemaKind::OneOf { one_of: options } | SchemaKind::AnyOf { any_of: options } => {
+                let matches_any = options
+                    .iter()
+                    .any(|option| self.validate(option, value, path).is_empty());
+                if !options.is_empty() && !matches_any {
+                    errors.push(format!(
+                        "{}: does not match any of the allowed schemas",
+                        path
+                    ));
+                }
+            }
+            SchemaKind::Not { not } => {
+                if self.validate(not, value, path).is_empty() {
+                    errors.push(format!("{}: matches a schema it must not match", path));
+                }
+            }
+            SchemaKind::Any(any) => {
+                if let Some(fields) = value.as_object() {
+                    for name in &any.required {
+                        if !fields.contains_key(name) {
+                            errors.push(format!("{}.{}: is required", path, name));
+                        }
+                    }
+                    for (name, property) in &any.properties {
+                        if let Some(field) = fields.get(name) {
+                            self.validate_boxed(
+                                property,
+                                field,
+                                &format!("{}.{}", path, name),
+                                errors,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::data::app::EndpointHandler;
+use crate::schema_validator::SchemaValidator;
+use log::{info, warn};
+use openapiv3::OpenAPI;
+
+// How the stubs of `--self-check` fared against their own schemas. Endpoints
+// without a JSON schema, or without a body, have nothing to be checked against
+#[derive(Debug, Default)]
+pub struct SelfCheckReport {
+    pub passed: usize,
+    pub skipped: usize,
+    // The endpoint, e.g. `GET /users 200`, and what is wrong with its stub
+    pub failed: Vec<(String, Vec<String>)>,
+}
+
+impl SelfCheckReport {
+    pub fn is_fatal(&self, strict: bool) -> bool {
+        strict && !self.failed.is_empty()
+    }
+}
+
+pub fn run(spec: &OpenAPI, endpoints: &[EndpointHandler]) -> SelfCheckReport {
+    let validator = SchemaValidator::new(spec);
+    let mut report = SelfCheckReport::default();
+
+    for endpoint in endpoints {
+        let Some(schema) = endpoint.response_schema.as_ref() else {
+            report.skipped += 1;
+            continue;
+        };
+        if endpoint.empty_body {
+            report.skipped += 1;
+            continue;
+        }
+
+        let errors = validator.validate(schema, &endpoint.response_body, "response");
+        if errors.is_empty() {
+            report.passed += 1;
+            continue;
+        }
+        let name = format!(
+            "{} {} {}",
+            endpoint.method.to_uppercase(),
+            endpoint.path,
+            endpoint.response_code
+        );
+        for error in &errors {
+            warn!("Self-check of {}: {}", name, error);
+        }
+        report.failed.push((name, errors));
+    }
+
+    info!(
+        "Self-check: {} stubs passed, {} failed, {} without a schema",
+        report.passed,
+        report.failed.len(),
+        report.skipped
+    );
+    report
+}
@@ -0,0 +1,369 @@
+use crate::access_log::{access_log, LogFormat};
+use crate::body_limit::payload_too_large;
+use crate::data::app::AppState;
+use crate::data::cors::CorsConfig;
+use crate::rate_limit::rate_limit;
+use crate::routes;
+use crate::server_headers::server_headers;
+use crate::shutdown::{shutdown_signal, InFlight};
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::http::{KeepAlive, StatusCode};
+use actix_web::middleware::{from_fn, Compress, Condition, ErrorHandlers};
+use actix_web::{web, App, HttpServer};
+use log::{debug, error, info};
+use socket2::{Domain, Socket, Type};
+use std::cell::Cell;
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+#[cfg(unix)]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+// How `run_server` listens and wraps the app. The defaults match the ones of
+// the command line flags
+pub struct ServerConfig {
+    pub state: Arc<AppState>,
+    // Every one is listened on, with the same port
+    pub hosts: Vec<String>,
+    pub port: u16,
+    // Listened on instead of `hosts` and `port` when set
+    pub unix_socket: Option<PathBuf>,
+    pub cors: CorsConfig,
+    pub tls: Option<rustls::ServerConfig>,
+    pub log_format: LogFormat,
+    pub shutdown_timeout: u64,
+    pub workers: usize,
+    // Seconds an idle connection stays open, 0 disabling keep-alive
+    pub keep_alive: u64,
+    // Accept h2c on plain TCP. Over TLS, HTTP/2 is negotiated through ALPN
+    pub http2: bool,
+}
+
+impl ServerConfig {
+    pub fn new(state: Arc<AppState>) -> Self {
+        ServerConfig {
+            state,
+            hosts: vec!["127.0.0.1".to_string()],
+            port: 8080,
+            unix_socket: None,
+            cors: CorsConfig::default(),
+            tls: None,
+            log_format: LogFormat::Text,
+            shutdown_timeout: 30,
+            workers: std::thread::available_parallelism().map_or(1, |count| count.get()),
+            keep_alive: 5,
+            http2: false,
+        }
+    }
+
+    pub fn with_addresses(mut self, hosts: &[String], port: u16) -> Self {
+        self.hosts = hosts.to_vec();
+        self.port = port;
+        self
+    }
+
+    pub fn with_unix_socket(mut self, path: PathBuf) -> Self {
+        self.unix_socket = Some(path);
+        self
+    }
+
+    pub fn with_cors(mut self, cors: CorsConfig) -> Self {
+        self.cors = cors;
+        self
+    }
+
+    pub fn with_tls(mut self, tls: rustls::ServerConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    pub fn with_log_format(mut self, log_format: LogFormat) -> Self {
+        self.log_format = log_format;
+        self
+    }
+
+    pub fn with_shutdown_timeout(mut self, shutdown_timeout: u64) -> Self {
+        self.shutdown_timeout = shutdown_timeout;
+        self
+    }
+
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    pub fn with_keep_alive(mut self, keep_alive: u64) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    pub fn with_http2(mut self, http2: bool) -> Self {
+        self.http2 = http2;
+        self
+    }
+}
+
+// The app serving the stubs of `state` under its `--base-path`, usable with
+// `actix_web::test` without binding a socket. `run_server` adds CORS, the
+// access log and the in-flight tracking on top
+pub fn build_app(
+    state: Arc<AppState>,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    let base_path = state.settings.base_path.clone();
+    let max_body_size = state.settings.max_body_size;
+    let compress = state.settings.compress;
+    App::new()
+        .wrap(Condition::new(compress, Compress::default()))
+        .wrap(ErrorHandlers::new().handler(StatusCode::PAYLOAD_TOO_LARGE, payload_too_large))
+        .wrap(from_fn(rate_limit))
+        .wrap(from_fn(server_headers))
+        .app_data(web::PayloadConfig::new(max_body_size))
+        .app_data(web::Data::new(state))
+        .service(routes::scope(&base_path))
+}
+
+// Serves until the server fails or a shutdown signal arrives, then waits up
+// to `shutdown_timeout` seconds for the requests in flight
+pub async fn run_server(config: ServerConfig) -> std::io::Result<()> {
+    let scheme = if config.tls.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    if config.unix_socket.is_some() && config.tls.is_some() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "TLS is not supported on a unix socket",
+        ));
+    }
+    if config.unix_socket.is_some() && config.http2 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "HTTP/2 is not supported on a unix socket",
+        ));
+    }
+    let listeners = match &config.unix_socket {
+        Some(path) => {
+            info!(
+                "Listening on unix socket {}, ignoring --server and --port",
+                path.display()
+            );
+            info!(
+                "Starting server on unix:{}{}",
+                path.display(),
+                config.state.settings.base_path
+            );
+            Vec::new()
+        }
+        None => bind_listeners(&config.hosts, config.port)?,
+    };
+    for listener in &listeners {
+        info!(
+            "Starting server on {}://{}{}",
+            scheme,
+            listener.local_addr()?,
+            config.state.settings.base_path
+        );
+    }
+    match config.keep_alive {
+        0 => info!("Using {} workers, keep-alive disabled", config.workers),
+        seconds => info!("Using {} workers, keep-alive {}s", config.workers, seconds),
+    }
+    if config.http2 && config.tls.is_none() {
+        info!("Accepting cleartext HTTP/2 (h2c)");
+    }
+
+    let state = config.state;
+    let app_state = state.clone();
+    let cors = config.cors;
+    let log_format = config.log_format;
+    let in_flight = InFlight::default();
+    let tracker = in_flight.clone();
+
+    let server = HttpServer::new(move || {
+        let tracker = tracker.clone();
+
+        build_app(state.clone())
+            .wrap_fn(|req, srv| {
+                log_protocol(&req);
+                srv.call(req)
+            })
+            .wrap(cors.build())
+            .wrap_fn(move |req, srv| {
+                let guard = tracker.track();
+                let response = srv.call(req);
+                async move {
+                    let response = response.await;
+                    drop(guard);
+                    response
+                }
+            })
+            .wrap(from_fn(move |req, next| access_log(log_format, req, next)))
+    })
+    .on_connect(|_, extensions| {
+        extensions.insert(ProtocolLogged(Cell::new(false)));
+    })
+    .workers(config.workers)
+    .keep_alive(match config.keep_alive {
+        0 => KeepAlive::Disabled,
+        seconds => KeepAlive::Timeout(Duration::from_secs(seconds)),
+    })
+    .shutdown_timeout(config.shutdown_timeout)
+    // Signals are handled below, to report on the requests being drained
+    .disable_signals();
+
+    let server = match (config.unix_socket, config.tls) {
+        #[cfg(unix)]
+        (Some(path), _) => {
+            remove_stale_socket(&path)?;
+            server.bind_uds(path)?
+        }
+        #[cfg(not(unix))]
+        (Some(_), _) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "unix sockets are only available on unix",
+            ))
+        }
+        (None, tls) => {
+            let mut server = server;
+            for listener in listeners {
+                server = match &tls {
+                    Some(tls) => server.listen_rustls_0_23(listener, tls.clone())?,
+                    None if config.http2 => server.listen_auto_h2c(listener)?,
+                    None => server.listen(listener)?,
+                };
+            }
+            server
+        }
+    }
+    .run();
+
+    let handle = server.handle();
+    let mut server = actix_web::rt::spawn(server);
+
+    tokio::select! {
+        // The server only stops by itself when it fails
+        result = &mut server => return result.map_err(std::io::Error::other)?,
+        _ = shutdown_signal() => {}
+        _ = app_state.shutdown_requested() => {}
+    }
+
+    let pending = in_flight.count();
+    info!(
+        "Shutting down, waiting up to {}s for {} in-flight requests",
+        config.shutdown_timeout, pending
+    );
+    handle.stop(true).await;
+    server.await.map_err(std::io::Error::other)??;
+
+    let abandoned = in_flight.count();
+    info!(
+        "Drained {} in-flight requests, {} abandoned",
+        pending.saturating_sub(abandoned),
+        abandoned
+    );
+    Ok(())
+}
+
+// Set on every new connection, for its protocol to be logged on its first
+// request. Connections are served by a single thread, a `Cell` is enough
+struct ProtocolLogged(Cell<bool>);
+
+fn log_protocol(req: &ServiceRequest) {
+    let Some(logged) = req.conn_data::<ProtocolLogged>() else {
+        return;
+    };
+    if !logged.0.replace(true) {
+        debug!(
+            "Connection from {} negotiated {:?}",
+            req.peer_addr()
+                .map(|addr| addr.to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            req.version()
+        );
+    }
+}
+
+// A socket left behind by a previous run would make the bind fail, so it is
+// removed first. Any other kind of file at the path is left alone
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if metadata.file_type().is_socket() {
+            info!("Removing stale unix socket {}", path.display());
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+// `host:port`, with an IPv6 host in brackets as in `[::1]:8080`
+pub fn socket_address(host: &str, port: u16) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+// Binds every address the hosts resolve to, reporting the ones that fail.
+// IPv6 sockets only take IPv6 connections, for `[::]` to be listened on along
+// with `0.0.0.0`. Fails when nothing could be bound
+fn bind_listeners(hosts: &[String], port: u16) -> std::io::Result<Vec<TcpListener>> {
+    let mut listeners = Vec::new();
+    let mut failures = Vec::new();
+    for host in hosts {
+        let address = socket_address(host, port);
+        let resolved = match address.to_socket_addrs() {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                error!("Can't listen on {}: {}", address, e);
+                failures.push(address);
+                continue;
+            }
+        };
+        for addr in resolved {
+            match bind_listener(addr) {
+                Ok(listener) => listeners.push(listener),
+                Err(e) => {
+                    error!("Failed to bind {}: {}", addr, e);
+                    failures.push(addr.to_string());
+                }
+            }
+        }
+    }
+
+    if listeners.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AddrNotAvailable,
+            format!("could not bind {}", failures.join(", ")),
+        ));
+    }
+    Ok(listeners)
+}
+
+fn bind_listener(addr: SocketAddr) -> std::io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, None)?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(true)?;
+    }
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.into())?;
+    // The backlog of actix
+    socket.listen(1024)?;
+    Ok(socket.into())
+}
@@ -0,0 +1,38 @@
+use crate::data::app::AppState;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{web, Error};
+use log::warn;
+use std::sync::Arc;
+
+// Middleware setting the `Server` header of `--server-header` and the fixed
+// `Date` of `--freeze-date`. Actix only writes its own `Date` when the
+// response has none, so the frozen one is what clients see
+pub async fn server_headers(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let (server, date) = req
+        .app_data::<web::Data<Arc<AppState>>>()
+        .map(|app_state| {
+            let settings = &app_state.settings;
+            (settings.server_header.clone(), settings.freeze_date.clone())
+        })
+        .unwrap_or_default();
+
+    let mut response = next.call(req).await?;
+    for (name, value) in [(header::SERVER, server), (header::DATE, date)] {
+        let Some(value) = value else {
+            continue;
+        };
+        match HeaderValue::from_str(&value) {
+            Ok(value) => {
+                response.headers_mut().insert(name, value);
+            }
+            Err(_) => warn!("Invalid {} header value {:?}", name, value),
+        }
+    }
+    Ok(response)
+}
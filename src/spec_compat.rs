@@ -0,0 +1,164 @@
+use crate::errors::AppError;
+use crate::spec_source::SpecFormat;
+use serde_yaml::{Mapping, Value};
+
+// The version `openapiv3` understands, stamped on downconverted documents
+const TARGET_VERSION: &str = "3.0.3";
+
+// Keys whose values are maps of schemas under arbitrary names, which can
+// clash with keywords such as `example` or `default`
+pub(crate) const SCHEMA_MAPS: [&str; 4] =
+    ["properties", "patternProperties", "$defs", "definitions"];
+
+// Keys holding literal values rather than spec objects
+pub(crate) const LITERAL_KEYS: [&str; 4] = ["example", "default", "enum", "const"];
+
+// Parses the raw spec document, rewriting OpenAPI 3.1 constructs into their
+// 3.0 equivalents so both versions can be read by `openapiv3`
+pub fn parse_document(content: &str, format: SpecFormat) -> Result<Value, AppError> {
+    let mut document = read_document(content, format)?;
+    normalize(&mut document);
+    Ok(document)
+}
+
+// The document as it is written, before `normalize`
+pub fn read_document(content: &str, format: SpecFormat) -> Result<Value, AppError> {
+    let document = match format {
+        SpecFormat::Yaml => serde_yaml::from_str(content)?,
+        SpecFormat::Json => {
+            serde_yaml::to_value(serde_json::from_str::<serde_json::Value>(content)?)?
+        }
+    };
+    Ok(document)
+}
+
+pub fn normalize(document: &mut Value) {
+    if is_openapi_31(document) {
+        downconvert(document);
+    }
+    walk(document, keep_xml);
+}
+
+pub fn is_openapi_31(document: &Value) -> bool {
+    document
+        .get("openapi")
+        .and_then(Value::as_str)
+        .is_some_and(|version| version.starts_with("3.1"))
+}
+
+pub fn downconvert(document: &mut Value) {
+    let Some(root) = document.as_mapping_mut() else {
+        return;
+    };
+    root.insert("openapi".into(), TARGET_VERSION.into());
+    // `paths` became optional in 3.1, e.g. for webhook-only documents
+    if !root.contains_key("paths") {
+        root.insert("paths".into(), Value::Mapping(Mapping::new()));
+    }
+
+    walk(document, convert_schema);
+}
+
+// Calls `visit` on every object of the document but the literal values
+fn walk(node: &mut Value, visit: fn(&mut Mapping)) {
+    match node {
+        Value::Mapping(mapping) => {
+            visit(mapping);
+            for (key, value) in mapping.iter_mut() {
+                let key = key.as_str().unwrap_or_default();
+                if LITERAL_KEYS.contains(&key) || key.starts_with("x-") {
+                    continue;
+                }
+                if SCHEMA_MAPS.contains(&key) {
+                    walk_schema_map(value, visit);
+                } else {
+                    walk(value, visit);
+                }
+            }
+        }
+        Value::Sequence(items) => items.iter_mut().for_each(|item| walk(item, visit)),
+        _ => {}
+    }
+}
+
+fn walk_schema_map(node: &mut Value, visit: fn(&mut Mapping)) {
+    match node.as_mapping_mut() {
+        Some(schemas) => schemas
+            .iter_mut()
+            .for_each(|(_, schema)| walk(schema, visit)),
+        None => walk(node, visit),
+    }
+}
+
+// `openapiv3` drops the `xml` object of schemas, which is kept as the `x-xml`
+// extension for XML bodies to be laid out from it
+fn keep_xml(schema: &mut Mapping) {
+    if schema.contains_key("x-xml") || !schema.get("xml").is_some_and(Value::is_mapping) {
+        return;
+    }
+    if let Some(xml) = schema.remove("xml") {
+        schema.insert("x-xml".into(), xml);
+    }
+}
+
+// Rewrites the 3.1 keywords of a single schema in place; objects that are not
+// schemas don't carry these keywords in the shapes matched here
+fn convert_schema(schema: &mut Mapping) {
+    if let Some(Value::Sequence(types)) = schema.get("type").cloned() {
+        let nullable = types.iter().any(|t| t.as_str() == Some("null"));
+        let mut types: Vec<Value> = types
+            .into_iter()
+            .filter(|t| t.as_str() != Some("null"))
+            .collect();
+
+        schema.remove("type");
+        match types.len() {
+            0 => {}
+            1 => {
+                schema.insert("type".into(), types.remove(0));
+            }
+            // Several types can only be expressed as alternatives in 3.0
+            _ => {
+                let options = types
+                    .into_iter()
+                    .map(|t| {
+                        let mut option = Mapping::new();
+                        option.insert("type".into(), t);
+                        Value::Mapping(option)
+                    })
+                    .collect();
+                schema.insert("anyOf".into(), Value::Sequence(options));
+            }
+        }
+        if nullable {
+            schema.insert("nullable".into(), true.into());
+        }
+    } else if schema.get("type").and_then(Value::as_str) == Some("null") {
+        schema.remove("type");
+        schema.insert("nullable".into(), true.into());
+    }
+
+    for (exclusive, bound) in [
+        ("exclusiveMinimum", "minimum"),
+        ("exclusiveMaximum", "maximum"),
+    ] {
+        if let Some(limit) = schema.get(exclusive).filter(|v| v.is_number()).cloned() {
+            schema.insert(bound.into(), limit);
+            schema.insert(exclusive.into(), true.into());
+        }
+    }
+
+    // Media types keep a map of named examples, schemas now take a list
+    if let Some(Value::Sequence(examples)) = schema.get("examples").cloned() {
+        schema.remove("examples");
+        if let Some(first) = examples.into_iter().next() {
+            if !schema.contains_key("example") {
+                schema.insert("example".into(), first);
+            }
+        }
+    }
+
+    if let Some(value) = schema.remove("const") {
+        schema.insert("enum".into(), Value::Sequence(vec![value]));
+    }
+}
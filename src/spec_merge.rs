@@ -0,0 +1,201 @@
+use crate::errors::AppError;
+use crate::external_refs;
+use crate::spec_compat;
+use crate::spec_source::SpecSource;
+use log::warn;
+use serde_yaml::{Mapping, Value};
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+// Names the spec an operation was read from when several are merged, for
+// the problems found later on to point at it
+pub const SOURCE_EXTENSION: &str = "x-mock-source";
+
+// Reads every `--spec` and merges them into one document, so their endpoints
+// and documentation are served together. The first spec provides `info`,
+// `servers` and the other top level fields
+pub fn load_merged(sources: &[SpecSource], strict: bool) -> Result<Value, AppError> {
+    let mut merged: Option<Value> = None;
+
+    for source in sources {
+        let content = source.read()?;
+        let mut document = spec_compat::read_document(&content, source.format(&content))?;
+        if let SpecSource::File(path) = source {
+            external_refs::inline(&mut document, path)?;
+        }
+        spec_compat::normalize(&mut document);
+        if sources.len() > 1 {
+            tag_operations(&mut document, &source.to_string());
+        }
+        match &mut merged {
+            None => merged = Some(document),
+            Some(merged) => merge_document(merged, document, &source.to_string(), strict)?,
+        }
+    }
+    merged.ok_or_else(|| AppError::ConfigError("no spec was given".to_string()))
+}
+
+fn tag_operations(document: &mut Value, name: &str) {
+    let Some(paths) = document.get_mut("paths").and_then(Value::as_mapping_mut) else {
+        return;
+    };
+    for (_, item) in paths.iter_mut() {
+        let Some(item) = item.as_mapping_mut() else {
+            continue;
+        };
+        for (key, operation) in item.iter_mut() {
+            let is_method = key.as_str().is_some_and(|key| METHODS.contains(&key));
+            if let (true, Some(operation)) = (is_method, operation.as_mapping_mut()) {
+                operation.insert(SOURCE_EXTENSION.into(), name.into());
+            }
+        }
+    }
+}
+
+fn merge_document(
+    merged: &mut Value,
+    document: Value,
+    name: &str,
+    strict: bool,
+) -> Result<(), AppError> {
+    let (Some(merged), Value::Mapping(document)) = (merged.as_mapping_mut(), document) else {
+        return Err(AppError::ParseError(format!("{} is not a document", name)));
+    };
+
+    for (key, value) in document {
+        match key.as_str() {
+            Some("paths") => merge_paths(section(merged, "paths"), value, name, strict)?,
+            Some("components") => {
+                merge_components(section(merged, "components"), value, name, strict)?
+            }
+            Some("tags") => merge_tags(merged, value),
+            _ => {
+                if !merged.contains_key(&key) {
+                    merged.insert(key, value);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Operations are merged one by one, so specs can split a path's methods
+fn merge_paths(
+    merged: &mut Mapping,
+    paths: Value,
+    name: &str,
+    strict: bool,
+) -> Result<(), AppError> {
+    let Value::Mapping(paths) = paths else {
+        return Ok(());
+    };
+
+    for (path, item) in paths {
+        let Value::Mapping(item) = item else {
+            continue;
+        };
+        let target = merged
+            .entry(path.clone())
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+        let Some(target) = target.as_mapping_mut() else {
+            continue;
+        };
+
+        for (key, value) in item {
+            let method = key.as_str().filter(|key| METHODS.contains(key));
+            if let (Some(method), true) = (method, target.contains_key(&key)) {
+                collision(
+                    format!(
+                        "{} {} is declared again by {}",
+                        method.to_uppercase(),
+                        path.as_str().unwrap_or_default(),
+                        name
+                    ),
+                    strict,
+                )?;
+            }
+            target.insert(key, value);
+        }
+    }
+    Ok(())
+}
+
+// Identical components are expected, e.g. a shared `Error` schema. Different
+// ones under the same name are a collision
+fn merge_components(
+    merged: &mut Mapping,
+    components: Value,
+    name: &str,
+    strict: bool,
+) -> Result<(), AppError> {
+    let Value::Mapping(components) = components else {
+        return Ok(());
+    };
+
+    for (kind, entries) in components {
+        let Value::Mapping(entries) = entries else {
+            continue;
+        };
+        let target = section(merged, kind.as_str().unwrap_or_default());
+        for (component, value) in entries {
+            if target
+                .get(&component)
+                .is_some_and(|existing| *existing != value)
+            {
+                collision(
+                    format!(
+                        "components.{}.{} is declared differently by {}",
+                        kind.as_str().unwrap_or_default(),
+                        component.as_str().unwrap_or_default(),
+                        name
+                    ),
+                    strict,
+                )?;
+            }
+            target.insert(component, value);
+        }
+    }
+    Ok(())
+}
+
+fn merge_tags(merged: &mut Mapping, tags: Value) {
+    let Value::Sequence(tags) = tags else {
+        return;
+    };
+    let Value::Sequence(target) = merged
+        .entry("tags".into())
+        .or_insert_with(|| Value::Sequence(Vec::new()))
+    else {
+        return;
+    };
+
+    for tag in tags {
+        if !target
+            .iter()
+            .any(|known| known.get("name") == tag.get("name"))
+        {
+            target.push(tag);
+        }
+    }
+}
+
+// The later declaration wins, unless `--strict` makes it an error
+fn collision(message: String, strict: bool) -> Result<(), AppError> {
+    if strict {
+        return Err(AppError::ValidationError(message));
+    }
+    warn!("{}, the last declaration wins", message);
+    Ok(())
+}
+
+fn section<'a>(mapping: &'a mut Mapping, key: &str) -> &'a mut Mapping {
+    let value = mapping
+        .entry(key.into())
+        .or_insert_with(|| Value::Mapping(Mapping::new()));
+    if !value.is_mapping() {
+        *value = Value::Mapping(Mapping::new());
+    }
+    value.as_mapping_mut().unwrap()
+}
@@ -0,0 +1,88 @@
+use crate::errors::AppError;
+use std::path::PathBuf;
+
+// Where the OpenAPI spec is read from, as given to `--spec`
+#[derive(Debug, Clone)]
+pub enum SpecSource {
+    File(PathBuf),
+    Url(String),
+}
+
+// Serialization format of the spec content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecFormat {
+    Yaml,
+    Json,
+}
+
+impl SpecFormat {
+    // Uses the file extension when there is one, otherwise looks at the content
+    pub fn detect(name: &str, content: &str) -> Self {
+        let name = name.to_lowercase();
+        if name.ends_with(".json") {
+            SpecFormat::Json
+        } else if name.ends_with(".yaml") || name.ends_with(".yml") {
+            SpecFormat::Yaml
+        } else if content.trim_start().starts_with('{') {
+            SpecFormat::Json
+        } else {
+            SpecFormat::Yaml
+        }
+    }
+}
+
+impl std::fmt::Display for SpecSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecSource::File(path) => write!(f, "{}", path.display()),
+            SpecSource::Url(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+impl From<&str> for SpecSource {
+    fn from(spec: &str) -> Self {
+        if spec.starts_with("http://") || spec.starts_with("https://") {
+            SpecSource::Url(spec.to_string())
+        } else {
+            SpecSource::File(PathBuf::from(spec))
+        }
+    }
+}
+
+impl SpecSource {
+    pub fn read(&self) -> Result<String, AppError> {
+        match self {
+            SpecSource::File(path) => Ok(std::fs::read_to_string(path)?),
+            SpecSource::Url(url) => fetch(url),
+        }
+    }
+
+    pub fn format(&self, content: &str) -> SpecFormat {
+        match self {
+            SpecSource::File(path) => SpecFormat::detect(&path.to_string_lossy(), content),
+            // Ignore any query string when looking at the extension
+            SpecSource::Url(url) => {
+                let path = url.split(['?', '#']).next().unwrap_or(url);
+                SpecFormat::detect(path, content)
+            }
+        }
+    }
+}
+
+// The blocking client can't run on the actix runtime thread, so the request
+// is made from a separate one
+fn fetch(url: &str) -> Result<String, AppError> {
+    let request = || -> Result<String, String> {
+        let response = reqwest::blocking::get(url).map_err(|e| e.to_string())?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("server responded with {}", status));
+        }
+        response.text().map_err(|e| e.to_string())
+    };
+
+    std::thread::scope(|scope| scope.spawn(request).join())
+        .unwrap_or_else(|_| Err("request thread panicked".to_string()))
+        .map_err(|reason| AppError::FetchError(format!("{}: {}", url, reason)))
+}
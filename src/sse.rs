@@ -0,0 +1,42 @@
+use actix_web::web::Bytes;
+use futures_util::stream::{self, Stream};
+use std::convert::Infallible;
+use std::time::Duration;
+
+pub const EVENT_STREAM: &str = "text/event-stream";
+
+// Repeats `data` as server-sent events, the first one right away and then one
+// every `interval_ms`. The stream ends after `max_events`, or else when the
+// client goes away
+pub fn events(
+    data: Vec<u8>,
+    interval_ms: u64,
+    max_events: Option<u64>,
+) -> impl Stream<Item = Result<Bytes, Infallible>> {
+    let event = String::from_utf8_lossy(&data).into_owned();
+    stream::unfold(0u64, move |id| {
+        let event = event.clone();
+        async move {
+            if max_events.is_some_and(|max_events| id >= max_events) {
+                return None;
+            }
+            if id > 0 {
+                actix_web::rt::time::sleep(Duration::from_millis(interval_ms)).await;
+            }
+            Some((Ok(Bytes::from(format_event(id + 1, &event))), id + 1))
+        }
+    })
+}
+
+// Every line of the payload needs its own `data:` field, the event ending
+// with a blank line
+fn format_event(id: u64, data: &str) -> String {
+    let mut event = format!("id: {}\n", id);
+    for line in data.lines() {
+        event.push_str("data: ");
+        event.push_str(line);
+        event.push('\n');
+    }
+    event.push('\n');
+    event
+}
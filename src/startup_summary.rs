@@ -0,0 +1,47 @@
+use crate::data::app::EndpointHandler;
+use crate::transactions::SkippedOperation;
+use openapiv3::OpenAPI;
+use std::collections::BTreeMap;
+
+// Printed once on boot: what was loaded, where it is served, and what of the
+// spec was left out
+pub fn render(
+    spec: &OpenAPI,
+    address: &str,
+    endpoints: &[EndpointHandler],
+    skipped: &[SkippedOperation],
+) -> String {
+    let mut by_method = BTreeMap::new();
+    for endpoint in endpoints {
+        *by_method.entry(endpoint.method.to_uppercase()).or_insert(0) += 1;
+    }
+    let by_method: Vec<String> = by_method
+        .iter()
+        .map(|(method, count)| format!("{} {}", method, count))
+        .collect();
+
+    let mut summary = String::new();
+    summary.push_str(&format!(
+        "Spec       {} {}\n",
+        spec.info.title, spec.info.version
+    ));
+    summary.push_str(&format!("Listening  {}\n", address));
+    summary.push_str(&format!(
+        "Endpoints  {} ({})\n",
+        endpoints.len(),
+        by_method.join(", ")
+    ));
+    summary.push_str(&format!("Skipped    {}\n", skipped.len()));
+    for operation in skipped {
+        let method = operation
+            .method
+            .as_deref()
+            .map(str::to_uppercase)
+            .unwrap_or_else(|| "*".to_string());
+        summary.push_str(&format!(
+            "  {} {}: {}\n",
+            method, operation.path, operation.reason
+        ));
+    }
+    summary
+}
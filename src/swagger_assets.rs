@@ -0,0 +1,30 @@
+use actix_web::{web, HttpResponse};
+use rust_embed::RustEmbed;
+
+// Where the docs page loads Swagger UI from, by default and with `--swagger-cdn`.
+// The CDN serves the same version as the embedded copy
+pub const LOCAL_URL: &str = "/docs/assets";
+pub const CDN_URL: &str = "https://unpkg.com/swagger-ui-dist@5.33.1";
+
+// The Swagger UI distribution, compiled into the binary so that the docs
+// work without network access
+#[derive(RustEmbed)]
+#[folder = "assets/swagger-ui/"]
+#[include = "*.js"]
+#[include = "*.css"]
+struct SwaggerAssets;
+
+pub async fn serve_asset(file: web::Path<String>) -> HttpResponse {
+    let Some(asset) = SwaggerAssets::get(&file) else {
+        return HttpResponse::NotFound().finish();
+    };
+
+    let content_type = match file.rsplit('.').next() {
+        Some("js") => "application/javascript",
+        Some("css") => "text/css",
+        _ => "application/octet-stream",
+    };
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .body(asset.data.into_owned())
+}
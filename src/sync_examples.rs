@@ -0,0 +1,61 @@
+use crate::data::app::EndpointHandler;
+use crate::transactions::DEFAULT_RESPONSE;
+use openapiv3::{OpenAPI, ReferenceOr, Response};
+
+// Writes the body each endpoint serves as the `example` of its JSON media
+// types, under `--sync-examples`, for the "Example Value" of the Swagger UI
+// to show what the mock returns rather than what it makes up from the
+// schema. Media types with named `examples` keep them, those being what the
+// UI lists. Responses and path items given as `$ref`s are shared, and left
+// as they are
+pub fn apply(spec: &mut OpenAPI, endpoints: &[EndpointHandler]) {
+    for endpoint in endpoints.iter().filter(|endpoint| !endpoint.empty_body) {
+        let Some(response) = find_response(spec, endpoint) else {
+            continue;
+        };
+        let body = endpoint
+            .response_override
+            .as_ref()
+            .unwrap_or(&endpoint.response_body);
+        for (content_type, media_type) in response.content.iter_mut() {
+            if content_type.contains("json") && media_type.examples.is_empty() {
+                media_type.example = Some(body.clone());
+            }
+        }
+    }
+}
+
+fn find_response<'a>(
+    spec: &'a mut OpenAPI,
+    endpoint: &EndpointHandler,
+) -> Option<&'a mut Response> {
+    let ReferenceOr::Item(path_item) = spec.paths.paths.get_mut(&endpoint.path)? else {
+        return None;
+    };
+    let operation = match endpoint.method.as_str() {
+        "get" => path_item.get.as_mut(),
+        "put" => path_item.put.as_mut(),
+        "post" => path_item.post.as_mut(),
+        "delete" => path_item.delete.as_mut(),
+        "options" => path_item.options.as_mut(),
+        "head" => path_item.head.as_mut(),
+        "patch" => path_item.patch.as_mut(),
+        "trace" => path_item.trace.as_mut(),
+        _ => None,
+    }?;
+
+    let response = if endpoint.response_code == DEFAULT_RESPONSE {
+        operation.responses.default.as_mut()
+    } else {
+        operation
+            .responses
+            .responses
+            .iter_mut()
+            .find(|(status, _)| status.to_string() == endpoint.response_code)
+            .map(|(_, response)| response)
+    };
+    match response? {
+        ReferenceOr::Item(response) => Some(response),
+        ReferenceOr::Reference { .. } => None,
+    }
+}
@@ -0,0 +1,118 @@
+use log::debug;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Replaces `{{ name }}` placeholders in every string of a response body.
+// `name` refers to a path parameter, `query.name` to a query parameter and
+// `body.user.id` to a field of the request body. Placeholders that refer to
+// no parameter are left as they are, missing body fields become null
+pub fn render(
+    body: &Value,
+    path_params: &HashMap<String, String>,
+    query: &HashMap<String, String>,
+    request_body: Option<&Value>,
+) -> Value {
+    match body {
+        // A string that is nothing but a body placeholder takes the field's
+        // value as it is, so numbers and objects keep their type
+        Value::String(text) => match whole_body_field(text) {
+            Some(field) => body_field(request_body, field).unwrap_or_else(|| {
+                debug!("No request body field for template placeholder {}", text);
+                Value::Null
+            }),
+            None => Value::String(render_text(text, path_params, query, request_body)),
+        },
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render(item, path_params, query, request_body))
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, field)| {
+                    (
+                        name.clone(),
+                        render(field, path_params, query, request_body),
+                    )
+                })
+                .collect(),
+        ),
+        _ => body.clone(),
+    }
+}
+
+fn render_text(
+    text: &str,
+    path_params: &HashMap<String, String>,
+    query: &HashMap<String, String>,
+    request_body: Option<&Value>,
+) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(open) = rest.find("{{") {
+        let Some(close) = rest[open..].find("}}").map(|offset| open + offset) else {
+            break;
+        };
+        output.push_str(&rest[..open]);
+
+        let placeholder = &rest[open..close + 2];
+        let name = rest[open + 2..close].trim();
+        if let Some(field) = name.strip_prefix("body.") {
+            match body_field(request_body, field) {
+                Some(Value::String(value)) => output.push_str(&value),
+                Some(value) => output.push_str(&value.to_string()),
+                None => {
+                    debug!(
+                        "No request body field for template placeholder {}",
+                        placeholder
+                    );
+                    output.push_str("null");
+                }
+            }
+            rest = &rest[close + 2..];
+            continue;
+        }
+
+        let value = match name.strip_prefix("query.") {
+            Some(param) => query.get(param),
+            None => path_params.get(name),
+        };
+        match value {
+            Some(value) => output.push_str(value),
+            None => {
+                debug!("No value for template placeholder {}", placeholder);
+                output.push_str(placeholder);
+            }
+        }
+
+        rest = &rest[close + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+// The field path of a string made of a single `{{ body.<field> }}`
+fn whole_body_field(text: &str) -> Option<&str> {
+    let name = text.trim().strip_prefix("{{")?.strip_suffix("}}")?;
+    if name.contains("{{") || name.contains("}}") {
+        return None;
+    }
+    name.trim().strip_prefix("body.")
+}
+
+// Follows a dotted path such as `user.id` through the request body. Numbers
+// index into arrays, as in `items.0.name`
+fn body_field(request_body: Option<&Value>, field: &str) -> Option<Value> {
+    field
+        .split('.')
+        .try_fold(request_body?, |value, segment| match value {
+            Value::Object(fields) => fields.get(segment),
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+        .cloned()
+}
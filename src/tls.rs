@@ -0,0 +1,39 @@
+use crate::errors::AppError;
+use rustls::ServerConfig;
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use std::path::Path;
+use std::sync::Arc;
+
+// Builds the rustls configuration from a PEM certificate chain and private key
+pub fn load_server_config(cert_path: &Path, key_path: &Path) -> Result<ServerConfig, AppError> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| {
+            AppError::TlsError(format!(
+                "could not read certificate {}: {}",
+                cert_path.display(),
+                e
+            ))
+        })?;
+    if certs.is_empty() {
+        return Err(AppError::TlsError(format!(
+            "no certificate found in {}",
+            cert_path.display()
+        )));
+    }
+
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(|e| {
+        AppError::TlsError(format!(
+            "could not read private key {}: {}",
+            key_path.display(),
+            e
+        ))
+    })?;
+
+    // The provider is picked explicitly, as other dependencies may enable more than one
+    ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key))
+        .map_err(|e| AppError::TlsError(e.to_string()))
+}
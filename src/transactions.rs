@@ -1,13 +1,86 @@
-use crate::data::app::{AppState, EndpointHandler};
+use crate::access_log::MatchedEndpoint;
+use crate::admin;
+use crate::circuit::Circuit;
+use crate::data::app::{AppState, Credential, EndpointHandler, ReloadState};
+use crate::data::settings::Settings;
+use crate::data::store::ResourceStore;
+use crate::endpoint_list;
 use crate::errors::AppError;
+use crate::formats;
+use crate::formats::xml::XmlShape;
+use crate::generator::{EnumStrategy, SchemaGenerator};
+use crate::pagination::{self, Pagination};
+use crate::request_body;
+use crate::resolver;
+use crate::schema_validator::SchemaValidator;
+use crate::spec_compat;
+use crate::spec_merge;
+use crate::spec_source::SpecFormat;
+use crate::sse;
+use crate::swagger_assets;
+use crate::templating;
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::http::header;
 use actix_web::{web, HttpResponse, Responder, Result as ActixResult};
 use anyhow::Result;
-use log::{info, warn};
-use openapiv3::{OpenAPI, Operation, ReferenceOr, Response};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use log::{debug, info, warn};
+use openapiv3::{
+    APIKeyLocation, Content, OpenAPI, Operation, Parameter, ParameterSchemaOrContent, ReferenceOr,
+    Response, Schema, SchemaKind, SecurityScheme, Type,
+};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::path::Path;
-use std::{collections::HashMap, sync::Arc};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+// The key of the response that covers every status an operation doesn't declare
+pub(crate) const DEFAULT_RESPONSE: &str = "default";
+
+// Why a request was answered with its status, sent back with `--debug-headers`
+#[derive(Debug, Clone, Copy)]
+enum StatusSource {
+    Spec,
+    Requested,
+    QueryMatch,
+    HeaderMatch,
+    ErrorRate,
+    Circuit,
+    Auth,
+    Validation,
+    Stateful,
+    Negotiation,
+    Conditional,
+    Upstream,
+    Unmatched,
+}
+
+impl StatusSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            StatusSource::Spec => "spec",
+            StatusSource::Requested => "requested",
+            StatusSource::QueryMatch => "query-match",
+            StatusSource::HeaderMatch => "header-match",
+            StatusSource::ErrorRate => "error-rate",
+            StatusSource::Circuit => "circuit",
+            StatusSource::Auth => "auth",
+            StatusSource::Validation => "validation",
+            StatusSource::Stateful => "stateful",
+            StatusSource::Negotiation => "content-negotiation",
+            StatusSource::Conditional => "conditional",
+            StatusSource::Upstream => "upstream",
+            StatusSource::Unmatched => "unmatched",
+        }
+    }
+}
+
+// Liveness: the process is up and answering
 pub async fn health_check() -> impl Responder {
     HttpResponse::Ok().json(serde_json::json!({
         "status": "healthy",
@@ -15,13 +88,35 @@ pub async fn health_check() -> impl Responder {
     }))
 }
 
-// Endpoint to show the loaded OpenAPI spec
-async fn serve_openapi_yaml(app_state: web::Data<Arc<AppState>>) -> ActixResult<HttpResponse> {
-    let yaml_content = serde_yaml::to_string(&app_state.openapi_spec)
+// Readiness: a spec is loaded and no reload is underway or has failed since
+pub async fn readiness_check(app_state: web::Data<Arc<AppState>>) -> impl Responder {
+    let endpoints = app_state.endpoints.read().unwrap().len();
+    let title = app_state.get_spec().info.title.clone();
+
+    let (mut response, status, error) = match app_state.reload_state() {
+        ReloadState::Idle => (HttpResponse::Ok(), "ready", None),
+        ReloadState::InProgress => (HttpResponse::ServiceUnavailable(), "reloading", None),
+        ReloadState::Failed(error) => (
+            HttpResponse::ServiceUnavailable(),
+            "reload_failed",
+            Some(error),
+        ),
+    };
+    response.json(serde_json::json!({
+        "status": status,
+        "endpoints": endpoints,
+        "title": title,
+        "error": error,
+    }))
+}
+
+// The loaded spec as YAML, for the tools that prefer it over JSON
+pub async fn serve_openapi_yaml(app_state: web::Data<Arc<AppState>>) -> ActixResult<HttpResponse> {
+    let yaml_content = serde_yaml::to_string(&served_spec(&app_state))
         .map_err(|e| actix_web::error::ErrorInternalServerError(e.to_string()))?;
 
     Ok(HttpResponse::Ok()
-        .content_type("application/json")
+        .content_type("application/yaml")
         .body(yaml_content))
 }
 
@@ -30,46 +125,182 @@ pub async fn api_redirect(
     body: web::Bytes,
     app_state: web::Data<Arc<AppState>>,
 ) -> impl Responder {
-    let path = req.uri().path().trim_start_matches("/api");
+    let started = Instant::now();
+    // Taken from the route rather than the URI, which includes `--base-path`
+    let path = format!("/{}", req.match_info().query("path"));
     let method = req.method().as_str().to_lowercase();
 
-    info!("API redirect: {} {}", method, path);
-
-    // Find matching endpoint
-    for endpoint in &app_state.endpoints {
-        if endpoint.method.to_lowercase() == method {
-            // Check if paths match (including path params)
-            if paths_match(&endpoint.path, path, &endpoint.path_params) {
-                // Return the stored response with status code
-                let status_code = endpoint.response_code.parse::<u16>().unwrap_or(200);
-
-                return HttpResponse::build(
-                    actix_web::http::StatusCode::from_u16(status_code).unwrap(),
-                )
-                .content_type("application/json")
-                .json(&endpoint.response_body);
+    debug!("API redirect: {} {}", method, path);
+
+    // Return the stored response of the matching endpoint
+    let query = web::Query::<HashMap<String, String>>::from_query(req.query_string())
+        .map(|query| query.into_inner())
+        .unwrap_or_default();
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    let json_body = request_body::parse(content_type, &body);
+    let stub_request = StubRequest {
+        http: &req,
+        method: &method,
+        path: &path,
+        query: &query,
+        body: json_body.as_ref(),
+        raw_body: &body,
+    };
+    respond(&app_state, &stub_request, started).await
+}
+
+// What `POST /api/op/{operationId}` sends the operation, every part optional.
+// Scalars stand for their text, as they would in a URL
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Invocation {
+    path: HashMap<String, Value>,
+    query: HashMap<String, Value>,
+    body: Option<Value>,
+}
+
+// Characters that can't appear as they are in a path segment
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+// Serves the operation named by its `operationId` as if its URL had been
+// requested, the path parameters being filled into its template
+pub async fn invoke_operation(
+    req: actix_web::HttpRequest,
+    operation_id: web::Path<String>,
+    body: web::Bytes,
+    app_state: web::Data<Arc<AppState>>,
+) -> impl Responder {
+    let started = Instant::now();
+    let operation_id = operation_id.into_inner();
+    let invocation: Invocation = if body.is_empty() {
+        Invocation::default()
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(invocation) => invocation,
+            Err(e) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": format!("Invalid invocation: {}", e),
+                }))
             }
         }
+    };
+
+    let target = app_state
+        .endpoints
+        .read()
+        .unwrap()
+        .iter()
+        .find(|endpoint| endpoint.operation_id.as_deref() == Some(operation_id.as_str()))
+        .map(|endpoint| (endpoint.method.clone(), endpoint.path.clone()));
+    let Some((method, template)) = target else {
+        return HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Unknown operationId",
+            "operation_id": operation_id,
+        }));
+    };
+
+    let params: HashMap<String, String> = invocation
+        .path
+        .iter()
+        .map(|(name, value)| (name.clone(), parameter_text(value)))
+        .collect();
+    let path = match fill_path_template(&template, &params) {
+        Ok(path) => path,
+        Err(missing) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Missing path parameters",
+                "missing": missing,
+            }))
+        }
+    };
+    let query: HashMap<String, String> = invocation
+        .query
+        .iter()
+        .map(|(name, value)| (name.clone(), parameter_text(value)))
+        .collect();
+    let raw_body = invocation
+        .body
+        .as_ref()
+        .map(|body| body.to_string().into_bytes())
+        .unwrap_or_default();
+
+    debug!("Invoking {} as {} {}", operation_id, method, path);
+    let stub_request = StubRequest {
+        http: &req,
+        method: &method,
+        path: &path,
+        query: &query,
+        body: invocation.body.as_ref(),
+        raw_body: &raw_body,
+    };
+    respond(&app_state, &stub_request, started).await
+}
+
+fn parameter_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+// The path of `template` with its `{param}`s replaced by their encoded
+// values, or the names of the parameters missing a value
+fn fill_path_template(
+    template: &str,
+    params: &HashMap<String, String>,
+) -> Result<String, Vec<String>> {
+    let mut path = String::new();
+    let mut missing = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|offset| open + offset) else {
+            break;
+        };
+        let name = &rest[open + 1..close];
+        path.push_str(&rest[..open]);
+        match params.get(name) {
+            Some(value) => path.extend(utf8_percent_encode(value, SEGMENT)),
+            None => missing.push(name.to_string()),
+        }
+        rest = &rest[close + 1..];
     }
+    path.push_str(rest);
 
-    // If no matching endpoint found
-    HttpResponse::NotFound().json(serde_json::json!({
-        "error": "Endpoint not found",
-        "path": path,
-        "method": method,
-    }))
+    if missing.is_empty() {
+        Ok(path)
+    } else {
+        Err(missing)
+    }
+}
+
+pub async fn metrics(app_state: web::Data<Arc<AppState>>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(app_state.metrics.render())
 }
 
-pub async fn swagger_ui() -> ActixResult<HttpResponse> {
+pub async fn swagger_ui(app_state: web::Data<Arc<AppState>>) -> ActixResult<HttpResponse> {
     // TODO: This is bolierplate from AI chat maybe a more elegant solution can be used ...
     let html = r#"<!DOCTYPE html>
     <html lang="en">
 <head>
     <meta charset="UTF-8">
     <title>Swagger UI</title>
-    <link rel="stylesheet" type="text/css" href="https://unpkg.com/swagger-ui-dist@4.5.0/swagger-ui.css" />
-    <link rel="icon" type="image/png" href="https://unpkg.com/swagger-ui-dist@4.5.0/favicon-32x32.png" sizes="32x32" />
-    <link rel="icon" type="image/png" href="https://unpkg.com/swagger-ui-dist@4.5.0/favicon-16x16.png" sizes="16x16" />
+    <link rel="stylesheet" type="text/css" href="{assets}/swagger-ui.css" />
     <style>
         html { box-sizing: border-box; overflow: -moz-scrollbars-vertical; overflow-y: scroll; }
         *, *:before, *:after { box-sizing: inherit; }
@@ -78,34 +309,40 @@ pub async fn swagger_ui() -> ActixResult<HttpResponse> {
 </head>
 <body>
     <div id="swagger-ui"></div>
-    <script src="https://unpkg.com/swagger-ui-dist@4.5.0/swagger-ui-bundle.js"></script>
-    <script src="https://unpkg.com/swagger-ui-dist@4.5.0/swagger-ui-standalone-preset.js"></script>
+    <script src="{assets}/swagger-ui-bundle.js"></script>
     <script>
     window.onload = function() {
         const ui = SwaggerUIBundle({
-            url: "/api/openapi.json",
+            url: "{base}/api/openapi.json",
             // Use our API server URL for requests
             // This makes "Try it out" in Swagger UI work with our mock server
             requestInterceptor: (req) => {
                 // Rewrite URLs to use our API endpoints
                 if (req.url.startsWith('http://') || req.url.startsWith('https://')) {
                     const url = new URL(req.url);
-                    const path = url.pathname;
+                    const api = '{base}/api';
+                    // Base of the spec's first server, which prefixes every operation
+                    const server = {server};
+                    let path = url.pathname;
+                    if (path.startsWith(api + '/')) {
+                        path = path.slice(api.length);
+                    } else if (path.startsWith(server + '/')) {
+                        path = path.slice(server.length);
+                    }
                     // Rewrite to use our /api prefix
-                    req.url = '/api' + path;
+                    req.url = api + path + url.search;
                 }
                 return req;
             },
             dom_id: '#swagger-ui',
             deepLinking: true,
             presets: [
-                SwaggerUIBundle.presets.apis,
-                SwaggerUIStandalonePreset
+                SwaggerUIBundle.presets.apis
             ],
             plugins: [
                 SwaggerUIBundle.plugins.DownloadUrl
             ],
-            layout: "StandaloneLayout"
+            layout: "BaseLayout"
         });
         window.ui = ui;
     };
@@ -113,28 +350,79 @@ pub async fn swagger_ui() -> ActixResult<HttpResponse> {
 </body>
 </html>"#;
 
+    let base_path = &app_state.settings.base_path;
+    let assets = if app_state.settings.swagger_cdn {
+        swagger_assets::CDN_URL.to_string()
+    } else {
+        format!("{}{}", base_path, swagger_assets::LOCAL_URL)
+    };
+    // A JSON string is a valid JavaScript one, `<` is escaped to stay inside the script
+    let server = serde_json::Value::from(server_path(&app_state.get_spec()))
+        .to_string()
+        .replace('<', "\\u003c");
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
-        .body(html))
+        .body(
+            html.replace("{assets}", &assets)
+                .replace("{server}", &server)
+                .replace("{base}", base_path),
+        ))
+}
+
+// Path of the first server declared by the spec, `/api` when there is none
+fn server_path(spec: &OpenAPI) -> String {
+    let Some(server) = spec.servers.first() else {
+        return "/api".to_string();
+    };
+
+    let mut url = server.url.clone();
+    for (name, variable) in server.variables.iter().flatten() {
+        url = url.replace(&format!("{{{}}}", name), &variable.default);
+    }
+    let path = match reqwest::Url::parse(&url) {
+        Ok(absolute) => absolute.path().to_string(),
+        Err(_) => url,
+    };
+    format!("/{}", path.trim_matches('/'))
+        .trim_end_matches('/')
+        .to_string()
 }
 
 pub async fn show_openapi_spec(app_state: web::Data<Arc<AppState>>) -> impl Responder {
-    let spec_json = serde_json::to_value(&app_state.openapi_spec).unwrap_or(serde_json::json!({
+    HttpResponse::Ok().json(served_spec(&app_state))
+}
+
+// The loaded spec as served by `/api/openapi.json` and `/api/openapi.yaml`
+fn served_spec(app_state: &AppState) -> Value {
+    let mut spec_json = serde_json::to_value(&*app_state.get_spec()).unwrap_or(serde_json::json!({
         "error": "Failed to serialize OpenAPI spec"
     }));
 
-    HttpResponse::Ok().json(spec_json)
+    // Point "Try it out" at the mounted routes rather than the real servers
+    let base_path = &app_state.settings.base_path;
+    if !base_path.is_empty() {
+        spec_json["servers"] = serde_json::json!([{ "url": format!("{}/api", base_path) }]);
+    }
+    spec_json
 }
 
 pub async fn list_endpoints(app_state: web::Data<Arc<AppState>>) -> impl Responder {
     let endpoints: Vec<serde_json::Value> = app_state
         .endpoints
+        .read()
+        .unwrap()
+        .iter()
+        .map(endpoint_list::describe)
+        .collect();
+
+    // Routes of the server itself, which don't come from the spec
+    let admin_routes: Vec<serde_json::Value> = admin::admin_routes(&app_state.settings)
         .iter()
-        .map(|ep| {
+        .map(|(method, path)| {
             serde_json::json!({
-                "path": ep.path,
-                "method": ep.method,
-                "status_code": ep.response_code,
+                "path": format!("{}{}", app_state.settings.base_path, path),
+                "method": method,
+                "enabled": app_state.settings.admin_token.is_some(),
             })
         })
         .collect();
@@ -142,83 +430,1075 @@ pub async fn list_endpoints(app_state: web::Data<Arc<AppState>>) -> impl Respond
     HttpResponse::Ok().json(serde_json::json!({
         "endpoints": endpoints,
         "count": endpoints.len(),
+        "admin_routes": admin_routes,
+    }))
+}
+
+// What of the spec `list_endpoints` is missing, and why
+pub async fn diagnostics(app_state: web::Data<Arc<AppState>>) -> impl Responder {
+    let skipped = app_state.skipped.read().unwrap();
+    HttpResponse::Ok().json(serde_json::json!({
+        "skipped": *skipped,
+        "count": skipped.len(),
     }))
 }
 
 pub async fn dynamic_handler(
-    req_path: web::Path<(String, String)>, // Path and method
+    req: actix_web::HttpRequest,
+    req_path: web::Path<(String, String)>, // Method and path
     app_state: web::Data<Arc<AppState>>,
     query: web::Query<HashMap<String, String>>,
-    path_params: web::Path<HashMap<String, String>>,
-    req_body: Option<web::Json<Value>>,
+    body: web::Bytes,
 ) -> impl Responder {
-    let (path_str, method_str) = req_path.into_inner();
+    let started = Instant::now();
+    let (method_str, _) = req_path.into_inner();
     let method_str = method_str.to_lowercase();
+    let path_str = format!("/{}", raw_path(&req));
+
+    debug!("Handling request: {} {}", method_str, path_str);
+
+    // In a more advance implementation, we could modify the response
+    // based on the query parameters, path parameters, and request body
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    let json_body = request_body::parse(content_type, &body);
+    let stub_request = StubRequest {
+        http: &req,
+        method: &method_str,
+        path: &path_str,
+        query: &query,
+        body: json_body.as_ref(),
+        raw_body: &body,
+    };
+    respond(&app_state, &stub_request, started).await
+}
+
+// The `{path}` of the route before `web::Path` decodes it, in which actix
+// leaves `%2F` encoded. Its segments are decoded once split, for an encoded
+// slash to stay within its segment
+pub(crate) fn raw_path(req: &actix_web::HttpRequest) -> &str {
+    req.match_info().get("path").unwrap_or_default()
+}
+
+// The parts of an incoming request that stub responses can depend on
+struct StubRequest<'a> {
+    http: &'a actix_web::HttpRequest,
+    method: &'a str,
+    path: &'a str,
+    query: &'a HashMap<String, String>,
+    body: Option<&'a Value>,
+    raw_body: &'a [u8],
+}
+
+// Answers a request with its stub, or through the upstream when none matches,
+// then records and measures it
+async fn respond(app_state: &AppState, req: &StubRequest<'_>, started: Instant) -> HttpResponse {
+    let (mut response, delay_override, source, matched) = match serve_stub(app_state, req) {
+        Some((response, delay_override, source, matched)) => {
+            (response, delay_override, source, Some(matched))
+        }
+        None => {
+            let source = match app_state.upstream {
+                Some(_) => StatusSource::Upstream,
+                None => StatusSource::Unmatched,
+            };
+            (
+                unmatched_response(app_state, req).await,
+                Some(0),
+                source,
+                None,
+            )
+        }
+    };
+    record_request(app_state, req, &response);
+
+    // `--latency-ms` delays each status class by its own amount
+    let delay_ms = delay_override
+        .unwrap_or_else(|| app_state.settings.latency_ms.delay_for(response.status()));
+    simulate_latency(app_state.jittered_delay(delay_ms)).await;
+    observe_request(
+        app_state,
+        req,
+        matched.as_ref().map(|matched| matched.path.as_str()),
+        &response,
+        started.elapsed(),
+    );
+
+    if app_state.settings.debug_headers {
+        insert_debug_headers(&mut response, matched.as_ref(), source);
+    }
+    if let Some(matched) = matched {
+        response
+            .extensions_mut()
+            .insert(MatchedEndpoint(matched.path));
+    }
+    response
+}
+
+// The method is the endpoint's, which is GET for a HEAD answered as GET
+fn insert_debug_headers(
+    response: &mut HttpResponse,
+    matched: Option<&Matched>,
+    source: StatusSource,
+) {
+    let headers = response.headers_mut();
+    if let Some(matched) = matched {
+        if let Ok(template) = header::HeaderValue::from_str(&matched.path) {
+            headers.insert(
+                header::HeaderName::from_static("x-mock-matched-path"),
+                template,
+            );
+        }
+        if let Ok(method) = header::HeaderValue::from_str(&matched.method.to_uppercase()) {
+            headers.insert(
+                header::HeaderName::from_static("x-mock-matched-method"),
+                method,
+            );
+        }
+    }
+    headers.insert(
+        header::HeaderName::from_static("x-mock-status-source"),
+        header::HeaderValue::from_static(source.as_str()),
+    );
+}
+
+async fn unmatched_response(app_state: &AppState, req: &StubRequest<'_>) -> HttpResponse {
+    let Some(upstream) = &app_state.upstream else {
+        let allowed = {
+            let endpoints = app_state.endpoints.read().unwrap();
+            allowed_methods(&endpoints, req.path)
+        };
+        if allowed.is_empty() {
+            let status = actix_web::http::StatusCode::from_u16(app_state.settings.not_found_status)
+                .unwrap_or(actix_web::http::StatusCode::NOT_FOUND);
+            return match &app_state.not_found_body {
+                Some(body) => HttpResponse::build(status).json(body),
+                None => HttpResponse::build(status).json(serde_json::json!({
+                    "error": "Endpoint not found",
+                    "path": req.path,
+                    "method": req.method,
+                })),
+            };
+        }
+        return HttpResponse::MethodNotAllowed()
+            .insert_header((header::ALLOW, allowed.join(", ")))
+            .json(serde_json::json!({
+                "error": "Method not allowed",
+                "path": req.path,
+                "method": req.method,
+                "allowed": allowed,
+            }));
+    };
+
+    info!(
+        "No stub for {} {}, forwarding it upstream",
+        req.method.to_uppercase(),
+        req.path
+    );
+    upstream
+        .forward(
+            req.method,
+            req.path,
+            req.http.query_string(),
+            req.http.headers(),
+            req.raw_body,
+        )
+        .await
+}
+
+// Writes the request and the status it was answered with to the `--record` file
+fn record_request(app_state: &AppState, req: &StubRequest, response: &HttpResponse) {
+    let Some(recorder) = &app_state.recorder else {
+        return;
+    };
+
+    let headers: serde_json::Map<String, Value> = req
+        .http
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            (name.to_string(), Value::String(value))
+        })
+        .collect();
+
+    recorder.record(&serde_json::json!({
+        "method": req.method,
+        "path": req.path,
+        "query": req.query,
+        "headers": headers,
+        "body": req.body,
+        // The bytes as sent, which replay resends along with their
+        // `Content-Type`. `body` is only what they were read as
+        "raw_body": (!req.raw_body.is_empty()).then(|| STANDARD.encode(req.raw_body)),
+        "status": response.status().as_u16(),
+    }));
+}
+
+// Counts the request under the path template it matched, so the metrics
+// don't grow with every distinct id a client sends
+fn observe_request(
+    app_state: &AppState,
+    req: &StubRequest,
+    template: Option<&str>,
+    response: &HttpResponse,
+    elapsed: Duration,
+) {
+    app_state.metrics.observe(
+        req.method,
+        template.unwrap_or("unmatched"),
+        response.status().as_u16(),
+        elapsed,
+    );
+}
+
+// An XML request body with the types of the endpoint's schema, `None` when
+// the request or the endpoint isn't about XML
+fn typed_xml_body(endpoint: &EndpointHandler, req: &StubRequest) -> Option<Value> {
+    let shape = endpoint.request_xml.as_ref()?;
+    let mime = req
+        .http
+        .headers()
+        .get(header::CONTENT_TYPE)?
+        .to_str()
+        .ok()?
+        .split(';')
+        .next()?
+        .trim()
+        .to_ascii_lowercase();
+    if !request_body::is_xml(&mime) {
+        return None;
+    }
+    formats::xml::from_xml(std::str::from_utf8(req.raw_body).ok()?, Some(shape))
+}
+
+// The endpoint a request was served from, which the metrics, the access log
+// and `--debug-headers` report
+struct Matched {
+    path: String,
+    method: String,
+}
+
+// Matches a request against the loaded endpoints and builds its stub response,
+// `None` when no endpoint matches. The returned delay, `None` unless the
+// operation sets its own, is simulated by the caller once the endpoints lock
+// is released
+fn serve_stub(
+    app_state: &AppState,
+    req: &StubRequest,
+) -> Option<(HttpResponse, Option<u64>, StatusSource, Matched)> {
+    let endpoints = app_state.endpoints.read().unwrap();
+
+    let requested_status = requested_status(req.http, req.query);
+    let find = |method: &str| {
+        find_endpoint(
+            &endpoints,
+            method,
+            req.path,
+            req.query,
+            req.http.headers(),
+            requested_status.as_deref(),
+        )
+    };
+    // HEAD is answered as GET, without the body, unless the spec declares it
+    let endpoint = find(req.method).or_else(|| (req.method == "head").then(|| find("get"))?)?;
+    let (response, delay_ms, source) = serve_endpoint(
+        app_state,
+        &endpoints,
+        endpoint,
+        req,
+        requested_status.as_deref(),
+    );
+    let matched = Matched {
+        path: endpoint.path.clone(),
+        method: endpoint.method.clone(),
+    };
+    Some((response, delay_ms, source, matched))
+}
+
+fn serve_endpoint(
+    app_state: &AppState,
+    endpoints: &[EndpointHandler],
+    endpoint: &EndpointHandler,
+    req: &StubRequest,
+    requested_status: Option<&str>,
+) -> (HttpResponse, Option<u64>, StatusSource) {
+    // XML bodies are read again now that the schema they follow is known
+    let typed_body;
+    let typed_request;
+    let req = match typed_xml_body(endpoint, req) {
+        Some(body) => {
+            typed_body = body;
+            typed_request = StubRequest {
+                body: Some(&typed_body),
+                ..*req
+            };
+            &typed_request
+        }
+        None => req,
+    };
 
-    info!("Handling request: {} {}", method_str, path_str);
+    if app_state.settings.enforce_auth && !is_authorized(endpoint, req) {
+        let response = HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Unauthorized",
+            "path": req.path,
+            "method": req.method,
+        }));
+        return (response, Some(0), StatusSource::Auth);
+    }
+
+    if app_state.settings.validate_requests {
+        let errors = validate_request(&app_state.get_spec(), endpoint, req);
+        if !errors.is_empty() {
+            let response = HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Request validation failed",
+                "details": errors,
+            }));
+            return (response, Some(0), StatusSource::Validation);
+        }
+    }
+
+    if !endpoint.chaos_exempt && app_state.inject_failure() {
+        return injected_failure(app_state, endpoints, endpoint, req);
+    }
+
+    if let Some(circuit) = endpoint.circuit.as_ref().filter(|circuit| circuit.trip()) {
+        return open_circuit(app_state, endpoints, endpoint, circuit, req);
+    }
+
+    // A status asked for explicitly is served from the spec, not the store
+    if app_state.settings.stateful && requested_status.is_none() {
+        if let Some(response) =
+            stateful_response(&app_state.store, endpoint, req, app_state.settings.pretty)
+        {
+            return (response, endpoint.delay_ms, StatusSource::Stateful);
+        }
+    }
+    let status = response_status(endpoint, requested_status);
+    // `find_endpoint` falls back to the usual response for undeclared statuses
+    let source = match requested_status {
+        Some(requested)
+            if endpoint.response_code == requested
+                || endpoint.status.as_str() == requested
+                || endpoint.response_code == DEFAULT_RESPONSE =>
+        {
+            StatusSource::Requested
+        }
+        _ if !endpoint.header_match.is_empty() => StatusSource::HeaderMatch,
+        _ if !endpoint.query_match.is_empty() => StatusSource::QueryMatch,
+        _ => StatusSource::Spec,
+    };
+    stub_response(req, endpoint, status, source, &app_state.settings)
+}
+
+// Serves one of the operation's declared 5xx responses at random, or a plain
+// 500 when it declares none
+fn injected_failure(
+    app_state: &AppState,
+    endpoints: &[EndpointHandler],
+    endpoint: &EndpointHandler,
+    req: &StubRequest,
+) -> (HttpResponse, Option<u64>, StatusSource) {
+    let failures: Vec<&EndpointHandler> = endpoints
+        .iter()
+        .filter(|ep| {
+            ep.method == endpoint.method
+                && ep.path == endpoint.path
+                && ep.response_code != DEFAULT_RESPONSE
+                && ep.status.is_server_error()
+        })
+        .collect();
+
+    match app_state.choose(&failures) {
+        Some(failure) => stub_response(
+            req,
+            failure,
+            failure.status,
+            StatusSource::ErrorRate,
+            &app_state.settings,
+        ),
+        None => {
+            let response = HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "injected failure",
+            }));
+            (response, endpoint.delay_ms, StatusSource::ErrorRate)
+        }
+    }
+}
+
+// Serves the operation's declared response for the `x-mock-circuit` failure
+// status, or a plain error when it declares none
+fn open_circuit(
+    app_state: &AppState,
+    endpoints: &[EndpointHandler],
+    endpoint: &EndpointHandler,
+    circuit: &Circuit,
+    req: &StubRequest,
+) -> (HttpResponse, Option<u64>, StatusSource) {
+    let failure = endpoints.iter().find(|ep| {
+        ep.method == endpoint.method
+            && ep.path == endpoint.path
+            && ep.response_code == circuit.fail_status.as_str()
+    });
+
+    match failure {
+        Some(failure) => stub_response(
+            req,
+            failure,
+            failure.status,
+            StatusSource::Circuit,
+            &app_state.settings,
+        ),
+        None => {
+            let response = HttpResponse::build(circuit.fail_status).json(serde_json::json!({
+                "error": "circuit open",
+            }));
+            (response, endpoint.delay_ms, StatusSource::Circuit)
+        }
+    }
+}
+
+fn is_authorized(endpoint: &EndpointHandler, req: &StubRequest) -> bool {
+    if endpoint.security.is_empty() {
+        return true;
+    }
+
+    endpoint.security.iter().any(|credentials| {
+        credentials.iter().all(|credential| match credential {
+            Credential::Header(name) => req.http.headers().contains_key(name.as_str()),
+            Credential::Query(name) => req.query.contains_key(name),
+            Credential::Cookie(name) => req.http.cookie(name).is_some(),
+        })
+    })
+}
+
+// Checks the request against the inputs the operation declares
+fn validate_request(spec: &OpenAPI, endpoint: &EndpointHandler, req: &StubRequest) -> Vec<String> {
+    let mut errors: Vec<String> = endpoint
+        .required_query
+        .iter()
+        .filter(|name| !req.query.contains_key(*name))
+        .map(|name| format!("query.{}: is required", name))
+        .collect();
+
+    let params = extract_path_params(&endpoint.path, endpoint.greedy_param.as_deref(), req.path)
+        .unwrap_or_default();
+    for (name, schema) in &endpoint.path_param_schemas {
+        if let Some(raw) = params.get(name) {
+            let value = parameter_value(spec, schema, raw);
+            errors.extend(SchemaValidator::new(spec).validate(
+                schema,
+                &value,
+                &format!("path.{}", name),
+            ));
+        }
+    }
+
+    match (req.body, &endpoint.request_schema) {
+        (Some(body), Some(schema)) => {
+            errors.extend(SchemaValidator::new(spec).validate(schema, body, "body"))
+        }
+        (None, _) if endpoint.request_body_required => errors.push("body: is required".to_string()),
+        _ => {}
+    }
+    errors
+}
+
+// Path segments are text, read as the type of their schema for it to be
+// checked. A value that doesn't parse stays a string, which the schema rejects
+fn parameter_value(spec: &OpenAPI, schema: &ReferenceOr<Schema>, raw: &str) -> Value {
+    let schema = match schema {
+        ReferenceOr::Item(schema) => Some(schema),
+        ReferenceOr::Reference { reference } => resolver::resolve_schema(spec, reference),
+    };
+    let parsed = match schema.map(|schema| &schema.schema_kind) {
+        Some(SchemaKind::Type(Type::Integer(_))) => raw.parse::<i64>().ok().map(Value::from),
+        Some(SchemaKind::Type(Type::Number(_))) => raw.parse::<f64>().ok().map(Value::from),
+        Some(SchemaKind::Type(Type::Boolean {})) => raw.parse::<bool>().ok().map(Value::from),
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+// Serves the request from the resource store. Returns `None` when the store
+// has nothing to offer, so the spec's stub is used instead. An id missing from
+// a collection the store holds is a 404, as it may have been deleted
+fn stateful_response(
+    store: &ResourceStore,
+    endpoint: &EndpointHandler,
+    req: &StubRequest,
+    pretty: bool,
+) -> Option<HttpResponse> {
+    // Templates ending in a parameter (`/users/{id}`) address a single resource,
+    // others (`/users`) address the collection
+    let path = req.path.trim_end_matches('/');
+    let is_item = endpoint
+        .path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .is_some_and(|segment| segment.starts_with('{') && segment.ends_with('}'));
+
+    let (collection, id) = match (is_item, path.rsplit_once('/')) {
+        (true, Some((collection, id))) => (collection, Some(id)),
+        _ => (path, None),
+    };
+
+    match (req.method, id) {
+        ("post", None) => {
+            let resource = store.create(collection, req.body?.clone());
+            Some(json_response(HttpResponse::Created(), &resource, pretty))
+        }
+        ("get", None) => store
+            .list(collection)
+            .map(|resources| json_response(HttpResponse::Ok(), &resources, pretty)),
+        ("get", Some(id)) => match store.get(collection, id) {
+            Some(resource) => Some(json_response(HttpResponse::Ok(), &resource, pretty)),
+            None => store
+                .contains(collection)
+                .then(|| missing_resource(req, pretty)),
+        },
+        ("put", Some(id)) => {
+            let resource = store.put(collection, id, req.body?.clone());
+            Some(json_response(HttpResponse::Ok(), &resource, pretty))
+        }
+        ("delete", Some(id)) => match store.delete(collection, id) {
+            Some(_) => Some(HttpResponse::NoContent().finish()),
+            None => store
+                .contains(collection)
+                .then(|| missing_resource(req, pretty)),
+        },
+        _ => None,
+    }
+}
+
+fn missing_resource(req: &StubRequest, pretty: bool) -> HttpResponse {
+    let body = serde_json::json!({
+        "error": "Resource not found",
+        "path": req.path,
+    });
+    json_response(HttpResponse::NotFound(), &body, pretty)
+}
+
+// What `.json()` would answer, indented under `--pretty`
+fn json_response(
+    mut builder: actix_web::HttpResponseBuilder,
+    value: &impl Serialize,
+    pretty: bool,
+) -> HttpResponse {
+    let body = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    match body {
+        Ok(body) => builder.content_type("application/json").body(body),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+async fn simulate_latency(delay_ms: u64) {
+    if delay_ms > 0 {
+        actix_web::rt::time::sleep(Duration::from_millis(delay_ms)).await;
+    }
+}
 
-    for endpoint in &app_state.endpoints {
-        if endpoint.method.to_lowercase() == method_str {
-            // Check if the path matches
-            if paths_match(&endpoint.path, &path_str, &endpoint.path_params) {
-                let status_code = endpoint.response_code.parse::<u16>().unwrap_or(200);
+// Clients pick one of the declared responses through the `X-Mock-Status`
+// header or the `__status` query parameter
+fn requested_status(
+    req: &actix_web::HttpRequest,
+    query: &HashMap<String, String>,
+) -> Option<String> {
+    req.headers()
+        .get("X-Mock-Status")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .or_else(|| query.get("__status").cloned())
+}
+
+pub(crate) fn find_endpoint<'a>(
+    endpoints: &'a [EndpointHandler],
+    method: &str,
+    path: &str,
+    query: &HashMap<String, String>,
+    headers: &header::HeaderMap,
+    requested_status: Option<&str>,
+) -> Option<&'a EndpointHandler> {
+    // Every declared response of the operation is its own endpoint. The
+    // endpoints being sorted by specificity, the first template matching the
+    // path is the one serving it, e.g. `/users/me` rather than `/users/{id}`
+    let matching = |endpoint: &&EndpointHandler| {
+        endpoint.method.to_lowercase() == method && endpoint_matches(endpoint, path)
+    };
+    let template = &endpoints.iter().find(matching)?.path;
+    let candidates: Vec<&EndpointHandler> = endpoints
+        .iter()
+        .filter(matching)
+        .filter(|endpoint| endpoint.path == *template)
+        .collect();
+
+    let default = candidates
+        .iter()
+        .find(|ep| ep.response_code == DEFAULT_RESPONSE);
+
+    if let Some(status) = requested_status {
+        // `5XX` can be asked for by name or through a code in its range
+        if let Some(endpoint) = candidates
+            .iter()
+            .find(|ep| ep.response_code == status || ep.status.as_str() == status)
+        {
+            return Some(endpoint);
+        }
+        // Any status the operation doesn't declare is described by `default`
+        if let Some(endpoint) = default {
+            return Some(endpoint);
+        }
+    }
+
+    // Then the response whose `x-mock-match` and `x-mock-match-headers`
+    // conditions the request satisfies, the most specific one when several do
+    if let Some(endpoint) = candidates
+        .iter()
+        .filter(|ep| ep.is_conditional() && ep.matches_query(query) && ep.matches_headers(headers))
+        .max_by_key(|ep| {
+            (
+                ep.query_match.len() + ep.header_match.len(),
+                Reverse(ep.response_code.clone()),
+            )
+        })
+    {
+        return Some(endpoint);
+    }
+
+    // Without an explicit choice, serve the lowest unconditioned 2xx response
+    candidates
+        .iter()
+        .filter(|ep| ep.response_code.starts_with('2') && !ep.is_conditional())
+        .min_by_key(|ep| ep.response_code.clone())
+        .or(default)
+        .or(candidates.first())
+        .copied()
+}
+
+// Methods of the path templates matching `path`, grouped across templates as
+// `/users/me` and `/users/{id}` can both describe the same request
+fn allowed_methods(endpoints: &[EndpointHandler], path: &str) -> Vec<String> {
+    let mut by_template: BTreeMap<(&str, Option<&str>), BTreeSet<String>> = BTreeMap::new();
+    for endpoint in endpoints {
+        let methods = by_template
+            .entry((endpoint.path.as_str(), endpoint.greedy_param.as_deref()))
+            .or_default();
+        methods.insert(endpoint.method.to_uppercase());
+        // HEAD is served wherever GET is
+        if endpoint.method == "get" {
+            methods.insert("HEAD".to_string());
+        }
+    }
+
+    let methods: BTreeSet<String> = by_template
+        .into_iter()
+        .filter(|((template, greedy), _)| extract_path_params(template, *greedy, path).is_some())
+        .flat_map(|(_, methods)| methods)
+        .collect();
+    methods.into_iter().collect()
+}
+
+// The status to answer a matched endpoint with. The `default` response takes
+// whichever status was asked for, as long as it is a valid one
+fn response_status(
+    endpoint: &EndpointHandler,
+    requested_status: Option<&str>,
+) -> actix_web::http::StatusCode {
+    if endpoint.response_code != DEFAULT_RESPONSE {
+        return endpoint.status;
+    }
+
+    requested_status
+        .and_then(|status| status.parse::<u16>().ok())
+        .filter(|code| (100..600).contains(code))
+        .and_then(|code| actix_web::http::StatusCode::from_u16(code).ok())
+        .unwrap_or(endpoint.status)
+}
+
+// Builds the HTTP response for a matched endpoint, along with its simulated latency
+fn stub_response(
+    req: &StubRequest,
+    endpoint: &EndpointHandler,
+    status: actix_web::http::StatusCode,
+    source: StatusSource,
+    settings: &Settings,
+) -> (HttpResponse, Option<u64>, StatusSource) {
+    // 204 and 304 responses carry neither a body nor its `Content-Type`, so
+    // there is nothing to negotiate
+    if endpoint.empty_body || is_bodyless(status) {
+        let mut builder = HttpResponse::build(status);
+        apply_headers(&mut builder, endpoint);
+        return (builder.finish(), endpoint.delay_ms, source);
+    }
 
-                // In a more advance implementation, we could modify the response
-                // based on the query parameters, path parameters, and request body
+    let accept = req
+        .http
+        .headers()
+        .get(actix_web::http::header::ACCEPT)
+        .and_then(|value| value.to_str().ok());
+    let content_type = match negotiate_content_type(accept, &endpoint.content_types) {
+        Some(content_type) => content_type,
+        None => {
+            let response = HttpResponse::NotAcceptable().json(serde_json::json!({
+                "error": "Not acceptable",
+                "supported": endpoint.content_types,
+            }));
+            return (response, Some(0), StatusSource::Negotiation);
+        }
+    };
+
+    let mut builder = HttpResponse::build(status);
+    builder.content_type(content_type.as_str());
+    apply_headers(&mut builder, endpoint);
+
+    let body = match (&endpoint.binary_body, &endpoint.response_override) {
+        (Some(bytes), _) if is_binary(&content_type) => bytes.clone(),
+        (_, Some(body)) => {
+            let body = fill_placeholders(req, endpoint, body.clone(), settings);
+            formats::render_body(
+                &content_type,
+                &body,
+                endpoint.response_xml.as_ref(),
+                settings.pretty,
+            )
+            .into_bytes()
+        }
+        _ => render_stub(req, endpoint, &content_type, settings).into_bytes(),
+    };
+
+    // Events keep coming, so the stream can't be revalidated
+    if endpoint.event_stream && content_type.starts_with(sse::EVENT_STREAM) {
+        builder.insert_header((header::CACHE_CONTROL, "no-cache"));
+        if req.method == "head" {
+            return (builder.finish(), endpoint.delay_ms, source);
+        }
+        let events = sse::events(body, settings.sse_interval_ms, settings.sse_max_events);
+        return (builder.streaming(events), endpoint.delay_ms, source);
+    }
 
-                return HttpResponse::build(
-                    actix_web::http::StatusCode::from_u16(status_code).unwrap(),
-                )
-                .content_type("application/json")
-                .json(&endpoint.response_body);
+    // Successful GET and HEAD responses can be revalidated with `If-None-Match`
+    let revalidated = matches!(endpoint.method.as_str(), "get" | "head") && status.is_success();
+    if revalidated {
+        let etag = etag(&body);
+        if if_none_match(req.http, &etag) {
+            let mut not_modified = HttpResponse::NotModified();
+            not_modified.insert_header((header::ETAG, etag));
+            for (name, value) in &endpoint.headers {
+                if !name.eq_ignore_ascii_case("set-cookie") {
+                    not_modified.insert_header((name.as_str(), value.as_str()));
+                }
             }
+            return (
+                not_modified.finish(),
+                endpoint.delay_ms,
+                StatusSource::Conditional,
+            );
         }
+        builder.insert_header((header::ETAG, etag));
+    }
+
+    // HEAD responses carry the status and headers but never a body, their
+    // `Content-Length` being the one of the body GET would send. Actix only
+    // keeps a length set by hand on streamed bodies
+    if req.method == "head" {
+        builder.no_chunking(body.len() as u64);
+        let empty = futures_util::stream::empty::<Result<web::Bytes, actix_web::Error>>();
+        return (builder.streaming(empty), endpoint.delay_ms, source);
+    }
+    (builder.body(body), endpoint.delay_ms, source)
+}
+
+// Every `Set-Cookie` is sent as a header of its own, the other headers are
+// sent once
+fn apply_headers(builder: &mut actix_web::HttpResponseBuilder, endpoint: &EndpointHandler) {
+    for (name, value) in &endpoint.headers {
+        if name.eq_ignore_ascii_case("set-cookie") {
+            builder.append_header((name.as_str(), value.as_str()));
+        } else {
+            builder.insert_header((name.as_str(), value.as_str()));
+        }
+    }
+    for cookie in &endpoint.cookies {
+        builder.cookie(cookie.clone());
     }
-    // If no matching endpoint is found, return a 404 Not Found response
-    HttpResponse::NotFound().json(serde_json::json!({
-        "error": "Endpoint not found",
-        "path": path_str,
-        "method": method_str,
-    }))
 }
 
-fn paths_match(api_path: &str, request_path: &str, path_params: &[String]) -> bool {
-    // Convert API path template to a regex pattern
-    // For example: /users/{id} -> /users/[^/]+
-    let mut pattern = api_path.to_string();
+// The endpoint's stub body, shaped by the request it answers
+fn render_stub(
+    req: &StubRequest,
+    endpoint: &EndpointHandler,
+    content_type: &str,
+    settings: &Settings,
+) -> String {
+    // `Prefer: example=<name>` picks one of the named examples
+    let body = preferred_example(req.http)
+        .and_then(|name| {
+            endpoint
+                .examples
+                .iter()
+                .find(|(example, _)| *example == name)
+        })
+        .map(|(_, example)| example)
+        .or_else(|| endpoint.next_in_sequence())
+        .or_else(|| endpoint.next_enum_variant())
+        .unwrap_or(&endpoint.response_body);
+    let body = match &endpoint.pagination {
+        Some(pagination) => pagination::paginate(body, pagination, req.query, settings.array_size),
+        None => body.clone(),
+    };
+    let body = fill_placeholders(req, endpoint, body, settings);
+    formats::render_body(
+        content_type,
+        &body,
+        endpoint.response_xml.as_ref(),
+        settings.pretty,
+    )
+}
 
-    for param in path_params {
-        let param_pattern = format!("{{{}}}", param);
-        pattern = pattern.replace(&param_pattern, "[^/]+");
+// Fills the `{{ }}` placeholders of a body from the request, under `--templates`
+fn fill_placeholders(
+    req: &StubRequest,
+    endpoint: &EndpointHandler,
+    body: Value,
+    settings: &Settings,
+) -> Value {
+    if !settings.templates {
+        return body;
     }
+    let params = extract_path_params(&endpoint.path, endpoint.greedy_param.as_deref(), req.path)
+        .unwrap_or_default();
+    templating::render(&body, &params, req.query, req.body)
+}
+
+// A strong validator derived from the body alone, so it stays the same across
+// runs and restarts as long as the body does. FNV-1a is plenty for telling
+// bodies apart, the tag doesn't have to resist forgery
+fn etag(body: &[u8]) -> String {
+    let hash = body.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("\"{:016x}\"", hash)
+}
 
-    // Escape regex special characters
-    let pattern = pattern
-        .replace(".", "\\.")
-        .replace("?", "\\?")
-        .replace("+", "\\+")
-        .replace("*", "\\*")
-        .replace("(", "\\(")
-        .replace(")", "\\)")
-        .replace("[", "\\[")
-        .replace("]", "\\]");
+// `If-None-Match` lists the tags the client has cached, compared weakly as
+// RFC 9110 requires, or is `*` for any of them
+fn if_none_match(req: &actix_web::HttpRequest, etag: &str) -> bool {
+    let Some(tags) = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    tags.split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+// Statuses that HTTP forbids a body for
+fn is_bodyless(status: actix_web::http::StatusCode) -> bool {
+    status == actix_web::http::StatusCode::NO_CONTENT
+        || status == actix_web::http::StatusCode::NOT_MODIFIED
+}
 
-    // Add start and end anchors
-    let pattern = format!("^{}$", pattern);
+// Reads the example name out of an RFC 7240 `Prefer` header
+fn preferred_example(req: &actix_web::HttpRequest) -> Option<String> {
+    let prefer = req.headers().get("Prefer")?.to_str().ok()?;
+    prefer
+        .split([',', ';'])
+        .filter_map(|preference| preference.trim().strip_prefix("example="))
+        .map(|name| name.trim_matches('"').to_string())
+        .next()
+}
+
+// Picks the declared content type that best satisfies the `Accept` header.
+// Each one takes the quality of the most specific range matching it, exact
+// types before `type/*` and then `*/*`, so `application/xml;q=0, */*` rules
+// XML out. The highest quality wins, then the more specific range, then the
+// order of declaration
+fn negotiate_content_type(accept: Option<&str>, declared: &[String]) -> Option<String> {
+    let accept = match accept {
+        Some(accept) if !accept.trim().is_empty() => accept,
+        _ => return declared.first().cloned(),
+    };
 
-    // Try to match
-    match regex::Regex::new(&pattern) {
-        Ok(re) => re.is_match(request_path),
-        Err(_) => false, // If regex fails, consider it a mismatch
+    let ranges: Vec<(String, f32)> = accept
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let media_range = parts.next()?.trim().to_ascii_lowercase();
+            let quality = parts
+                .find_map(|param| {
+                    let (name, value) = param.split_once('=')?;
+                    if !name.trim().eq_ignore_ascii_case("q") {
+                        return None;
+                    }
+                    value.trim().parse::<f32>().ok()
+                })
+                .unwrap_or(1.0);
+            media_range
+                .contains('/')
+                .then_some((media_range, quality.clamp(0.0, 1.0)))
+        })
+        .collect();
+
+    let specificity = |media_range: &str, content_type: &str| match media_range.strip_suffix("/*") {
+        Some("*") => Some(0),
+        Some(main_type) => content_type
+            .strip_prefix(main_type)
+            .is_some_and(|rest| rest.starts_with('/'))
+            .then_some(1),
+        None => (media_range == content_type).then_some(2),
+    };
+
+    declared
+        .iter()
+        .enumerate()
+        .filter_map(|(position, declared_type)| {
+            let content_type = declared_type
+                .split(';')
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_ascii_lowercase();
+            let (quality, specific) = ranges
+                .iter()
+                .filter_map(|(media_range, quality)| {
+                    specificity(media_range, &content_type).map(|specific| (*quality, specific))
+                })
+                .max_by_key(|(_, specific)| *specific)?;
+            (quality > 0.0).then_some((quality, specific, position, declared_type))
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(b.2.cmp(&a.2)))
+        .map(|(_, _, _, declared_type)| declared_type.clone())
+}
+
+fn endpoint_matches(endpoint: &EndpointHandler, request_path: &str) -> bool {
+    extract_path_params(
+        &endpoint.path,
+        endpoint.greedy_param.as_deref(),
+        request_path,
+    )
+    .is_some()
+}
+
+// The values of the template's `{param}`s in the request path, or `None` when
+// the path doesn't match the template. A `greedy` parameter ending the
+// template takes every remaining segment, slashes included
+fn extract_path_params(
+    api_path: &str,
+    greedy: Option<&str>,
+    request_path: &str,
+) -> Option<HashMap<String, String>> {
+    // A trailing slash doesn't make a different resource
+    let mut api_segments: Vec<&str> = api_path.trim_end_matches('/').split('/').collect();
+    let mut request_segments: Vec<String> = request_path
+        .trim_end_matches('/')
+        .split('/')
+        .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+        .collect();
+
+    let mut params = HashMap::new();
+    if let Some(name) = greedy {
+        api_segments.pop();
+        if request_segments.len() <= api_segments.len() {
+            return None;
+        }
+        let rest = request_segments.split_off(api_segments.len()).join("/");
+        if rest.is_empty() {
+            return None;
+        }
+        params.insert(name.to_string(), rest);
+    }
+    if api_segments.len() != request_segments.len() {
+        return None;
+    }
+
+    api_segments
+        .iter()
+        .zip(&request_segments)
+        .all(|(template, segment)| match_segment(template, segment, &mut params))
+        .then_some(params)
+}
+
+// Matches one path segment against its template, where every `{param}` stands
+// for a non-empty run of characters, e.g. `{name}.json` matches `report.json`
+fn match_segment(template: &str, segment: &str, params: &mut HashMap<String, String>) -> bool {
+    let Some(open) = template.find('{') else {
+        return template == segment;
+    };
+    let Some(close) = template[open..].find('}').map(|offset| open + offset) else {
+        return template == segment;
+    };
+
+    let Some(rest) = segment.strip_prefix(&template[..open]) else {
+        return false;
+    };
+    let name = &template[open + 1..close];
+    let remaining_template = &template[close + 1..];
+
+    // The parameter takes at least one character, then the rest of the
+    // template has to match whatever follows it
+    for index in (1..=rest.len()).filter(|&index| rest.is_char_boundary(index)) {
+        if match_segment(remaining_template, &rest[index..], params) {
+            params.insert(name.to_string(), rest[..index].to_string());
+            return true;
+        }
     }
+    false
+}
+
+pub fn parse_openapi_spec(content: &str, format: SpecFormat) -> Result<OpenAPI, AppError> {
+    parse_openapi_document(&spec_compat::parse_document(content, format)?)
+}
+
+pub fn parse_openapi_document(document: &serde_yaml::Value) -> Result<OpenAPI, AppError> {
+    Ok(serde_yaml::from_value(document.clone())?)
+}
+
+// The endpoints built from a spec, along with what of it could not be served
+pub struct BuiltEndpoints {
+    pub endpoints: Vec<EndpointHandler>,
+    pub skipped: Vec<SkippedOperation>,
 }
 
-pub fn build_endpoints_from_spec(spec_path: &Path) -> Result<(Vec<EndpointHandler>), AppError> {
-    let yaml_content = std::fs::read_to_string(spec_path)?;
+// An operation, or a response of one, that no endpoint was built for. `method`
+// is `None` when the whole path was skipped
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkippedOperation {
+    pub method: Option<String>,
+    pub path: String,
+    pub reason: String,
+}
+
+pub fn build_endpoints_from_spec(
+    content: &str,
+    format: SpecFormat,
+    settings: &Settings,
+) -> Result<BuiltEndpoints, AppError> {
+    build_endpoints_from_document(&spec_compat::parse_document(content, format)?, settings)
+}
 
-    // Parse the YAML into OpenAPI spec
-    let openapi_spec: OpenAPI = serde_yaml::from_str(&yaml_content)?;
-    let mut endpoints = Vec::new();
+// Builds the endpoints of a raw document, such as the merge of several specs
+pub fn build_endpoints_from_document(
+    raw_spec: &serde_yaml::Value,
+    settings: &Settings,
+) -> Result<BuiltEndpoints, AppError> {
+    // Parse the document into OpenAPI spec, keeping the raw one around
+    // to resolve references that `openapiv3` does not model
+    let openapi_spec = parse_openapi_document(raw_spec)?;
+    let mut built = BuiltEndpoints {
+        endpoints: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let mut filtered = 0;
 
     info!(
         "Processiong OpenAPI spec with {} paths",
@@ -227,72 +1507,466 @@ pub fn build_endpoints_from_spec(spec_path: &Path) -> Result<(Vec<EndpointHandle
 
     // Process each path and its operations
     for (path, path_item) in &openapi_spec.paths.paths {
+        let resolved_item;
         let path_item = match path_item {
             ReferenceOr::Item(item) => item,
-            ReferenceOr::Reference { .. } => {
-                warn!("References not supported yet, skipping path: {}", path);
-                continue;
+            ReferenceOr::Reference { reference } => {
+                match resolver::resolve_path_item(raw_spec, reference) {
+                    Some(item) => {
+                        resolved_item = item;
+                        &resolved_item
+                    }
+                    None => {
+                        warn!(
+                            "Could not resolve reference {}, skipping path: {}",
+                            reference, path
+                        );
+                        built.skipped.push(SkippedOperation {
+                            method: None,
+                            path: path.clone(),
+                            reason: format!("unresolved reference {}", reference),
+                        });
+                        continue;
+                    }
+                }
             }
         };
 
+        for method in unsupported_methods(raw_spec, path) {
+            warn!(
+                "Unsupported method, skipping {} {}",
+                method.to_uppercase(),
+                path
+            );
+            built.skipped.push(SkippedOperation {
+                method: Some(method),
+                path: path.clone(),
+                reason: "unsupported method".to_string(),
+            });
+        }
+
+        filtered += path_item
+            .iter()
+            .filter(|(_, operation)| !is_selected(operation, &settings.tags))
+            .map(|(_, operation)| {
+                operation.responses.responses.len() + operation.responses.default.iter().count()
+            })
+            .sum::<usize>();
+
         // FIXME: This is a temporary fix to handle paths with parameters,
         //        maybe there is a cleaner way to do this.
 
         // Process GET operations
         if let Some(op) = &path_item.get {
-            process_operation(path, "get", op, &mut endpoints);
+            process_operation(
+                path,
+                "get",
+                op,
+                &path_item.parameters,
+                &openapi_spec,
+                settings,
+                &mut built,
+            );
         }
 
         // Process POST operations
         if let Some(op) = &path_item.post {
-            process_operation(path, "post", op, &mut endpoints);
+            process_operation(
+                path,
+                "post",
+                op,
+                &path_item.parameters,
+                &openapi_spec,
+                settings,
+                &mut built,
+            );
         }
 
         // Process PUT operations
         if let Some(op) = &path_item.put {
-            process_operation(path, "put", op, &mut endpoints);
+            process_operation(
+                path,
+                "put",
+                op,
+                &path_item.parameters,
+                &openapi_spec,
+                settings,
+                &mut built,
+            );
         }
 
         if let Some(op) = &path_item.delete {
-            process_operation(path, "delete", op, &mut endpoints);
+            process_operation(
+                path,
+                "delete",
+                op,
+                &path_item.parameters,
+                &openapi_spec,
+                settings,
+                &mut built,
+            );
+        }
+
+        if let Some(op) = &path_item.patch {
+            process_operation(
+                path,
+                "patch",
+                op,
+                &path_item.parameters,
+                &openapi_spec,
+                settings,
+                &mut built,
+            );
+        }
+
+        if let Some(op) = &path_item.options {
+            process_operation(
+                path,
+                "options",
+                op,
+                &path_item.parameters,
+                &openapi_spec,
+                settings,
+                &mut built,
+            );
+        }
+
+        if let Some(op) = &path_item.head {
+            process_operation(
+                path,
+                "head",
+                op,
+                &path_item.parameters,
+                &openapi_spec,
+                settings,
+                &mut built,
+            );
+        }
+
+        if let Some(op) = &path_item.trace {
+            process_operation(
+                path,
+                "trace",
+                op,
+                &path_item.parameters,
+                &openapi_spec,
+                settings,
+                &mut built,
+            );
+        }
+    }
+
+    if filtered > 0 {
+        info!(
+            "Filtered out {} endpoints not tagged {}",
+            filtered,
+            settings.tags.join(", ")
+        );
+    }
+    check_duplicates(&built.endpoints, &openapi_spec, settings.strict)?;
+    Ok(built)
+}
+
+// Path templates differing only by the names of their parameters, or by a
+// trailing slash, answer the same requests, so one of their operations
+// shadows the other. They come from merged specs or `$ref`ed path items.
+// Under `--strict` they are an error
+fn check_duplicates(
+    endpoints: &[EndpointHandler],
+    spec: &OpenAPI,
+    strict: bool,
+) -> Result<(), AppError> {
+    let describe = |endpoint: &EndpointHandler| {
+        let source = spec
+            .operations()
+            .find(|(path, method, _)| *path == endpoint.path && *method == endpoint.method)
+            .and_then(|(_, _, operation)| {
+                operation
+                    .extensions
+                    .get(spec_merge::SOURCE_EXTENSION)?
+                    .as_str()
+            })
+            .map(|source| format!(" (from {})", source))
+            .unwrap_or_default();
+        format!(
+            "{} {} {}{}",
+            endpoint.method.to_uppercase(),
+            endpoint.path,
+            endpoint.response_code,
+            source
+        )
+    };
+
+    let mut seen: HashMap<(String, &str, &str), &EndpointHandler> = HashMap::new();
+    let mut duplicates = Vec::new();
+    for endpoint in endpoints {
+        let key = (
+            normalized_template(&endpoint.path),
+            endpoint.method.as_str(),
+            endpoint.response_code.as_str(),
+        );
+        match seen.get(&key) {
+            Some(first) => duplicates.push(format!(
+                "{} duplicates {}",
+                describe(endpoint),
+                describe(first)
+            )),
+            None => {
+                seen.insert(key, endpoint);
+            }
         }
+    }
+
+    if strict && !duplicates.is_empty() {
+        return Err(AppError::ValidationError(duplicates.join("; ")));
+    }
+    for duplicate in &duplicates {
+        warn!("{}, only one of them is served", duplicate);
+    }
+    Ok(())
+}
 
-        // TODO: Process other HTTP methods (PATCH, OPTIONS, etc.)
+// `/users/{id}/` and `/users/{userId}` both become `/users/{}`
+fn normalized_template(path: &str) -> String {
+    let re = regex::Regex::new(r"\{[^}]*\}").unwrap();
+    let path = re.replace_all(path, "{}");
+    match path.trim_end_matches('/') {
+        "" => "/".to_string(),
+        path => path.to_string(),
     }
-    Ok(endpoints)
+}
+
+// The keys of a raw path item that look like methods `openapiv3` doesn't
+// model, such as `query` or `connect`. They would be dropped without a word
+fn unsupported_methods(raw_spec: &serde_yaml::Value, path: &str) -> Vec<String> {
+    const PATH_ITEM_KEYS: [&str; 13] = [
+        "$ref",
+        "summary",
+        "description",
+        "servers",
+        "parameters",
+        "get",
+        "put",
+        "post",
+        "delete",
+        "options",
+        "head",
+        "patch",
+        "trace",
+    ];
+    let Some(item) = raw_spec
+        .get("paths")
+        .and_then(|paths| paths.get(path))
+        .and_then(serde_yaml::Value::as_mapping)
+    else {
+        return Vec::new();
+    };
+    item.keys()
+        .filter_map(serde_yaml::Value::as_str)
+        .filter(|key| !key.starts_with("x-") && !PATH_ITEM_KEYS.contains(key))
+        .map(|key| key.to_lowercase())
+        .collect()
+}
+
+// Every operation is served unless `--tags` narrows them down to those
+// carrying at least one of the tags
+fn is_selected(operation: &Operation, tags: &[String]) -> bool {
+    tags.is_empty() || operation.tags.iter().any(|tag| tags.contains(tag))
 }
 
 fn process_operation(
     path: &str,
     method: &str,
     operation: &Operation,
-    endpoints: &mut Vec<EndpointHandler>,
+    path_item_parameters: &[ReferenceOr<Parameter>],
+    spec: &OpenAPI,
+    settings: &Settings,
+    built: &mut BuiltEndpoints,
 ) {
+    if !is_selected(operation, &settings.tags) {
+        return;
+    }
+
     // Extract path parameters from the path
     let mut path_params = Vec::new();
     let re = regex::Regex::new(r"\{([^}]+)\}").unwrap();
     for cap in re.captures_iter(path) {
         path_params.push(cap[1].to_string());
     }
-    for (status_code, response_or_ref) in &operation.responses.responses {
+
+    let parameters = operation_parameters(path_item_parameters, operation, spec);
+    let required_query: Vec<String> = parameters
+        .iter()
+        .filter_map(|parameter| match parameter {
+            Parameter::Query { parameter_data, .. } if parameter_data.required => {
+                Some(parameter_data.name.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    let path_param_schemas: Vec<(String, ReferenceOr<Schema>)> = parameters
+        .iter()
+        .filter_map(|parameter| match parameter {
+            Parameter::Path { parameter_data, .. } => match &parameter_data.format {
+                ParameterSchemaOrContent::Schema(schema) => {
+                    Some((parameter_data.name.clone(), schema.clone()))
+                }
+                ParameterSchemaOrContent::Content(_) => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let greedy_param = greedy_parameter(path, &parameters);
+    let (request_schema, request_xml, request_body_required) = request_body_schema(operation, spec);
+    let security = required_credentials(operation, spec);
+
+    // The `x-mock-delay` extension overrides the global latency
+    let delay_ms = operation
+        .extensions
+        .get("x-mock-delay")
+        .and_then(Value::as_u64);
+    let chaos_exempt = operation
+        .extensions
+        .get("x-no-chaos")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let websocket_interval_ms = websocket_interval(operation);
+
+    // `default` covers every status the operation doesn't declare, and is
+    // served as 200 unless the client asks for another one
+    let declared = operation
+        .responses
+        .responses
+        .iter()
+        .map(|(status_code, response)| {
+            (status_code.to_string(), http_status(status_code), response)
+        });
+    let default = operation.responses.default.iter().map(|response| {
+        (
+            DEFAULT_RESPONSE.to_string(),
+            Some(actix_web::http::StatusCode::OK),
+            response,
+        )
+    });
+
+    for (status_code, status, response_or_ref) in declared.chain(default) {
         let response = match response_or_ref {
             ReferenceOr::Item(reponse) => reponse,
-            ReferenceOr::Reference { .. } => {
-                warn!("References not supported yet, skipping",);
-                continue;
+            ReferenceOr::Reference { reference } => {
+                match resolver::resolve_response(spec, reference) {
+                    Some(response) => response,
+                    None => {
+                        warn!(
+                            "Could not resolve reference {}, skipping response {} for {} {}",
+                            reference,
+                            status_code,
+                            method.to_uppercase(),
+                            path
+                        );
+                        built.skipped.push(SkippedOperation {
+                            method: Some(method.to_string()),
+                            path: path.to_string(),
+                            reason: format!(
+                                "response {}: unresolved reference {}",
+                                status_code, reference
+                            ),
+                        });
+                        continue;
+                    }
+                }
             }
         };
 
+        let Some(status) = status else {
+            warn!(
+                "Invalid status code {}, skipping response for {} {}",
+                status_code,
+                method.to_uppercase(),
+                path
+            );
+            built.skipped.push(SkippedOperation {
+                method: Some(method.to_string()),
+                path: path.to_string(),
+                reason: format!("invalid status code {}", status_code),
+            });
+            continue;
+        };
+
         // Generate stub response based on schema or examples
-        let stub_response = generate_stub_response(response);
-
-        endpoints.push(EndpointHandler {
-            path: path.to_string(),
-            method: method.to_string(),
-            response_code: status_code.to_string(),
-            response_body: stub_response.to_string(),
-            path_params: path_params.clone(),
-        });
+        let empty_body = is_bodyless(status);
+        let (stub_response, enum_variants) = if empty_body {
+            (Value::Null, Vec::new())
+        } else {
+            (
+                generate_stub_response(operation, response, spec, settings),
+                enum_variants(operation, response, spec, settings),
+            )
+        };
+        let headers = generate_stub_headers(response, spec, settings);
+        let content_types = declared_content_types(response);
+        let event_stream = content_types
+            .iter()
+            .any(|content_type| content_type.starts_with(sse::EVENT_STREAM));
+        let response_schema = response
+            .content
+            .iter()
+            .find(|(content_type, _)| content_type.contains("json"))
+            .and_then(|(_, media_type)| media_type.schema.clone());
+        let response_xml = xml_shape(&response.content, spec);
+        let examples = collect_examples(response, spec);
+        let pagination = pagination_limit(&parameters)
+            .and_then(|default_limit| Pagination::detect(&stub_response, default_limit));
+        let sequence = response_sequence(operation, response, status);
+        let query_match = match_conditions(response, "x-mock-match");
+        let header_match = match_conditions(response, "x-mock-match-headers");
+        let cookies = response_cookies(response);
+        let response_override = settings
+            .responses_dir
+            .as_deref()
+            .and_then(|dir| response_override(dir, method, path, &status_code));
+        let binary_body = content_types
+            .iter()
+            .any(|content_type| is_binary(content_type))
+            .then(|| binary_body(method, path, &status_code, settings));
+
+        let circuit = status
+            .is_success()
+            .then(|| operation.extensions.get("x-mock-circuit"))
+            .flatten()
+            .and_then(Circuit::from_extension);
+
+        let endpoint = EndpointHandler::builder(path, method, &status_code, status)
+            .with_operation_id(operation.operation_id.clone())
+            .with_response_body(stub_response)
+            .with_response_schema(response_schema)
+            .with_response_xml(response_xml)
+            .with_path_params(path_params.clone())
+            .with_greedy_param(greedy_param.clone())
+            .with_delay_ms(delay_ms)
+            .with_headers(headers)
+            .with_content_types(content_types)
+            .with_examples(examples)
+            .with_request_schema(request_schema.clone())
+            .with_request_body_required(request_body_required)
+            .with_request_xml(request_xml.clone())
+            .with_required_query(required_query.clone())
+            .with_path_param_schemas(path_param_schemas.clone())
+            .with_security(security.clone())
+            .with_chaos_exempt(chaos_exempt)
+            .with_circuit(circuit)
+            .with_pagination(pagination)
+            .with_websocket_interval_ms(websocket_interval_ms)
+            .with_event_stream(event_stream)
+            .with_sequence(sequence)
+            .with_enum_variants(enum_variants)
+            .with_query_match(query_match)
+            .with_header_match(header_match)
+            .with_empty_body(empty_body)
+            .with_response_override(response_override)
+            .with_binary_body(binary_body)
+            .with_cookies(cookies)
+            .build();
+        built.endpoints.push(endpoint);
 
         info!(
             "Added endpoint: {} {} (status code: {})",
@@ -303,22 +1977,601 @@ fn process_operation(
     }
 }
 
-fn generate_stub_response(response: &Response) -> Value {
-    // TODO: In a real implementation, we'd use the response schema to generate
-    //  a more realistic stub response. For now we'll just return a simple JSON object.
-    //
-    // Check if there's an example we can use
-    for (content_type, media_type) in &response.content {
-        if content_type.starts_with("application/json") {
-            if let Some(example) = &media_type.example {
-                return example.clone();
+// The status to answer with for a response key. A range such as `2XX` is
+// served with its first code
+fn http_status(status_code: &openapiv3::StatusCode) -> Option<actix_web::http::StatusCode> {
+    let code = match status_code {
+        openapiv3::StatusCode::Code(code) => *code,
+        openapiv3::StatusCode::Range(range) => range * 100,
+    };
+
+    // OpenAPI only knows about the 1XX to 5XX classes
+    if !(100..600).contains(&code) {
+        return None;
+    }
+    actix_web::http::StatusCode::from_u16(code).ok()
+}
+
+// Translates the operation's security requirements, or the spec-wide ones when
+// the operation declares none, into the credentials a request has to carry
+fn required_credentials(operation: &Operation, spec: &OpenAPI) -> Vec<Vec<Credential>> {
+    let requirements = match (&operation.security, &spec.security) {
+        (Some(requirements), _) | (None, Some(requirements)) => requirements,
+        (None, None) => return Vec::new(),
+    };
+
+    let mut alternatives = Vec::new();
+    for requirement in requirements {
+        // An empty requirement (`security: [{}]`) makes authentication optional
+        if requirement.is_empty() {
+            return Vec::new();
+        }
+
+        let mut credentials = Vec::new();
+        for name in requirement.keys() {
+            let reference = format!("#/components/securitySchemes/{}", name);
+            let scheme = match resolver::resolve_security_scheme(spec, &reference) {
+                Some(scheme) => scheme,
+                None => {
+                    warn!(
+                        "Unknown security scheme {}, expecting an Authorization header",
+                        name
+                    );
+                    credentials.push(Credential::Header("Authorization".to_string()));
+                    continue;
+                }
+            };
+
+            credentials.push(match scheme {
+                SecurityScheme::APIKey { location, name, .. } => match location {
+                    APIKeyLocation::Header => Credential::Header(name.clone()),
+                    APIKeyLocation::Query => Credential::Query(name.clone()),
+                    APIKeyLocation::Cookie => Credential::Cookie(name.clone()),
+                },
+                _ => Credential::Header("Authorization".to_string()),
+            });
+        }
+        alternatives.push(credentials);
+    }
+
+    alternatives
+}
+
+// Operations marked with `x-websocket: true` also stream their stub over a
+// WebSocket, every `x-websocket-interval-ms` milliseconds (1000 by default)
+fn websocket_interval(operation: &Operation) -> Option<u64> {
+    let enabled = operation
+        .extensions
+        .get("x-websocket")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if !enabled {
+        return None;
+    }
+
+    let interval_ms = operation
+        .extensions
+        .get("x-websocket-interval-ms")
+        .and_then(Value::as_u64)
+        .unwrap_or(1000);
+    Some(interval_ms.max(1))
+}
+
+// Bodies listed by `x-mock-sequence`, served one per call. It can be set on a
+// response, or on the operation to cycle through its successful responses
+fn response_sequence(
+    operation: &Operation,
+    response: &Response,
+    status: actix_web::http::StatusCode,
+) -> Vec<Value> {
+    let sequence = response.extensions.get("x-mock-sequence").or_else(|| {
+        operation
+            .extensions
+            .get("x-mock-sequence")
+            .filter(|_| status.is_success())
+    });
+    match sequence {
+        Some(Value::Array(bodies)) => bodies.clone(),
+        Some(_) => {
+            warn!("x-mock-sequence has to be a list of bodies, ignoring it");
+            Vec::new()
+        }
+        None => Vec::new(),
+    }
+}
+
+// The body of `<dir>/<method>_<path>_<status>.json`, if there is one. Path
+// segments are joined with underscores and stripped of anything else, so
+// `GET /users/{id}` with a 200 reads `get_users_id_200.json`
+fn response_override(dir: &Path, method: &str, path: &str, status_code: &str) -> Option<Value> {
+    let file = override_file(dir, method, path, status_code, "json");
+    let content = std::fs::read_to_string(&file).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(body) => {
+            info!(
+                "Serving {} for {} {} {}",
+                file.display(),
+                method.to_uppercase(),
+                path,
+                status_code
+            );
+            Some(body)
+        }
+        Err(e) => {
+            warn!("Ignoring {}, it is not valid JSON: {}", file.display(), e);
+            None
+        }
+    }
+}
+
+// The content of `<dir>/<method>_<path>_<status>.bin` under `--responses-dir`,
+// or else `--binary-size` zeros
+fn binary_body(method: &str, path: &str, status_code: &str, settings: &Settings) -> Vec<u8> {
+    if let Some(dir) = &settings.responses_dir {
+        let file = override_file(dir, method, path, status_code, "bin");
+        if let Ok(bytes) = std::fs::read(&file) {
+            info!(
+                "Serving {} for {} {} {}",
+                file.display(),
+                method.to_uppercase(),
+                path,
+                status_code
+            );
+            return bytes;
+        }
+    }
+    vec![0; settings.binary_size]
+}
+
+fn override_file(
+    dir: &Path,
+    method: &str,
+    path: &str,
+    status_code: &str,
+    extension: &str,
+) -> PathBuf {
+    let mut sanitized = String::new();
+    for word in path.split(|c: char| !c.is_ascii_alphanumeric()) {
+        if !word.is_empty() {
+            if !sanitized.is_empty() {
+                sanitized.push('_');
+            }
+            sanitized.push_str(word);
+        }
+    }
+    if sanitized.is_empty() {
+        sanitized.push_str("root");
+    }
+
+    dir.join(format!(
+        "{}_{}_{}.{}",
+        method, sanitized, status_code, extension
+    ))
+}
+
+// Values listed by `x-mock-match` for query parameters, e.g. `{type: premium}`,
+// or by `x-mock-match-headers` for headers. Numbers and booleans are compared
+// as they are written in the request
+fn match_conditions(response: &Response, extension: &str) -> Vec<(String, String)> {
+    let Some(conditions) = response.extensions.get(extension) else {
+        return Vec::new();
+    };
+    let Some(conditions) = conditions.as_object() else {
+        warn!("{} has to map names to values, ignoring it", extension);
+        return Vec::new();
+    };
+
+    conditions
+        .iter()
+        .filter_map(|(name, value)| {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                Value::Number(_) | Value::Bool(_) => value.to_string(),
+                _ => {
+                    warn!(
+                        "{} value of {} is not a scalar, ignoring it",
+                        extension, name
+                    );
+                    return None;
+                }
+            };
+            Some((name.clone(), value))
+        })
+        .collect()
+}
+
+// The parameters shared by the path item, overridden by the operation's own
+// when they have the same name and location
+fn operation_parameters<'a>(
+    path_item_parameters: &'a [ReferenceOr<Parameter>],
+    operation: &'a Operation,
+    spec: &'a OpenAPI,
+) -> Vec<&'a Parameter> {
+    let mut parameters: Vec<&Parameter> = Vec::new();
+    for parameter in path_item_parameters.iter().chain(&operation.parameters) {
+        let parameter = match parameter {
+            ReferenceOr::Item(parameter) => parameter,
+            ReferenceOr::Reference { reference } => {
+                match resolver::resolve_parameter(spec, reference) {
+                    Some(parameter) => parameter,
+                    None => {
+                        warn!("Could not resolve reference {}, skipping it", reference);
+                        continue;
+                    }
+                }
+            }
+        };
+        parameters.retain(|known| !same_parameter(known, parameter));
+        parameters.push(parameter);
+    }
+    parameters
+}
+
+// The path parameter marked with `x-mock-greedy`, which has to be the whole
+// last segment of the template, as `{filepath}` in `/files/{filepath}`
+fn greedy_parameter(path: &str, parameters: &[&Parameter]) -> Option<String> {
+    let name = parameters.iter().find_map(|parameter| match parameter {
+        Parameter::Path { parameter_data, .. }
+            if parameter_data.extensions.get("x-mock-greedy") == Some(&Value::Bool(true)) =>
+        {
+            Some(parameter_data.name.clone())
+        }
+        _ => None,
+    })?;
+
+    let last_segment = path.trim_end_matches('/').rsplit('/').next();
+    if last_segment != Some(format!("{{{}}}", name).as_str()) {
+        warn!(
+            "Ignoring x-mock-greedy on {}, it has to be the last segment of {}",
+            name, path
+        );
+        return None;
+    }
+    Some(name)
+}
+
+fn same_parameter(a: &Parameter, b: &Parameter) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+        && a.parameter_data_ref().name == b.parameter_data_ref().name
+}
+
+// `Some` when the operation takes `page` or `limit` query parameters, holding
+// the declared default of `limit` if there is one
+fn pagination_limit(parameters: &[&Parameter]) -> Option<Option<usize>> {
+    let mut paginated = false;
+    let mut default_limit = None;
+
+    for parameter in parameters {
+        let Parameter::Query { parameter_data, .. } = parameter else {
+            continue;
+        };
+
+        match parameter_data.name.as_str() {
+            "page" => paginated = true,
+            "limit" => {
+                paginated = true;
+                if let ParameterSchemaOrContent::Schema(ReferenceOr::Item(schema)) =
+                    &parameter_data.format
+                {
+                    default_limit = schema
+                        .schema_data
+                        .default
+                        .as_ref()
+                        .and_then(Value::as_u64)
+                        .map(|limit| limit as usize);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    paginated.then_some(default_limit)
+}
+
+// The schema of the request body, taken from its JSON media type or else its
+// XML one, how an XML body is read, and whether a body is required
+fn request_body_schema(
+    operation: &Operation,
+    spec: &OpenAPI,
+) -> (Option<ReferenceOr<Schema>>, Option<XmlShape>, bool) {
+    let request_body = match &operation.request_body {
+        Some(ReferenceOr::Item(request_body)) => request_body,
+        Some(ReferenceOr::Reference { reference }) => {
+            match resolver::resolve_request_body(spec, reference) {
+                Some(request_body) => request_body,
+                None => {
+                    warn!(
+                        "Could not resolve reference {}, skipping request body",
+                        reference
+                    );
+                    return (None, None, false);
+                }
+            }
+        }
+        None => return (None, None, false),
+    };
+
+    let schema = ["json", "xml"].iter().find_map(|kind| {
+        request_body
+            .content
+            .iter()
+            .find(|(content_type, _)| content_type.contains(kind))
+            .and_then(|(_, media_type)| media_type.schema.clone())
+    });
+    (
+        schema,
+        xml_shape(&request_body.content, spec),
+        request_body.required,
+    )
+}
+
+// The layout of the XML media type of a body, from its own schema or else the
+// one of another media type, which XML is rendered from all the same
+fn xml_shape(content: &Content, spec: &OpenAPI) -> Option<XmlShape> {
+    let xml = content
+        .iter()
+        .find(|(content_type, _)| request_body::is_xml(&content_type.to_ascii_lowercase()))?;
+    let schema = xml.1.schema.as_ref().or_else(|| {
+        content
+            .values()
+            .find_map(|media_type| media_type.schema.as_ref())
+    })?;
+    Some(XmlShape::of(schema, spec))
+}
+
+// Where a stub body comes from: a value the spec gives, or a schema to
+// generate it from
+enum StubSource<'a> {
+    Fixed(Value),
+    Schema(&'a ReferenceOr<Schema>),
+}
+
+fn stub_source<'a>(
+    operation: &Operation,
+    response: &'a Response,
+    spec: &OpenAPI,
+) -> StubSource<'a> {
+    // `x-mock-response` is served as it is, before examples and schemas. One
+    // on the operation covers the responses that don't declare their own
+    if let Some(body) = response
+        .extensions
+        .get("x-mock-response")
+        .or_else(|| operation.extensions.get("x-mock-response"))
+    {
+        return StubSource::Fixed(body.clone());
+    }
+
+    // Prefer an explicit example, otherwise build one from the schema.
+    // JSON media types are looked at first, the others are rendered from the same value
+    let mut media_types: Vec<_> = response.content.iter().collect();
+    media_types.sort_by_key(|(content_type, _)| !content_type.contains("json"));
+
+    for (_, media_type) in media_types {
+        if let Some(example) = &media_type.example {
+            return StubSource::Fixed(example.clone());
+        }
+        if !media_type.examples.is_empty() {
+            if let Some((_, example)) = collect_examples(response, spec).into_iter().next() {
+                return StubSource::Fixed(example);
             }
         }
+        if let Some(schema) = &media_type.schema {
+            return StubSource::Schema(schema);
+        }
     }
 
     // default stub response
-    serde_json::json!({
+    StubSource::Fixed(serde_json::json!({
         "message": "This is a stub response",
         "status": "success",
-    })
+    }))
+}
+
+fn schema_generator<'a>(spec: &'a OpenAPI, settings: &Settings) -> SchemaGenerator<'a> {
+    SchemaGenerator::new(spec)
+        .with_seed(settings.seed)
+        .with_max_depth(settings.max_depth)
+        .with_enum_strategy(settings.enum_strategy)
+}
+
+fn generate_stub_response(
+    operation: &Operation,
+    response: &Response,
+    spec: &OpenAPI,
+    settings: &Settings,
+) -> Value {
+    match stub_source(operation, response, spec) {
+        StubSource::Fixed(body) => body,
+        StubSource::Schema(schema) => schema_generator(spec, settings).generate(schema),
+    }
+}
+
+// Under `--enum-strategy round-robin`, one body per value of the longest enum
+// of the schema, the first one being the stub body. Bodies the spec gives
+// are served as they are
+fn enum_variants(
+    operation: &Operation,
+    response: &Response,
+    spec: &OpenAPI,
+    settings: &Settings,
+) -> Vec<Value> {
+    if settings.enum_strategy != EnumStrategy::RoundRobin {
+        return Vec::new();
+    }
+    let StubSource::Schema(schema) = stub_source(operation, response, spec) else {
+        return Vec::new();
+    };
+
+    let generator = schema_generator(spec, settings);
+    let first = generator.generate(schema);
+    if generator.enum_cycle() <= 1 {
+        return Vec::new();
+    }
+    let mut variants = vec![first];
+    for position in 1..generator.enum_cycle() {
+        let generator = schema_generator(spec, settings).with_enum_position(position);
+        variants.push(generator.generate(schema));
+    }
+    variants
+}
+
+// Named examples of the response, from the first media type declaring any
+fn collect_examples(response: &Response, spec: &OpenAPI) -> Vec<(String, Value)> {
+    let mut media_types: Vec<_> = response.content.iter().collect();
+    media_types.sort_by_key(|(content_type, _)| !content_type.contains("json"));
+
+    let examples = match media_types
+        .iter()
+        .find(|(_, media_type)| !media_type.examples.is_empty())
+    {
+        Some((_, media_type)) => &media_type.examples,
+        None => return Vec::new(),
+    };
+
+    examples
+        .iter()
+        .filter_map(|(name, example_or_ref)| {
+            let example = match example_or_ref {
+                ReferenceOr::Item(example) => Some(example),
+                ReferenceOr::Reference { reference } => {
+                    let example = resolver::resolve_example(spec, reference);
+                    if example.is_none() {
+                        warn!(
+                            "Could not resolve reference {}, skipping example: {}",
+                            reference, name
+                        );
+                    }
+                    example
+                }
+            }?;
+            Some((name.clone(), example.value.clone()?))
+        })
+        .collect()
+}
+
+// Responses of these types are files, for which a JSON stub makes no sense
+fn is_binary(content_type: &str) -> bool {
+    let content_type = content_type.to_ascii_lowercase();
+    content_type.starts_with("application/octet-stream") || content_type.starts_with("image/")
+}
+
+fn declared_content_types(response: &Response) -> Vec<String> {
+    if response.content.is_empty() {
+        return vec!["application/json".to_string()];
+    }
+
+    // Keep JSON first so it stays the default when no `Accept` header is sent
+    let mut content_types: Vec<String> = response.content.keys().cloned().collect();
+    content_types.sort_by_key(|content_type| !content_type.contains("json"));
+    content_types
+}
+
+fn generate_stub_headers(
+    response: &Response,
+    spec: &OpenAPI,
+    settings: &Settings,
+) -> Vec<(String, String)> {
+    let generator = schema_generator(spec, settings);
+    let mut headers = Vec::new();
+
+    for (name, header_or_ref) in &response.headers {
+        let header = match header_or_ref {
+            ReferenceOr::Item(header) => header,
+            ReferenceOr::Reference { reference } => match resolver::resolve_header(spec, reference)
+            {
+                Some(header) => header,
+                None => {
+                    warn!(
+                        "Could not resolve reference {}, skipping header: {}",
+                        reference, name
+                    );
+                    continue;
+                }
+            },
+        };
+
+        // Use the declared example, falling back to a value built from the schema
+        let value = match (&header.example, &header.format) {
+            (Some(example), _) => example.clone(),
+            (None, ParameterSchemaOrContent::Schema(schema)) => generator.generate(schema),
+            (None, ParameterSchemaOrContent::Content(_)) => Value::Null,
+        };
+
+        // A list of cookies is sent as several `Set-Cookie` headers
+        if let (true, Value::Array(cookies)) = (name.eq_ignore_ascii_case("set-cookie"), &value) {
+            for cookie in cookies.iter().filter_map(Value::as_str) {
+                headers.push((name.clone(), cookie.to_string()));
+            }
+            continue;
+        }
+
+        let value = match value {
+            Value::String(value) => value,
+            Value::Null => continue,
+            value => value.to_string(),
+        };
+        headers.push((name.clone(), value));
+    }
+
+    headers
+}
+
+// Cookies set by the response's `x-mock-set-cookie` extension, mapping each
+// name to its value, or to an object with the `value` and the attributes
+// `path`, `domain`, `maxAge` (seconds), `httpOnly`, `secure` and `sameSite`
+fn response_cookies(response: &Response) -> Vec<Cookie<'static>> {
+    let Some(cookies) = response.extensions.get("x-mock-set-cookie") else {
+        return Vec::new();
+    };
+    let Some(cookies) = cookies.as_object() else {
+        warn!("x-mock-set-cookie has to map cookie names to values, ignoring it");
+        return Vec::new();
+    };
+
+    let mut result = Vec::new();
+    for (name, definition) in cookies {
+        let value = match definition {
+            Value::Object(attributes) => attributes.get("value"),
+            value => Some(value),
+        };
+        let value = match value {
+            Some(Value::String(value)) => value.clone(),
+            Some(Value::Null) | None => String::new(),
+            Some(value) => value.to_string(),
+        };
+
+        let mut cookie = Cookie::new(name.clone(), value);
+        if let Value::Object(attributes) = definition {
+            if let Some(path) = attributes.get("path").and_then(Value::as_str) {
+                cookie.set_path(path.to_string());
+            }
+            if let Some(domain) = attributes.get("domain").and_then(Value::as_str) {
+                cookie.set_domain(domain.to_string());
+            }
+            if let Some(max_age) = attributes.get("maxAge").and_then(Value::as_i64) {
+                cookie.set_max_age(actix_web::cookie::time::Duration::seconds(max_age));
+            }
+            if let Some(http_only) = attributes.get("httpOnly").and_then(Value::as_bool) {
+                cookie.set_http_only(http_only);
+            }
+            if let Some(secure) = attributes.get("secure").and_then(Value::as_bool) {
+                cookie.set_secure(secure);
+            }
+            match attributes.get("sameSite").and_then(Value::as_str) {
+                Some(same_site) if same_site.eq_ignore_ascii_case("strict") => {
+                    cookie.set_same_site(SameSite::Strict)
+                }
+                Some(same_site) if same_site.eq_ignore_ascii_case("lax") => {
+                    cookie.set_same_site(SameSite::Lax)
+                }
+                Some(same_site) if same_site.eq_ignore_ascii_case("none") => {
+                    cookie.set_same_site(SameSite::None)
+                }
+                Some(same_site) => warn!("Unknown sameSite {} for cookie {}", same_site, name),
+                None => {}
+            }
+        }
+        result.push(cookie);
+    }
+    result
 }
@@ -0,0 +1,165 @@
+use crate::errors::AppError;
+use crate::resolver;
+use crate::schema_validator::SchemaValidator;
+use openapiv3::{OpenAPI, Parameter, ReferenceOr, Response, StatusCode};
+use std::collections::HashMap;
+
+// Problems found in a spec. Errors always stop the server from starting,
+// warnings only do so under `--strict`
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub errors: Vec<AppError>,
+    pub warnings: Vec<AppError>,
+}
+
+impl ValidationReport {
+    pub fn is_fatal(&self, strict: bool) -> bool {
+        !self.errors.is_empty() || (strict && !self.warnings.is_empty())
+    }
+}
+
+pub fn validate_spec(spec: &OpenAPI) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let mut operation_ids: HashMap<&str, String> = HashMap::new();
+    let validator = SchemaValidator::new(spec);
+
+    for (path, method, operation) in spec.operations() {
+        let method = method.to_uppercase();
+
+        if let Some(operation_id) = &operation.operation_id {
+            if let Some(first_use) = operation_ids.get(operation_id.as_str()) {
+                report.errors.push(AppError::ValidationError(format!(
+                    "Duplicate operationId '{}' on {} {}, already used by {}",
+                    operation_id, method, path, first_use
+                )));
+            } else {
+                operation_ids.insert(operation_id, format!("{} {}", method, path));
+            }
+        }
+
+        // Path parameters can be declared on the path item or the operation
+        let path_item_parameters = match spec.paths.paths.get(path) {
+            Some(ReferenceOr::Item(item)) => item.parameters.as_slice(),
+            _ => &[],
+        };
+        let declared: Vec<&str> = path_item_parameters
+            .iter()
+            .chain(&operation.parameters)
+            .filter_map(|parameter| resolve_parameter(spec, parameter))
+            .filter_map(|parameter| match parameter {
+                Parameter::Path { parameter_data, .. } => Some(parameter_data.name.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        for param in template_params(path) {
+            if !declared.contains(&param) {
+                report.errors.push(AppError::ValidationError(format!(
+                    "Path parameter '{}' of {} {} is not declared in its parameters",
+                    param, method, path
+                )));
+            }
+        }
+
+        if let Some(response) = operation.responses.default.as_ref() {
+            if let Some(response) = resolve_response(spec, response) {
+                let endpoint = format!("{} {} default", method, path);
+                check_examples(&validator, spec, &endpoint, response, &mut report);
+            }
+        }
+        for (status_code, response) in &operation.responses.responses {
+            let response = resolve_response(spec, response);
+            if let Some(response) = response {
+                let endpoint = format!("{} {} {}", method, path, status_code);
+                check_examples(&validator, spec, &endpoint, response, &mut report);
+            }
+
+            match response {
+                // Body-less responses are expected to have no content
+                Some(response)
+                    if response.content.is_empty()
+                        && method != "HEAD"
+                        && !matches!(status_code, StatusCode::Code(204 | 304)) =>
+                {
+                    report.warnings.push(AppError::ValidationError(format!(
+                        "Response {} of {} {} declares no content",
+                        status_code, method, path
+                    )));
+                }
+                Some(_) => {}
+                None => report.errors.push(AppError::ValidationError(format!(
+                    "Response {} of {} {} references an unknown component",
+                    status_code, method, path
+                ))),
+            }
+        }
+    }
+
+    report
+}
+
+// Examples contradicting their own schema usually mean the spec drifted, and
+// they would be served as they are
+fn check_examples(
+    validator: &SchemaValidator,
+    spec: &OpenAPI,
+    endpoint: &str,
+    response: &Response,
+    report: &mut ValidationReport,
+) {
+    for (content_type, media_type) in &response.content {
+        let Some(schema) = &media_type.schema else {
+            continue;
+        };
+        let named = media_type.examples.iter().filter_map(|(name, example)| {
+            let example = match example {
+                ReferenceOr::Item(example) => Some(example),
+                ReferenceOr::Reference { reference } => resolver::resolve_example(spec, reference),
+            }?;
+            Some((format!("example '{}'", name), example.value.as_ref()?))
+        });
+        let examples = media_type
+            .example
+            .iter()
+            .map(|example| ("example".to_string(), example))
+            .chain(named);
+
+        for (label, example) in examples {
+            for error in validator.validate(schema, example, "example") {
+                report.warnings.push(AppError::ValidationError(format!(
+                    "The {} of {} ({}) does not match its schema: {}",
+                    label, endpoint, content_type, error
+                )));
+            }
+        }
+    }
+}
+
+fn resolve_response<'a>(
+    spec: &'a OpenAPI,
+    response: &'a ReferenceOr<Response>,
+) -> Option<&'a Response> {
+    match response {
+        ReferenceOr::Item(response) => Some(response),
+        ReferenceOr::Reference { reference } => resolver::resolve_response(spec, reference),
+    }
+}
+
+fn resolve_parameter<'a>(
+    spec: &'a OpenAPI,
+    parameter: &'a ReferenceOr<Parameter>,
+) -> Option<&'a Parameter> {
+    match parameter {
+        ReferenceOr::Item(parameter) => Some(parameter),
+        ReferenceOr::Reference { reference } => resolver::resolve_parameter(spec, reference),
+    }
+}
+
+// Extracts the `{name}` placeholders of a path template
+fn template_params(path: &str) -> Vec<&str> {
+    let re = regex::Regex::new(r"\{([^}]+)\}").unwrap();
+    re.captures_iter(path)
+        .filter_map(|cap| cap.get(1))
+        .map(|param| param.as_str())
+        .collect()
+}
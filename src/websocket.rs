@@ -0,0 +1,70 @@
+use crate::data::app::AppState;
+use crate::formats;
+use crate::transactions::{find_endpoint, raw_path};
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_ws::Message;
+use log::info;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Upgrades `/ws/<path>` to a WebSocket that keeps pushing the stub response of
+// the GET operation at `<path>`, provided it has the `x-websocket` extension
+pub async fn websocket_handler(
+    req: HttpRequest,
+    body: web::Payload,
+    app_state: web::Data<Arc<AppState>>,
+) -> actix_web::Result<HttpResponse> {
+    let path = format!("/{}", raw_path(&req));
+    let query = web::Query::<HashMap<String, String>>::from_query(req.query_string())
+        .map(web::Query::into_inner)
+        .unwrap_or_default();
+
+    let stream = {
+        let endpoints = app_state.endpoints.read().unwrap();
+        find_endpoint(&endpoints, "get", &path, &query, req.headers(), None).and_then(|endpoint| {
+            let interval_ms = endpoint.websocket_interval_ms?;
+            let message =
+                formats::render_body("application/json", &endpoint.response_body, None, false);
+            Some((interval_ms, message))
+        })
+    };
+    let Some((interval_ms, message)) = stream else {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "WebSocket not found",
+            "path": path,
+        })));
+    };
+
+    let (response, mut session, mut messages) = actix_ws::handle(&req, body)?;
+    info!("WebSocket opened on {}", path);
+
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(Duration::from_millis(interval_ms));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if session.text(message.clone()).await.is_err() {
+                        break;
+                    }
+                }
+                incoming = messages.recv() => match incoming {
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Close(reason))) => {
+                        let _ = session.close(reason).await;
+                        break;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(_)) | None => break,
+                },
+            }
+        }
+        info!("WebSocket closed on {}", path);
+    });
+
+    Ok(response)
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use actix_web::middleware::from_fn;
+use actix_web::{test, web, App};
+use rusty_stub_api::access_log::{access_log, LogFormat, REQUEST_ID_HEADER};
+use rusty_stub_api::transactions::dynamic_handler;
+
+#[actix_web::test]
+async fn request_id_is_generated_or_echoed() {
+    let state = common::state_from_spec("examples/specs/simple_get_users_spec-api.yaml");
+    let app = test::init_service(
+        App::new()
+            .wrap(from_fn(|req, next| access_log(LogFormat::Json, req, next)))
+            .app_data(web::Data::new(state))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users").to_request();
+    let resp = test::call_service(&app, req).await;
+    let generated = resp.headers().get(&REQUEST_ID_HEADER).unwrap();
+    uuid::Uuid::parse_str(generated.to_str().unwrap()).expect("a generated UUID");
+
+    let req = test::TestRequest::get()
+        .uri("/get/missing")
+        .insert_header(("X-Request-Id", "client-42"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    assert_eq!(resp.headers().get(&REQUEST_ID_HEADER).unwrap(), "client-42");
+}
@@ -0,0 +1,237 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::routes;
+use serde_json::Value;
+use std::time::Duration;
+
+const TOKEN: &str = "s3cret";
+
+#[actix_web::test]
+async fn reload_swaps_endpoints_and_reports_changes() {
+    let spec_path = std::env::temp_dir().join(format!("admin-reload-{}.yaml", std::process::id()));
+    std::fs::copy("tests/fixtures/default_spec.yaml", &spec_path).unwrap();
+
+    let settings = Settings {
+        admin_token: Some(TOKEN.to_string()),
+        ..Settings::default()
+    };
+    let state = common::state_with_settings(spec_path.to_str().unwrap(), settings);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::post().uri("/admin/reload").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+
+    std::fs::copy("tests/fixtures/methods_spec.yaml", &spec_path).unwrap();
+    let req = test::TestRequest::post()
+        .uri("/admin/reload")
+        .insert_header(("X-Admin-Token", TOKEN))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let summary: Value = test::read_body_json(resp).await;
+    assert!(!summary["added"].as_array().unwrap().is_empty());
+    assert!(!summary["removed"].as_array().unwrap().is_empty());
+
+    // A broken spec is rejected and the reloaded endpoints stay
+    std::fs::write(&spec_path, "openapi: [not, a, spec").unwrap();
+    let req = test::TestRequest::post()
+        .uri("/admin/reload")
+        .insert_header(("X-Admin-Token", TOKEN))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 422);
+
+    // So is one that parses but doesn't pass validation
+    std::fs::write(
+        &spec_path,
+        std::fs::read_to_string("tests/fixtures/methods_spec.yaml")
+            .unwrap()
+            .replace("operationId: headProfile", "operationId: patchProfile"),
+    )
+    .unwrap();
+    let req = test::TestRequest::post()
+        .uri("/admin/reload")
+        .insert_header(("X-Admin-Token", TOKEN))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 422);
+    let body: Value = test::read_body_json(resp).await;
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .contains("Duplicate operationId 'patchProfile'"));
+
+    let req = test::TestRequest::get().uri("/patch/profile").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    std::fs::remove_file(&spec_path).unwrap();
+}
+
+#[actix_web::test]
+async fn reload_is_disabled_without_a_token() {
+    let state = common::state_from_spec("tests/fixtures/default_spec.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/admin/reload")
+        .insert_header(("X-Admin-Token", TOKEN))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403);
+
+    let req = test::TestRequest::get().uri("/api/endpoints").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["admin_routes"][0]["path"], "/admin/reload");
+    assert_eq!(body["admin_routes"][0]["enabled"], false);
+}
+
+#[actix_web::test]
+async fn state_dumps_and_resets_the_stateful_store() {
+    let settings = Settings {
+        admin_token: Some(TOKEN.to_string()),
+        stateful: true,
+        ..Settings::default()
+    };
+    let state =
+        common::state_with_settings("examples/specs/simple_get_users_spec-api.yaml", settings);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/post/users")
+        .set_json(serde_json::json!({"name": "Ada"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+
+    let req = test::TestRequest::get().uri("/admin/state").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+
+    let req = test::TestRequest::get()
+        .uri("/admin/state")
+        .insert_header(("X-Admin-Token", TOKEN))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        body,
+        serde_json::json!({"/users": {"1": {"id": 1, "name": "Ada"}}})
+    );
+
+    let req = test::TestRequest::delete()
+        .uri("/admin/state")
+        .insert_header(("X-Admin-Token", TOKEN))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 204);
+
+    let req = test::TestRequest::get()
+        .uri("/admin/state")
+        .insert_header(("X-Admin-Token", TOKEN))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, serde_json::json!({}));
+}
+
+#[actix_web::test]
+async fn state_routes_only_exist_in_stateful_mode() {
+    let settings = Settings {
+        admin_token: Some(TOKEN.to_string()),
+        ..Settings::default()
+    };
+    let state = common::state_with_settings("tests/fixtures/default_spec.yaml", settings);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/admin/state")
+        .insert_header(("X-Admin-Token", TOKEN))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    let req = test::TestRequest::get().uri("/api/endpoints").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["admin_routes"].as_array().unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn shutdown_is_accepted_then_requested() {
+    let settings = Settings {
+        admin_token: Some(TOKEN.to_string()),
+        enable_admin: true,
+        ..Settings::default()
+    };
+    let state = common::state_with_settings("tests/fixtures/default_spec.yaml", settings);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/admin/shutdown")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+
+    let req = test::TestRequest::post()
+        .uri("/admin/shutdown")
+        .insert_header(("X-Admin-Token", TOKEN))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 202);
+
+    tokio::time::timeout(Duration::from_secs(1), state.shutdown_requested())
+        .await
+        .expect("shutdown should have been requested");
+
+    let req = test::TestRequest::get().uri("/api/endpoints").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["admin_routes"][1]["path"], "/admin/shutdown");
+}
+
+#[actix_web::test]
+async fn shutdown_route_needs_enable_admin() {
+    let settings = Settings {
+        admin_token: Some(TOKEN.to_string()),
+        ..Settings::default()
+    };
+    let state = common::state_with_settings("tests/fixtures/default_spec.yaml", settings);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/admin/shutdown")
+        .insert_header(("X-Admin-Token", TOKEN))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+}
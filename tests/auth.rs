@@ -0,0 +1,77 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::app::AppState;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use std::sync::Arc;
+
+fn enforcing_state() -> Arc<AppState> {
+    let state = common::state_from_spec("tests/fixtures/auth_spec.yaml");
+    let state = Arc::try_unwrap(state)
+        .ok()
+        .expect("state is not shared yet");
+    Arc::new(state.with_settings(Settings {
+        enforce_auth: true,
+        ..Settings::default()
+    }))
+}
+
+#[actix_web::test]
+async fn unprotected_endpoint_is_open() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(enforcing_state()))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/public").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn protected_endpoint_requires_credentials() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(enforcing_state()))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/private").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+
+    let req = test::TestRequest::get()
+        .uri("/get/private")
+        .insert_header(("Authorization", "Bearer anything"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn api_key_endpoint_requires_its_header() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(enforcing_state()))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/keyed")
+        .insert_header(("Authorization", "Bearer anything"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+
+    let req = test::TestRequest::get()
+        .uri("/get/keyed")
+        .insert_header(("X-API-Key", "secret"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use std::path::PathBuf;
+
+const BINARY_SPEC: &str = "tests/fixtures/binary_spec.yaml";
+
+#[actix_web::test]
+async fn octet_stream_responses_are_served_as_bytes() {
+    let settings = Settings {
+        binary_size: 16,
+        ..Default::default()
+    };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                BINARY_SPEC,
+                settings,
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/files/7").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/octet-stream"
+    );
+    let body = test::read_body(resp).await;
+    assert_eq!(body.as_ref(), [0u8; 16]);
+    assert!(serde_json::from_slice::<serde_json::Value>(&body).is_err());
+}
+
+#[actix_web::test]
+async fn image_bytes_come_from_the_responses_dir() {
+    let settings = Settings {
+        responses_dir: Some(PathBuf::from("tests/fixtures/responses")),
+        ..Default::default()
+    };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                BINARY_SPEC,
+                settings,
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/avatar")
+        .insert_header(("Accept", "image/png"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("content-type").unwrap(), "image/png");
+    let body = test::read_body(resp).await;
+    assert_eq!(body.as_ref(), b"\x89PNG\r\n\x1a\n");
+
+    // JSON stays the default for clients that don't ask for the image
+    let req = test::TestRequest::get().uri("/get/avatar").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["width"], 64);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use actix_web::test;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::server::build_app;
+use serde_json::Value;
+
+const ROUTING_SPEC: &str = "tests/fixtures/routing_spec.yaml";
+
+#[actix_web::test]
+async fn oversized_bodies_are_rejected_with_413() {
+    let settings = Settings {
+        max_body_size: 64,
+        ..Default::default()
+    };
+    let app = test::init_service(build_app(common::state_with_settings(
+        ROUTING_SPEC,
+        settings,
+    )))
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/users/42")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(format!("{{\"name\": \"{}\"}}", "x".repeat(100)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 413);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Payload too large");
+    assert_eq!(body["limit"], 64);
+
+    let req = test::TestRequest::post()
+        .uri("/api/users/42")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(r#"{"name": "x"}"#)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_ne!(resp.status(), 413);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use actix_web::test;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::server::build_app;
+use serde_json::Value;
+
+const SIMPLE_SPEC: &str = "examples/specs/simple_get_users_spec-api.yaml";
+
+#[actix_web::test]
+async fn built_app_serves_every_route() {
+    let app = test::init_service(build_app(common::state_from_spec(SIMPLE_SPEC))).await;
+
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::get().uri("/api/users").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::get().uri("/api/endpoints").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(body["count"].as_u64().unwrap() > 0);
+}
+
+#[actix_web::test]
+async fn built_app_honors_the_base_path() {
+    let settings = Settings {
+        base_path: "/mock".to_string(),
+        ..Default::default()
+    };
+    let app = test::init_service(build_app(common::state_with_settings(
+        SIMPLE_SPEC,
+        settings,
+    )))
+    .await;
+
+    let req = test::TestRequest::get().uri("/mock/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+}
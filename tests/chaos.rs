@@ -0,0 +1,58 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::Value;
+
+const CHAOS_SPEC: &str = "tests/fixtures/chaos_spec.yaml";
+
+fn always_failing() -> Settings {
+    Settings {
+        error_rate: 1.0,
+        seed: Some(1),
+        ..Settings::default()
+    }
+}
+
+#[actix_web::test]
+async fn failures_use_declared_server_errors() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                CHAOS_SPEC,
+                always_failing(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/orders").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "unavailable");
+
+    let req = test::TestRequest::get().uri("/get/profile").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 500);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "injected failure");
+}
+
+#[actix_web::test]
+async fn exempt_operations_never_fail() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                CHAOS_SPEC,
+                always_failing(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/status").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::{json, Value};
+
+const CIRCUIT_SPEC: &str = "tests/fixtures/circuit_spec.yaml";
+
+#[actix_web::test]
+async fn circuit_fails_after_the_successes_and_recovers() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                CIRCUIT_SPEC,
+                Settings {
+                    debug_headers: true,
+                    ..Settings::default()
+                },
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let mut calls = Vec::new();
+    for _ in 0..5 {
+        let req = test::TestRequest::get().uri("/get/payments").to_request();
+        let resp = test::call_service(&app, req).await;
+        let status = resp.status().as_u16();
+        let source = resp
+            .headers()
+            .get("x-mock-status-source")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body: Value = test::read_body_json(resp).await;
+        calls.push((status, source, body));
+    }
+
+    let ok = |source: &str| (200, source.to_string(), json!({ "ok": true }));
+    let failed = (
+        503,
+        "circuit".to_string(),
+        json!({ "error": "unavailable" }),
+    );
+    assert_eq!(
+        calls,
+        vec![ok("spec"), ok("spec"), failed.clone(), failed, ok("spec")]
+    );
+}
+
+#[actix_web::test]
+async fn undeclared_fail_status_is_served_as_a_plain_error() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(CIRCUIT_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/refunds").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 500);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body, json!({ "error": "circuit open" }));
+
+    let req = test::TestRequest::get().uri("/get/refunds").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({ "ok": true }));
+}
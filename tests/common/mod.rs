@@ -0,0 +1,32 @@
+use rusty_stub_api::data::app::AppState;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::spec_source::SpecSource;
+use rusty_stub_api::sync_examples;
+use rusty_stub_api::transactions::{build_endpoints_from_spec, parse_openapi_spec};
+use std::sync::Arc;
+
+// Loads a spec from disk the same way `main` does
+#[allow(dead_code)]
+pub fn state_from_spec(spec: &str) -> Arc<AppState> {
+    state_with_settings(spec, Settings::default())
+}
+
+#[allow(dead_code)]
+pub fn state_with_settings(spec: &str, settings: Settings) -> Arc<AppState> {
+    Arc::new(load_state(spec, settings))
+}
+
+// The state before it is shared, for a test to add to it
+#[allow(dead_code)]
+pub fn load_state(spec: &str, settings: Settings) -> AppState {
+    let source = SpecSource::from(spec);
+    let content = source.read().expect("spec should be readable");
+    let mut openapi_spec =
+        parse_openapi_spec(&content, source.format(&content)).expect("spec should parse");
+    let built = build_endpoints_from_spec(&content, source.format(&content), &settings)
+        .expect("spec should load");
+    if settings.sync_examples {
+        sync_examples::apply(&mut openapi_spec, &built.endpoints);
+    }
+    AppState::from_config(settings, built, openapi_spec).with_sources(vec![source])
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use actix_web::test;
+use flate2::read::GzDecoder;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::server::build_app;
+use serde_json::Value;
+use std::io::Read;
+
+const SIMPLE_SPEC: &str = "examples/specs/simple_get_users_spec-api.yaml";
+
+#[actix_web::test]
+async fn gzip_clients_get_compressed_bodies_under_compress() {
+    let settings = Settings {
+        compress: true,
+        ..Default::default()
+    };
+    let app = test::init_service(build_app(common::state_with_settings(
+        SIMPLE_SPEC,
+        settings,
+    )))
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/users")
+        .insert_header(("Accept-Encoding", "gzip"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("content-encoding").unwrap(), "gzip");
+
+    let compressed = test::read_body(resp).await;
+    let mut json = String::new();
+    GzDecoder::new(compressed.as_ref())
+        .read_to_string(&mut json)
+        .expect("a gzip body");
+    let body: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(body["users"][0]["name"], "John Doe");
+}
+
+#[actix_web::test]
+async fn bodies_are_sent_as_they_are_by_default() {
+    let app = test::init_service(build_app(common::state_from_spec(SIMPLE_SPEC))).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/users")
+        .insert_header(("Accept-Encoding", "gzip"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.headers().get("content-encoding").is_none());
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["users"][0]["name"], "John Doe");
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::routes;
+
+const SIMPLE_SPEC: &str = "examples/specs/simple_get_users_spec-api.yaml";
+
+#[actix_web::test]
+async fn get_responses_carry_a_stable_etag() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(SIMPLE_SPEC)))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let etag = resp.headers().get("etag").unwrap().clone();
+    assert!(etag.to_str().unwrap().starts_with('"'));
+
+    let req = test::TestRequest::get().uri("/api/users").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("etag"), Some(&etag));
+}
+
+#[actix_web::test]
+async fn matching_if_none_match_is_not_modified() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(SIMPLE_SPEC)))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users").to_request();
+    let resp = test::call_service(&app, req).await;
+    let etag = resp
+        .headers()
+        .get("etag")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    for if_none_match in [etag.clone(), format!("\"other\", W/{}", etag)] {
+        let req = test::TestRequest::get()
+            .uri("/get/users")
+            .insert_header(("If-None-Match", if_none_match))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 304);
+        assert_eq!(resp.headers().get("etag").unwrap(), etag.as_str());
+        assert!(test::read_body(resp).await.is_empty());
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/get/users")
+        .insert_header(("If-None-Match", "\"stale\""))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
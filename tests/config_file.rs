@@ -0,0 +1,61 @@
+use rusty_stub_api::data::cli_args::Args;
+use rusty_stub_api::data::latency::LatencyProfile;
+use std::path::PathBuf;
+
+fn write_config(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn command_line_wins_over_the_config_file() {
+    let path = write_config(
+        "stub.toml",
+        r#"
+spec = ["users.yaml", "orders.yaml"]
+host = "0.0.0.0"
+port = 9000
+latency-ms = 150
+error-rate = 0.25
+cors-origins = ["https://app.example.com"]
+"#,
+    );
+    let args = Args::load_from([
+        "rusty-stub-api",
+        "--config",
+        path.to_str().unwrap(),
+        "--port",
+        "7000",
+    ])
+    .expect("config should load");
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(args.port, 7000);
+    assert_eq!(args.host, vec!["0.0.0.0"]);
+    assert_eq!(args.spec, vec!["users.yaml", "orders.yaml"]);
+    assert_eq!(args.latency_ms, LatencyProfile::uniform(150));
+    assert_eq!(args.error_rate, 0.25);
+    assert_eq!(args.cors_origins, vec!["https://app.example.com"]);
+    // Left to the built-in default
+    assert_eq!(args.latency_jitter_ms, 0);
+}
+
+#[test]
+fn yaml_config_is_read_and_checked() {
+    let path = write_config("stub.yaml", "spec: api.yaml\nserver: 10.0.0.1\n");
+    let args = Args::load_from(["rusty-stub-api", "--config", path.to_str().unwrap()]).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(args.spec, vec!["api.yaml"]);
+    assert_eq!(args.host, vec!["10.0.0.1"]);
+
+    let path = write_config("invalid.yaml", "error-rate: 2.0\n");
+    let error = Args::load_from(["rusty-stub-api", "--config", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(error.is_err());
+
+    let path = write_config("unknown.yaml", "latency: 10\n");
+    let error = Args::load_from(["rusty-stub-api", "--config", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(error.unwrap_err().to_string().contains("latency"));
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::transactions::dynamic_handler;
+
+const COOKIE_SPEC: &str = "tests/fixtures/cookie_spec.yaml";
+
+#[actix_web::test]
+async fn every_cookie_gets_its_own_set_cookie_header() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(COOKIE_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/post/login").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let mut cookies: Vec<&str> = resp
+        .headers()
+        .get_all("set-cookie")
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    cookies.sort();
+    assert_eq!(cookies.len(), 4);
+    assert_eq!(cookies[0], "locale=en; Path=/");
+    assert!(cookies[1].starts_with("session=abc123"));
+    for attribute in [
+        "HttpOnly",
+        "Secure",
+        "SameSite=Strict",
+        "Path=/",
+        "Max-Age=3600",
+    ] {
+        assert!(
+            cookies[1].contains(attribute),
+            "{} lacks {}",
+            cookies[1],
+            attribute
+        );
+    }
+    assert_eq!(cookies[2], "theme=dark; Path=/");
+    assert_eq!(cookies[3], "tracking=off");
+}
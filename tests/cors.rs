@@ -0,0 +1,215 @@
+mod common;
+
+use actix_web::http::{header, Method};
+use actix_web::test;
+use clap::Parser;
+use rusty_stub_api::data::cli_args::Args;
+use rusty_stub_api::data::cors::CorsConfig;
+use rusty_stub_api::server::build_app;
+
+const SIMPLE_SPEC: &str = "examples/specs/simple_get_users_spec-api.yaml";
+const ORIGIN: &str = "https://app.example.com";
+
+#[actix_web::test]
+async fn any_origin_is_echoed_back() {
+    let cors = CorsConfig::default();
+    let app =
+        test::init_service(build_app(common::state_from_spec(SIMPLE_SPEC)).wrap(cors.build()))
+            .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/users")
+        .insert_header((header::ORIGIN, ORIGIN))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .unwrap(),
+        ORIGIN
+    );
+    assert!(resp
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+        .is_none());
+    let vary = resp.headers().get(header::VARY).unwrap().to_str().unwrap();
+    assert!(vary.contains("Origin"), "{}", vary);
+}
+
+fn configured_cors() -> CorsConfig {
+    let args = Args::try_parse_from([
+        "rusty-stub-api",
+        "--cors-origin",
+        ORIGIN,
+        "--cors-methods",
+        "get,post",
+        "--cors-headers",
+        "x-token",
+    ])
+    .unwrap();
+    CorsConfig::from_args(&args).unwrap()
+}
+
+#[actix_web::test]
+async fn preflights_list_the_configured_policy() {
+    let cors = configured_cors();
+    let app =
+        test::init_service(build_app(common::state_from_spec(SIMPLE_SPEC)).wrap(cors.build()))
+            .await;
+
+    let req = test::TestRequest::default()
+        .method(Method::OPTIONS)
+        .uri("/api/users")
+        .insert_header((header::ORIGIN, ORIGIN))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "POST"))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_HEADERS, "x-token"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let headers = resp.headers();
+    assert_eq!(
+        headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+        ORIGIN
+    );
+    let methods = headers
+        .get(header::ACCESS_CONTROL_ALLOW_METHODS)
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(
+        methods.contains("GET") && methods.contains("POST"),
+        "{}",
+        methods
+    );
+    assert!(!methods.contains("DELETE"), "{}", methods);
+    assert_eq!(
+        headers.get(header::ACCESS_CONTROL_ALLOW_HEADERS).unwrap(),
+        "x-token"
+    );
+}
+
+#[actix_web::test]
+async fn only_configured_origins_are_allowed() {
+    let cors = configured_cors();
+    let app =
+        test::init_service(build_app(common::state_from_spec(SIMPLE_SPEC)).wrap(cors.build()))
+            .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/users")
+        .insert_header((header::ORIGIN, ORIGIN))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .unwrap(),
+        ORIGIN
+    );
+
+    let req = test::TestRequest::default()
+        .method(Method::OPTIONS)
+        .uri("/api/users")
+        .insert_header((header::ORIGIN, "https://evil.example.com"))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "GET"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
+
+#[actix_web::test]
+async fn invalid_cors_flags_are_rejected() {
+    for flags in [
+        ["--cors-origin", "https://app.example.com/"],
+        ["--cors-origin", "app.example.com"],
+        ["--cors-methods", "get,not a method"],
+        ["--cors-headers", "x token"],
+    ] {
+        let args = Args::try_parse_from(["rusty-stub-api"].into_iter().chain(flags)).unwrap();
+        assert!(CorsConfig::from_args(&args).is_err(), "{:?}", flags);
+    }
+}
+
+#[actix_web::test]
+async fn credentialed_requests_get_their_origin_back() {
+    let cors = CorsConfig {
+        credentials: true,
+        ..CorsConfig::default()
+    };
+    let app =
+        test::init_service(build_app(common::state_from_spec(SIMPLE_SPEC)).wrap(cors.build()))
+            .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/users")
+        .insert_header((header::ORIGIN, ORIGIN))
+        .insert_header((header::COOKIE, "session=abc"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .unwrap(),
+        ORIGIN
+    );
+    assert_eq!(
+        resp.headers()
+            .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+            .unwrap(),
+        "true"
+    );
+    let vary = resp.headers().get(header::VARY).unwrap().to_str().unwrap();
+    assert!(vary.contains("Origin"), "{}", vary);
+}
+
+#[actix_web::test]
+async fn credentialed_preflights_echo_the_requested_headers() {
+    let cors = CorsConfig {
+        credentials: true,
+        ..CorsConfig::default()
+    };
+    let app =
+        test::init_service(build_app(common::state_from_spec(SIMPLE_SPEC)).wrap(cors.build()))
+            .await;
+
+    let req = test::TestRequest::default()
+        .method(Method::OPTIONS)
+        .uri("/api/users")
+        .insert_header((header::ORIGIN, ORIGIN))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "POST"))
+        .insert_header((
+            header::ACCESS_CONTROL_REQUEST_HEADERS,
+            "content-type,x-token",
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let headers = resp.headers();
+    assert_eq!(
+        headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+        ORIGIN
+    );
+    assert_eq!(
+        headers
+            .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+            .unwrap(),
+        "true"
+    );
+    assert_eq!(
+        headers.get(header::ACCESS_CONTROL_ALLOW_HEADERS).unwrap(),
+        "content-type,x-token"
+    );
+    let methods = headers
+        .get(header::ACCESS_CONTROL_ALLOW_METHODS)
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(methods.contains("POST"), "{}", methods);
+    assert!(!methods.contains('*'), "{}", methods);
+}
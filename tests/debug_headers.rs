@@ -0,0 +1,86 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+
+const ROUTING_SPEC: &str = "tests/fixtures/routing_spec.yaml";
+const STATUS_SPEC: &str = "tests/fixtures/status_spec.yaml";
+
+fn debug_settings() -> Settings {
+    Settings {
+        debug_headers: true,
+        ..Default::default()
+    }
+}
+
+#[actix_web::test]
+async fn responses_name_the_matched_endpoint() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                ROUTING_SPEC,
+                debug_settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/42").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("x-mock-matched-path").unwrap(),
+        "/users/{id}"
+    );
+    assert_eq!(resp.headers().get("x-mock-matched-method").unwrap(), "GET");
+    assert_eq!(resp.headers().get("x-mock-status-source").unwrap(), "spec");
+
+    let req = test::TestRequest::get().uri("/get/nowhere").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    assert!(resp.headers().get("x-mock-matched-path").is_none());
+    assert_eq!(
+        resp.headers().get("x-mock-status-source").unwrap(),
+        "unmatched"
+    );
+}
+
+#[actix_web::test]
+async fn requested_status_is_reported_as_its_source() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                STATUS_SPEC,
+                debug_settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/orders")
+        .insert_header(("X-Mock-Status", "500"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 500);
+    assert_eq!(
+        resp.headers().get("x-mock-status-source").unwrap(),
+        "requested"
+    );
+}
+
+#[actix_web::test]
+async fn debug_headers_are_off_by_default() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(ROUTING_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/42").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.headers().get("x-mock-matched-path").is_none());
+    assert!(resp.headers().get("x-mock-status-source").is_none());
+}
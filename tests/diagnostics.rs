@@ -0,0 +1,43 @@
+mod common;
+
+use actix_web::test;
+use rusty_stub_api::server::build_app;
+use serde_json::{json, Value};
+
+const SKIPPED_SPEC: &str = "tests/fixtures/skipped_spec.yaml";
+
+#[actix_web::test]
+async fn diagnostics_report_what_was_skipped() {
+    let app = test::init_service(build_app(common::state_from_spec(SKIPPED_SPEC))).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/diagnostics")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["count"], 2);
+    assert_eq!(
+        body["skipped"],
+        json!([
+            { "method": "query", "path": "/search", "reason": "unsupported method" },
+            {
+                "method": "get",
+                "path": "/search",
+                "reason": "response 404: unresolved reference #/components/responses/Missing",
+            },
+        ])
+    );
+}
+
+#[actix_web::test]
+async fn diagnostics_are_empty_when_everything_loaded() {
+    let app = test::init_service(build_app(common::state_from_spec(
+        "examples/specs/simple_get_users_spec-api.yaml",
+    )))
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/diagnostics")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({ "skipped": [], "count": 0 }));
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::Value;
+
+#[actix_web::test]
+async fn dynamic_handler_returns_json_object() {
+    let state = common::state_from_spec("examples/specs/simple_get_users_spec-api.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+
+    assert!(body.is_object(), "expected a JSON object, got {}", body);
+    assert_eq!(body["users"][0]["name"], "John Doe");
+}
@@ -0,0 +1,81 @@
+use actix_web::http::StatusCode;
+use openapiv3::OpenAPI;
+use rusty_stub_api::data::app::{AppState, EndpointHandler};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::{BuiltEndpoints, SkippedOperation};
+use serde_json::{json, Value};
+
+#[test]
+fn builder_starts_from_an_empty_endpoint() {
+    let endpoint = EndpointHandler::builder("/users/{id}", "get", "200", StatusCode::OK).build();
+
+    assert_eq!(endpoint.path, "/users/{id}");
+    assert_eq!(endpoint.method, "get");
+    assert_eq!(endpoint.response_code, "200");
+    assert_eq!(endpoint.status, StatusCode::OK);
+    assert_eq!(endpoint.response_body, Value::Null);
+    assert!(endpoint.operation_id.is_none());
+    assert!(endpoint.delay_ms.is_none());
+    assert!(endpoint.headers.is_empty());
+    assert!(endpoint.content_types.is_empty());
+    assert!(endpoint.request_schema.is_none());
+    assert!(!endpoint.request_body_required);
+    assert!(endpoint.security.is_empty());
+    assert!(!endpoint.chaos_exempt);
+    assert!(endpoint.circuit.is_none());
+    assert!(endpoint.sequence.is_empty());
+    assert!(!endpoint.is_conditional());
+    assert!(!endpoint.empty_body);
+    assert!(endpoint.cookies.is_empty());
+    assert_eq!(endpoint.next_in_sequence(), None);
+}
+
+#[test]
+fn builder_sets_what_it_is_given() {
+    let endpoint = EndpointHandler::builder("/jobs", "post", "2XX", StatusCode::OK)
+        .with_operation_id(Some("createJob".to_string()))
+        .with_response_body(json!({ "id": 1 }))
+        .with_delay_ms(Some(250))
+        .with_headers(vec![("X-Job".to_string(), "queued".to_string())])
+        .with_sequence(vec![json!("first"), json!("second")])
+        .with_query_match(vec![("mode".to_string(), "fast".to_string())])
+        .build();
+
+    assert_eq!(endpoint.operation_id.as_deref(), Some("createJob"));
+    assert_eq!(endpoint.response_body, json!({ "id": 1 }));
+    assert_eq!(endpoint.delay_ms, Some(250));
+    assert_eq!(endpoint.headers.len(), 1);
+    assert!(endpoint.is_conditional());
+    assert_eq!(endpoint.next_in_sequence(), Some(&json!("first")));
+    assert_eq!(endpoint.next_in_sequence(), Some(&json!("second")));
+}
+
+#[test]
+fn app_state_is_built_from_its_configuration() {
+    let built = BuiltEndpoints {
+        endpoints: vec![
+            EndpointHandler::builder("/users/{id}", "get", "200", StatusCode::OK).build(),
+            EndpointHandler::builder("/users/me", "get", "200", StatusCode::OK).build(),
+        ],
+        skipped: vec![SkippedOperation {
+            method: Some("trace".to_string()),
+            path: "/users".to_string(),
+            reason: "unsupported".to_string(),
+        }],
+    };
+    let settings = Settings {
+        stateful: true,
+        rate_limit: Some(5),
+        ..Settings::default()
+    };
+    let state = AppState::from_config(settings, built, OpenAPI::default());
+
+    assert!(state.settings.stateful);
+    assert!(state.rate_limiter.is_some());
+    assert_eq!(state.skipped.read().unwrap().len(), 1);
+    // The most specific path is matched first
+    assert_eq!(state.endpoints.read().unwrap()[0].path, "/users/me");
+    assert!(state.recorder.is_none());
+    assert!(state.upstream.is_none());
+    assert!(state.sources.is_empty());
+}
@@ -0,0 +1,38 @@
+use rusty_stub_api::data::app::EndpointHandler;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::endpoint_list::{render, ListFormat};
+use rusty_stub_api::spec_source::SpecFormat;
+use rusty_stub_api::transactions::build_endpoints_from_spec;
+use serde_json::Value;
+
+fn methods_endpoints() -> Vec<EndpointHandler> {
+    let content = std::fs::read_to_string("tests/fixtures/methods_spec.yaml").unwrap();
+    build_endpoints_from_spec(&content, SpecFormat::Yaml, &Settings::default())
+        .unwrap()
+        .endpoints
+}
+
+#[test]
+fn table_lists_every_endpoint() {
+    let table = render(&methods_endpoints(), ListFormat::Table);
+    let lines: Vec<&str> = table.lines().collect();
+
+    assert_eq!(lines[0], "METHOD   PATH            STATUS  CONTENT TYPES");
+    assert_eq!(
+        lines[1],
+        "PATCH    /profile        200     application/json"
+    );
+    assert_eq!(lines[4], "DELETE   /sessions/{id}  204     (no body)");
+    assert_eq!(lines[5], "4 endpoints");
+}
+
+#[test]
+fn json_lists_every_endpoint() {
+    let list: Value =
+        serde_json::from_str(&render(&methods_endpoints(), ListFormat::Json)).unwrap();
+
+    assert_eq!(list["count"], 4);
+    assert_eq!(list["endpoints"][0]["method"], "patch");
+    assert_eq!(list["endpoints"][0]["status_code"], "200");
+    assert_eq!(list["endpoints"][0]["content_types"][0], "application/json");
+}
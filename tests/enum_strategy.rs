@@ -0,0 +1,85 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::generator::EnumStrategy;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::{json, Value};
+
+const ENUM_SPEC: &str = "tests/fixtures/enum_spec.yaml";
+
+fn settings(enum_strategy: EnumStrategy) -> Settings {
+    Settings {
+        enum_strategy,
+        ..Settings::default()
+    }
+}
+
+async fn bodies(enum_strategy: EnumStrategy, uri: &str, count: usize) -> Vec<Value> {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                ENUM_SPEC,
+                settings(enum_strategy),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let mut bodies = Vec::new();
+    for _ in 0..count {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        bodies.push(test::call_and_read_body_json(&app, req).await);
+    }
+    bodies
+}
+
+#[actix_web::test]
+async fn first_strategy_always_serves_the_first_value() {
+    let bodies = bodies(EnumStrategy::First, "/get/orders/1", 3).await;
+    assert!(bodies
+        .iter()
+        .all(|body| *body == json!({ "status": "pending", "priority": 1 })));
+}
+
+#[actix_web::test]
+async fn round_robin_strategy_cycles_on_every_call() {
+    let bodies = bodies(EnumStrategy::RoundRobin, "/get/orders/1", 4).await;
+    assert_eq!(
+        bodies,
+        vec![
+            json!({ "status": "pending", "priority": 1 }),
+            json!({ "status": "shipped", "priority": 2 }),
+            json!({ "status": "delivered", "priority": 1 }),
+            json!({ "status": "pending", "priority": 1 }),
+        ]
+    );
+}
+
+#[actix_web::test]
+async fn round_robin_strategy_leaves_examples_alone() {
+    let bodies = bodies(EnumStrategy::RoundRobin, "/get/flags", 2).await;
+    assert_eq!(bodies, vec![json!({ "mode": "off" }); 2]);
+}
+
+#[actix_web::test]
+async fn random_strategy_is_reproducible_from_the_seed() {
+    let seeded = |seed| {
+        let settings = Settings {
+            seed: Some(seed),
+            ..settings(EnumStrategy::Random)
+        };
+        common::state_with_settings(ENUM_SPEC, settings)
+            .endpoints
+            .read()
+            .unwrap()
+            .iter()
+            .find(|endpoint| endpoint.path == "/orders/{id}")
+            .map(|endpoint| endpoint.response_body.clone())
+            .unwrap()
+    };
+    assert_eq!(seeded(7), seeded(7));
+
+    let statuses: Vec<Value> = (0..16).map(|seed| seeded(seed)["status"].clone()).collect();
+    assert!(statuses.iter().any(|status| *status != statuses[0]));
+}
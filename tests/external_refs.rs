@@ -0,0 +1,56 @@
+use actix_web::{test, web, App};
+use rusty_stub_api::data::app::AppState;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::errors::AppError;
+use rusty_stub_api::spec_merge::load_merged;
+use rusty_stub_api::spec_source::SpecSource;
+use rusty_stub_api::transactions::{
+    build_endpoints_from_document, dynamic_handler, parse_openapi_document,
+};
+use serde_json::Value;
+use std::sync::Arc;
+
+fn load(spec: &str) -> Result<serde_yaml::Value, AppError> {
+    load_merged(&[SpecSource::from(spec)], false)
+}
+
+#[actix_web::test]
+async fn schemas_in_other_files_are_inlined() {
+    let document = load("tests/fixtures/multi_file/spec.yaml").expect("spec should load");
+    let built = build_endpoints_from_document(&document, &Settings::default()).unwrap();
+    let spec = parse_openapi_document(&document).unwrap();
+    assert!(built.skipped.is_empty(), "{:?}", built.skipped);
+
+    let state = Arc::new(AppState::new(built.endpoints, spec));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/42").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["id"], 42);
+    assert_eq!(body["email"], "ada@example.com");
+    // Local references of an external file point into that file
+    assert_eq!(body["address"]["city"], "London");
+
+    let req = test::TestRequest::get().uri("/get/users").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body[0]["address"]["city"], "London");
+}
+
+#[actix_web::test]
+async fn circular_file_references_are_reported() {
+    let error = load("tests/fixtures/multi_file/cycle_spec.yaml").unwrap_err();
+    assert!(matches!(error, AppError::RefError(_)), "{}", error);
+    assert!(error.to_string().contains("circular"), "{}", error);
+}
+
+#[actix_web::test]
+async fn missing_files_are_reported() {
+    let error = load("tests/fixtures/multi_file/missing_spec.yaml").unwrap_err();
+    assert!(matches!(error, AppError::RefError(_)), "{}", error);
+    assert!(error.to_string().contains("nowhere.yaml"), "{}", error);
+}
@@ -0,0 +1,396 @@
+use openapiv3::{OpenAPI, ReferenceOr, Schema};
+use rusty_stub_api::generator::{EnumStrategy, SchemaGenerator};
+use serde_json::Value;
+
+fn schema(yaml: &str) -> ReferenceOr<Schema> {
+    serde_yaml::from_str(yaml).expect("schema should parse")
+}
+
+fn empty_spec() -> OpenAPI {
+    serde_yaml::from_str("openapi: 3.0.0\ninfo: {title: t, version: '1'}\npaths: {}")
+        .expect("spec should parse")
+}
+
+#[test]
+fn required_properties_are_always_present() {
+    let spec = empty_spec();
+    let generator = SchemaGenerator::new(&spec);
+
+    let value = generator.generate(&schema(
+        "type: object
+required: [id, sku, legacy]
+properties:
+  id: {type: integer}
+  sku: {type: string}
+  active: {type: boolean}",
+    ));
+    assert_eq!(
+        value,
+        serde_json::json!({"id": 0, "sku": "string", "active": true, "legacy": null})
+    );
+}
+
+#[test]
+fn enums_are_served_their_first_value() {
+    let spec = empty_spec();
+    let generator = SchemaGenerator::new(&spec);
+
+    assert_eq!(
+        generator.generate(&schema("type: string\nenum: [active, archived]")),
+        "active"
+    );
+    assert_eq!(
+        generator.generate(&schema("type: integer\nenum: [3, 1, 2]")),
+        3
+    );
+    assert_eq!(
+        generator.generate(&schema("type: number\nenum: [2.5, 1.5]")),
+        2.5
+    );
+}
+
+#[test]
+fn references_are_resolved_through_the_components() {
+    let spec: OpenAPI = serde_yaml::from_str(
+        r#"
+openapi: 3.0.0
+info: {title: t, version: '1'}
+paths: {}
+components:
+  schemas:
+    User:
+      type: object
+      properties:
+        id: {type: integer, example: 42}
+        address: {$ref: '#/components/schemas/Address'}
+    Address:
+      $ref: '#/components/schemas/PostalAddress'
+    PostalAddress:
+      type: object
+      properties:
+        city: {type: string, example: Lisbon}
+"#,
+    )
+    .expect("spec should parse");
+    let generator = SchemaGenerator::new(&spec);
+
+    let value = generator.generate(&schema("$ref: '#/components/schemas/User'"));
+    assert_eq!(
+        value,
+        serde_json::json!({"id": 42, "address": {"city": "Lisbon"}})
+    );
+
+    let value = generator.generate(&schema("$ref: '#/components/schemas/Missing'"));
+    assert_eq!(value, Value::Null);
+}
+
+#[test]
+fn pattern_constrained_string_matches_its_pattern() {
+    let spec = empty_spec();
+    let generator = SchemaGenerator::new(&spec);
+    let pattern = r"^[A-Z]{3}-\d{4}$";
+
+    let value = generator.generate(&schema(&format!("type: string\npattern: '{}'", pattern)));
+    let value = value.as_str().expect("a string");
+    assert!(
+        regex::Regex::new(pattern).unwrap().is_match(value),
+        "{} does not match {}",
+        value,
+        pattern
+    );
+}
+
+#[test]
+fn bounded_integer_stays_within_bounds() {
+    let spec = empty_spec();
+    let generator = SchemaGenerator::new(&spec);
+
+    let value = generator.generate(&schema("type: integer\nminimum: 18\nmaximum: 99"));
+    assert_eq!(value, 18);
+
+    let value = generator.generate(&schema("type: integer\nmaximum: -5"));
+    assert_eq!(value, -5);
+
+    let value = generator.generate(&schema("type: integer\nminimum: 0\nexclusiveMinimum: true"));
+    assert_eq!(value, 1);
+}
+
+#[test]
+fn string_length_and_array_size_are_respected() {
+    let spec = empty_spec();
+    let generator = SchemaGenerator::new(&spec);
+
+    let value = generator.generate(&schema("type: string\nminLength: 10"));
+    assert_eq!(value.as_str().unwrap().len(), 10);
+
+    let value = generator.generate(&schema("type: string\nmaxLength: 3"));
+    assert_eq!(value.as_str().unwrap().len(), 3);
+
+    let value = generator.generate(&schema("type: array\nminItems: 3\nitems:\n  type: integer"));
+    assert_eq!(value.as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn formats_and_field_names_get_realistic_values() {
+    let spec = empty_spec();
+    let generator = SchemaGenerator::new(&spec);
+
+    let value = generator.generate(&schema(
+        "type: object
+properties:
+  id: {type: string, format: uuid}
+  contact: {type: string, format: email}
+  email: {type: string}
+  createdAt: {type: string}
+  updated: {type: string, format: date-time}
+  name: {type: string}
+  name_code: {type: string}",
+    ));
+    uuid::Uuid::parse_str(value["id"].as_str().unwrap()).expect("a UUID");
+    assert!(value["contact"].as_str().unwrap().contains('@'));
+    assert!(value["email"].as_str().unwrap().contains('@'));
+    let rfc3339 = regex::Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z$").unwrap();
+    for field in ["createdAt", "updated"] {
+        let timestamp = value[field].as_str().unwrap();
+        assert!(
+            rfc3339.is_match(timestamp),
+            "{} is not a timestamp",
+            timestamp
+        );
+    }
+    assert_ne!(value["name"], "string");
+    assert_eq!(value["name_code"], "string");
+}
+
+#[test]
+fn byte_strings_are_valid_base64() {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    let spec = empty_spec();
+    let generator = SchemaGenerator::new(&spec);
+
+    let value = generator.generate(&schema(
+        "type: object
+properties:
+  thumbnail: {type: string, format: byte}
+  attachment: {type: string, format: binary}",
+    ));
+    for field in ["thumbnail", "attachment"] {
+        let encoded = value[field].as_str().unwrap();
+        let decoded = STANDARD.decode(encoded).expect("valid base64");
+        assert!(!decoded.is_empty());
+        assert_eq!(STANDARD.encode(&decoded), encoded);
+    }
+}
+
+#[test]
+fn fake_values_are_reproducible_from_the_seed() {
+    let spec = empty_spec();
+    let email = schema("type: object\nproperties:\n  email: {type: string}");
+
+    let first = SchemaGenerator::new(&spec).generate(&email);
+    assert_eq!(first, SchemaGenerator::new(&spec).generate(&email));
+
+    let seeded = SchemaGenerator::new(&spec)
+        .with_seed(Some(7))
+        .generate(&email);
+    assert_eq!(
+        seeded,
+        SchemaGenerator::new(&spec)
+            .with_seed(Some(7))
+            .generate(&email)
+    );
+    assert_ne!(first, seeded);
+}
+
+#[test]
+fn fake_values_respect_length_constraints() {
+    let spec = empty_spec();
+    let generator = SchemaGenerator::new(&spec);
+
+    let value = generator.generate(&schema(
+        "type: object\nproperties:\n  email: {type: string, maxLength: 3}",
+    ));
+    assert_eq!(value["email"], "str");
+}
+
+fn pets_spec() -> OpenAPI {
+    serde_yaml::from_str(
+        r#"
+openapi: 3.0.0
+info: {title: t, version: '1'}
+paths: {}
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [id]
+      properties:
+        id: {type: integer, example: 7}
+        petType: {type: string}
+    Cat:
+      allOf:
+        - $ref: '#/components/schemas/Pet'
+        - type: object
+          properties:
+            lives: {type: integer, example: 9}
+    Dog:
+      allOf:
+        - $ref: '#/components/schemas/Pet'
+        - type: object
+          properties:
+            goodBoy: {type: boolean}
+"#,
+    )
+    .expect("spec should parse")
+}
+
+#[test]
+fn all_of_merges_the_inherited_properties() {
+    let spec = pets_spec();
+    let generator = SchemaGenerator::new(&spec);
+
+    let value = generator.generate(&schema("$ref: '#/components/schemas/Cat'"));
+    assert_eq!(value["id"], 7);
+    assert_eq!(value["petType"], "string");
+    assert_eq!(value["lives"], 9);
+}
+
+#[test]
+fn one_of_follows_the_discriminator() {
+    let spec = pets_spec();
+    let generator = SchemaGenerator::new(&spec);
+
+    let value = generator.generate(&schema(
+        r#"
+oneOf:
+  - $ref: '#/components/schemas/Cat'
+  - $ref: '#/components/schemas/Dog'
+discriminator:
+  propertyName: petType
+  mapping:
+    dog: '#/components/schemas/Dog'
+    cat: Cat
+"#,
+    ));
+    assert_eq!(value["petType"], "dog");
+    assert_eq!(value["goodBoy"], true);
+    assert!(value.get("lives").is_none());
+
+    // Without a mapping the first option is served, tagged with its name
+    let value = generator.generate(&schema(
+        r#"
+anyOf:
+  - $ref: '#/components/schemas/Cat'
+  - $ref: '#/components/schemas/Dog'
+discriminator:
+  propertyName: petType
+"#,
+    ));
+    assert_eq!(value["petType"], "Cat");
+    assert_eq!(value["lives"], 9);
+}
+
+fn tree_spec() -> OpenAPI {
+    serde_yaml::from_str(
+        r#"
+openapi: 3.0.0
+info: {title: t, version: '1'}
+paths: {}
+components:
+  schemas:
+    Node:
+      type: object
+      properties:
+        label: {type: string}
+        parent: {$ref: '#/components/schemas/Node'}
+        children:
+          type: array
+          items: {$ref: '#/components/schemas/Node'}
+    Ping:
+      type: object
+      properties:
+        pong: {$ref: '#/components/schemas/Pong'}
+    Pong:
+      type: object
+      properties:
+        ping: {$ref: '#/components/schemas/Ping'}
+"#,
+    )
+    .expect("spec should parse")
+}
+
+#[test]
+fn recursive_schemas_stop_at_the_cycle() {
+    let spec = tree_spec();
+    let generator = SchemaGenerator::new(&spec);
+
+    let value = generator.generate(&schema("$ref: '#/components/schemas/Node'"));
+    assert_eq!(value["label"], "string");
+    assert_eq!(value["parent"], Value::Null);
+    assert_eq!(value["children"], serde_json::json!([]));
+
+    let value = generator.generate(&schema("$ref: '#/components/schemas/Ping'"));
+    assert_eq!(value, serde_json::json!({"pong": {"ping": null}}));
+}
+
+#[test]
+fn nesting_past_the_max_depth_is_served_empty() {
+    let spec = empty_spec();
+    let nested = schema(
+        "type: object
+properties:
+  a:
+    type: object
+    properties:
+      b:
+        type: array
+        items:
+          type: object
+          properties:
+            c: {type: integer}",
+    );
+
+    let value = SchemaGenerator::new(&spec).generate(&nested);
+    assert_eq!(value, serde_json::json!({"a": {"b": [{"c": 0}]}}));
+
+    let value = SchemaGenerator::new(&spec)
+        .with_max_depth(2)
+        .generate(&nested);
+    assert_eq!(value, serde_json::json!({"a": {"b": []}}));
+
+    let value = SchemaGenerator::new(&spec)
+        .with_max_depth(0)
+        .generate(&nested);
+    assert_eq!(value, serde_json::json!({}));
+}
+
+#[test]
+fn enum_strategies_pick_their_value() {
+    let spec = empty_spec();
+    let colors = schema("type: string\nenum: [red, green, blue]");
+
+    let generator = SchemaGenerator::new(&spec).with_enum_strategy(EnumStrategy::First);
+    assert_eq!(generator.generate(&colors), "red");
+
+    let picked: Vec<Value> = (0..4)
+        .map(|position| {
+            SchemaGenerator::new(&spec)
+                .with_enum_strategy(EnumStrategy::RoundRobin)
+                .with_enum_position(position)
+                .generate(&colors)
+        })
+        .collect();
+    assert_eq!(picked, vec!["red", "green", "blue", "red"]);
+    assert_eq!(generator.enum_cycle(), 3);
+
+    let random = |seed| {
+        SchemaGenerator::new(&spec)
+            .with_seed(Some(seed))
+            .with_enum_strategy(EnumStrategy::Random)
+            .generate(&colors)
+    };
+    assert_eq!(random(3), random(3));
+    assert!((0..16).any(|seed| random(seed) != random(0)));
+}
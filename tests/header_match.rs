@@ -0,0 +1,63 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::{json, Value};
+
+const HEADER_MATCH_SPEC: &str = "tests/fixtures/header_match_spec.yaml";
+
+#[actix_web::test]
+async fn api_version_header_selects_the_matching_response() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(HEADER_MATCH_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    for (version, uri, status, body) in [
+        (None, "/get/widgets", 200, json!({ "version": 1 })),
+        (Some("1"), "/get/widgets", 200, json!({ "version": 1 })),
+        (Some("2"), "/get/widgets", 203, json!({ "version": 2 })),
+        (
+            Some("2"),
+            "/get/widgets?beta=true",
+            206,
+            json!({ "version": 2, "beta": true }),
+        ),
+        (None, "/get/widgets?beta=true", 200, json!({ "version": 1 })),
+        (
+            Some("0"),
+            "/get/widgets",
+            410,
+            json!({ "error": "version retired" }),
+        ),
+    ] {
+        let mut req = test::TestRequest::get().uri(uri);
+        if let Some(version) = version {
+            req = req.insert_header(("Api-Version", version));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), status, "{:?} {}", version, uri);
+
+        let served: Value = test::read_body_json(resp).await;
+        assert_eq!(served, body, "{:?} {}", version, uri);
+    }
+}
+
+#[actix_web::test]
+async fn requested_status_wins_over_header_match() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(HEADER_MATCH_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/widgets?__status=200")
+        .insert_header(("Api-Version", "2"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
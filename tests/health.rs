@@ -0,0 +1,70 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::app::ReloadState;
+use rusty_stub_api::reload::reload_spec;
+use rusty_stub_api::routes;
+use rusty_stub_api::spec_source::SpecSource;
+use serde_json::Value;
+
+#[actix_web::test]
+async fn liveness_is_served_under_both_paths() {
+    let state = common::state_from_spec("tests/fixtures/default_spec.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for uri in ["/health", "/health/live"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["status"], "healthy", "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn readiness_follows_the_reloads() {
+    let spec_path = std::env::temp_dir().join(format!("health-ready-{}.yaml", std::process::id()));
+    std::fs::copy("tests/fixtures/default_spec.yaml", &spec_path).unwrap();
+    let state = common::state_from_spec(spec_path.to_str().unwrap());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/health/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["title"], state.get_spec().info.title.as_str());
+    assert!(body["endpoints"].as_u64().unwrap() > 0);
+
+    state.set_reload_state(ReloadState::InProgress);
+    let req = test::TestRequest::get().uri("/health/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "reloading");
+
+    std::fs::write(&spec_path, "openapi: [not, a, spec").unwrap();
+    let sources = vec![SpecSource::File(spec_path.clone())];
+    assert!(reload_spec(&state, &sources, &state.settings).is_err());
+    let req = test::TestRequest::get().uri("/health/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "reload_failed");
+
+    std::fs::copy("tests/fixtures/default_spec.yaml", &spec_path).unwrap();
+    reload_spec(&state, &sources, &state.settings).unwrap();
+    let req = test::TestRequest::get().uri("/health/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    std::fs::remove_file(&spec_path).unwrap();
+}
@@ -0,0 +1,28 @@
+use clap::Parser;
+use rusty_stub_api::data::cli_args::Args;
+
+#[test]
+fn http2_combines_with_tls_but_not_a_unix_socket() {
+    let args = Args::try_parse_from([
+        "rusty-stub-api",
+        "--spec",
+        "api.yaml",
+        "--http2",
+        "--tls-cert",
+        "cert.pem",
+        "--tls-key",
+        "key.pem",
+    ])
+    .expect("h2c and TLS flags should combine");
+    assert!(args.http2);
+
+    let conflict = Args::try_parse_from([
+        "rusty-stub-api",
+        "--spec",
+        "api.yaml",
+        "--http2",
+        "--unix-socket",
+        "/tmp/stub.sock",
+    ]);
+    assert!(conflict.is_err());
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use actix_web::test;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::server::build_app;
+use serde_json::{json, Value};
+
+const TEMPLATE_SPEC: &str = "tests/fixtures/template_spec.yaml";
+
+fn templating_settings() -> Settings {
+    Settings {
+        templates: true,
+        ..Settings::default()
+    }
+}
+
+#[actix_web::test]
+async fn operations_are_invoked_by_their_id() {
+    let app = test::init_service(build_app(common::state_with_settings(
+        TEMPLATE_SPEC,
+        templating_settings(),
+    )))
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/op/getUser")
+        .set_json(json!({ "path": { "id": "john doe/1" }, "query": { "name": "Ada" } }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["id"], "john doe/1");
+    assert_eq!(body["greeting"], "Hello Ada");
+
+    let req = test::TestRequest::post()
+        .uri("/api/op/createUser")
+        .set_json(json!({ "body": { "user": { "id": 7, "name": "Ada" }, "roles": [] } }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["id"], 7);
+    assert_eq!(body["name"], "Ada");
+}
+
+#[actix_web::test]
+async fn unknown_operations_and_missing_parameters_are_reported() {
+    let app = test::init_service(build_app(common::state_with_settings(
+        TEMPLATE_SPEC,
+        templating_settings(),
+    )))
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/op/deleteUser")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    let req = test::TestRequest::post()
+        .uri("/api/op/getUser")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["missing"], json!(["id"]));
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::routes;
+
+const KEY_ORDER_SPEC: &str = "tests/fixtures/key_order_spec.yaml";
+
+#[actix_web::test]
+async fn bodies_are_serialized_with_sorted_keys() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(KEY_ORDER_SPEC)))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let expected = r#"{"alpha":"first","middle":{"b":false,"y":true},"zeta":1}"#;
+    for uri in ["/get/profile", "/api/profile", "/get/profile"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, expected, "{}", uri);
+    }
+}
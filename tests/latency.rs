@@ -0,0 +1,93 @@
+mod common;
+
+use actix_web::{web, App};
+use rusty_stub_api::data::latency::LatencyProfile;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use std::time::{Duration, Instant};
+
+fn seeded_settings(seed: u64) -> Settings {
+    Settings {
+        latency_jitter_ms: 50,
+        seed: Some(seed),
+        ..Settings::default()
+    }
+}
+
+#[test]
+fn jitter_stays_within_bounds() {
+    let state = common::state_with_settings("tests/fixtures/status_spec.yaml", seeded_settings(7));
+
+    for _ in 0..200 {
+        let delay = state.jittered_delay(100);
+        assert!(
+            (100..=150).contains(&delay),
+            "delay {} out of bounds",
+            delay
+        );
+    }
+}
+
+#[test]
+fn same_seed_gives_same_delays() {
+    let first = common::state_with_settings("tests/fixtures/status_spec.yaml", seeded_settings(7));
+    let second = common::state_with_settings("tests/fixtures/status_spec.yaml", seeded_settings(7));
+
+    let first: Vec<u64> = (0..20).map(|_| first.jittered_delay(0)).collect();
+    let second: Vec<u64> = (0..20).map(|_| second.jittered_delay(0)).collect();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn latency_profile_is_parsed_by_status_class() {
+    let profile: LatencyProfile = "2xx=10, 5XX=2000".parse().unwrap();
+    assert_eq!(profile.delay_for(actix_web::http::StatusCode::OK), 10);
+    assert_eq!(
+        profile.delay_for(actix_web::http::StatusCode::SERVICE_UNAVAILABLE),
+        2000
+    );
+    assert_eq!(profile.delay_for(actix_web::http::StatusCode::NOT_FOUND), 0);
+
+    let profile: LatencyProfile = "50,5xx=2000".parse().unwrap();
+    assert_eq!(
+        profile.delay_for(actix_web::http::StatusCode::NOT_FOUND),
+        50
+    );
+    assert_eq!(
+        "75".parse::<LatencyProfile>(),
+        Ok(LatencyProfile::uniform(75))
+    );
+
+    assert!("6xx=10".parse::<LatencyProfile>().is_err());
+    assert!("2xx=soon".parse::<LatencyProfile>().is_err());
+}
+
+#[actix_web::test]
+async fn errors_are_delayed_by_their_status_class() {
+    let settings = Settings {
+        latency_ms: "2xx=0,5xx=300".parse().unwrap(),
+        ..Settings::default()
+    };
+    let app = actix_web::test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                "tests/fixtures/status_spec.yaml",
+                settings,
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let mut timed = Vec::new();
+    for uri in ["/get/orders", "/get/orders?__status=500"] {
+        let started = Instant::now();
+        let req = actix_web::test::TestRequest::get().uri(uri).to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        timed.push((resp.status().as_u16(), started.elapsed()));
+    }
+
+    assert_eq!(timed[0].0, 200);
+    assert!(timed[0].1 < Duration::from_millis(300), "{:?}", timed[0]);
+    assert_eq!(timed[1].0, 500);
+    assert!(timed[1].1 >= Duration::from_millis(300), "{:?}", timed[1]);
+}
@@ -0,0 +1,20 @@
+use clap::Parser;
+use rusty_stub_api::access_log::log_filter;
+use rusty_stub_api::data::cli_args::Args;
+
+fn filter_for(flags: &[&str]) -> Option<&'static str> {
+    let args = Args::try_parse_from(["rusty-stub-api"].iter().chain(flags)).unwrap();
+    log_filter(args.quiet, args.verbose)
+}
+
+#[test]
+fn quiet_and_verbose_flags_pick_the_log_filter() {
+    assert_eq!(filter_for(&[]), None);
+    assert_eq!(filter_for(&["-q"]), Some("error"));
+    assert_eq!(filter_for(&["-v"]), Some("info,rusty_stub_api=debug"));
+    assert_eq!(filter_for(&["-vv"]), Some("debug"));
+    assert_eq!(filter_for(&["-v", "-v", "-v"]), Some("trace"));
+    assert_eq!(filter_for(&["--verbose", "-vvv"]), Some("trace"));
+
+    assert!(Args::try_parse_from(["rusty-stub-api", "-q", "-v"]).is_err());
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::Value;
+
+const METHODS_SPEC: &str = "tests/fixtures/methods_spec.yaml";
+
+#[actix_web::test]
+async fn patch_endpoint_is_matched() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(METHODS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/patch/profile").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["updated"], true);
+}
+
+#[actix_web::test]
+async fn head_endpoint_has_no_body() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(METHODS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/head/profile").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(test::read_body(resp).await.is_empty());
+}
+
+#[actix_web::test]
+async fn undeclared_method_on_known_path_is_not_allowed() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(METHODS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/delete/profile").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers().get("allow").unwrap(), "HEAD, OPTIONS, PATCH");
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Method not allowed");
+}
+
+#[actix_web::test]
+async fn unknown_path_is_not_found() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(METHODS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/delete/settings")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    assert!(resp.headers().get("allow").is_none());
+}
+
+#[actix_web::test]
+async fn no_content_response_has_no_body() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(METHODS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/delete/sessions/42")
+        .insert_header(("Accept", "text/plain"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 204);
+    assert!(resp.headers().get("content-type").is_none());
+    assert!(test::read_body(resp).await.is_empty());
+}
+
+#[actix_web::test]
+async fn head_reports_the_length_of_the_get_body() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(
+                "tests/fixtures/routing_spec.yaml",
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/42").to_request();
+    let get = test::call_service(&app, req).await;
+    assert_eq!(get.status(), 200);
+    let content_type = get.headers().get("content-type").cloned();
+    let body = test::read_body(get).await;
+
+    let req = test::TestRequest::get().uri("/head/users/42").to_request();
+    let head = test::call_service(&app, req).await;
+    assert_eq!(head.status(), 200);
+    assert_eq!(
+        head.headers().get("content-length").unwrap(),
+        body.len().to_string().as_str()
+    );
+    assert_eq!(head.headers().get("content-type").cloned(), content_type);
+    assert!(test::read_body(head).await.is_empty());
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::{dynamic_handler, metrics};
+
+#[actix_web::test]
+async fn requests_are_counted_in_metrics() {
+    let state = common::state_from_spec("tests/fixtures/routing_spec.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/metrics", web::get().to(metrics))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    for uri in ["/get/users/1", "/get/users/2", "/get/missing"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        test::call_service(&app, req).await;
+    }
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    let body = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(
+        body.contains(r#"stub_requests_total{method="GET",path="/users/{id}",status="200"} 2"#),
+        "{}",
+        body
+    );
+    assert!(body.contains(r#"stub_requests_total{method="GET",path="unmatched",status="404"} 1"#));
+    assert!(
+        body.contains(r#"stub_request_duration_seconds_count{method="GET",path="/users/{id}"} 2"#)
+    );
+}
+
+#[actix_web::test]
+async fn head_answered_as_get_is_reported_under_its_endpoint() {
+    let state = common::state_with_settings(
+        "tests/fixtures/routing_spec.yaml",
+        Settings {
+            debug_headers: true,
+            ..Settings::default()
+        },
+    );
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/metrics", web::get().to(metrics))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/head/users/1").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("x-mock-matched-path").unwrap(),
+        "/users/{id}"
+    );
+    assert_eq!(resp.headers().get("x-mock-matched-method").unwrap(), "GET");
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(
+        body.contains(r#"stub_requests_total{method="HEAD",path="/users/{id}",status="200"} 1"#),
+        "{}",
+        body
+    );
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::{json, Value};
+
+const MOCK_RESPONSE_SPEC: &str = "tests/fixtures/mock_response_spec.yaml";
+
+#[actix_web::test]
+async fn x_mock_response_wins_over_examples_and_schemas() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(MOCK_RESPONSE_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    // Declared on the operation, it replaces the generated body
+    let req = test::TestRequest::get().uri("/get/flags").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({"flags": ["dark-mode"]}));
+
+    // Declared on the response, it wins over the operation's and the example
+    let req = test::TestRequest::get()
+        .uri("/get/flags")
+        .insert_header(("X-Mock-Status", "503"))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({"error": "maintenance"}));
+}
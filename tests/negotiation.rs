@@ -0,0 +1,55 @@
+mod common;
+
+use actix_web::http::header;
+use actix_web::{test, web, App};
+use rusty_stub_api::transactions::dynamic_handler;
+
+const NEGOTIATION_SPEC: &str = "tests/fixtures/negotiation_spec.yaml";
+
+#[actix_web::test]
+async fn accept_ranges_pick_the_content_type() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(NEGOTIATION_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    for (accept, expected) in [
+        (None, Some("application/json")),
+        (Some("application/*"), Some("application/json")),
+        (Some("text/*"), Some("text/csv")),
+        (Some("*/*"), Some("application/json")),
+        (Some("application/xml, */*"), Some("application/xml")),
+        (Some("application/json;q=0.5, text/csv"), Some("text/csv")),
+        (
+            Some("application/json;q=0, */*;q=0.1"),
+            Some("application/xml"),
+        ),
+        (Some("application/*;q=0, text/csv;q=0.2"), Some("text/csv")),
+        (
+            Some("Application/XML; Q=0.9, application/json; q=0.8"),
+            Some("application/xml"),
+        ),
+        (Some("application/xml;q=0"), None),
+        (Some("image/*"), None),
+    ] {
+        let mut req = test::TestRequest::get().uri("/get/reports");
+        if let Some(accept) = accept {
+            req = req.insert_header((header::ACCEPT, accept));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        match expected {
+            Some(content_type) => {
+                assert_eq!(resp.status(), 200, "{:?}", accept);
+                assert_eq!(
+                    resp.headers().get(header::CONTENT_TYPE).unwrap(),
+                    content_type,
+                    "{:?}",
+                    accept
+                );
+            }
+            None => assert_eq!(resp.status(), 406, "{:?}", accept),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use actix_web::{test, web, App};
+use rusty_stub_api::data::app::AppState;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::routes;
+use rusty_stub_api::spec_source::SpecFormat;
+use rusty_stub_api::transactions::{build_endpoints_from_spec, parse_openapi_spec};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const METHODS_SPEC: &str = "tests/fixtures/methods_spec.yaml";
+
+fn state(settings: Settings, not_found_body: Option<Value>) -> Arc<AppState> {
+    let content = std::fs::read_to_string(METHODS_SPEC).unwrap();
+    let spec = parse_openapi_spec(&content, SpecFormat::Yaml).unwrap();
+    let endpoints = build_endpoints_from_spec(&content, SpecFormat::Yaml, &settings)
+        .unwrap()
+        .endpoints;
+    let mut state = AppState::new(endpoints, spec).with_settings(settings);
+    if let Some(body) = not_found_body {
+        state = state.with_not_found_body(body);
+    }
+    Arc::new(state)
+}
+
+#[actix_web::test]
+async fn unknown_endpoints_get_the_configured_body_and_status() {
+    let settings = Settings {
+        not_found_status: 410,
+        ..Default::default()
+    };
+    let envelope = json!({"errors": [{"code": "NOT_FOUND"}]});
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state(settings, Some(envelope.clone()))))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for uri in ["/get/missing", "/api/missing"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 410, "{}", uri);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body, envelope, "{}", uri);
+    }
+
+    // Known paths still answer undeclared methods with a 405
+    let req = test::TestRequest::get().uri("/delete/profile").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 405);
+}
+
+#[actix_web::test]
+async fn default_not_found_body_is_kept() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state(Settings::default(), None)))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/missing").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Endpoint not found");
+}
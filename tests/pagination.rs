@@ -0,0 +1,57 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::Value;
+
+const PAGINATION_SPEC: &str = "tests/fixtures/pagination_spec.yaml";
+
+#[actix_web::test]
+async fn wrapped_collection_is_paged() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(PAGINATION_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    // `limit` defaults to the parameter's declared default
+    let req = test::TestRequest::get().uri("/get/articles").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["items"].as_array().unwrap().len(), 4);
+    assert_eq!(body["total"], 10);
+    assert_eq!(body["page"], 1);
+
+    let req = test::TestRequest::get()
+        .uri("/get/articles?page=3&limit=4")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    let ids: Vec<u64> = body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![9, 10]);
+    assert_eq!(body["page"], 3);
+}
+
+#[actix_web::test]
+async fn bare_array_respects_limit() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(PAGINATION_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/tags?limit=3")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body.as_array().unwrap().len(), 3);
+
+    let req = test::TestRequest::get().uri("/get/tags").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body.as_array().unwrap().len(), 10);
+}
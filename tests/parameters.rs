@@ -0,0 +1,131 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::Value;
+
+const PARAMETERS_SPEC: &str = "tests/fixtures/parameters_spec.yaml";
+
+fn validating_settings() -> Settings {
+    Settings {
+        validate_requests: true,
+        ..Default::default()
+    }
+}
+
+#[actix_web::test]
+async fn missing_required_query_parameters_are_rejected() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                PARAMETERS_SPEC,
+                validating_settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/reports").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = test::read_body_json(resp).await;
+    let details: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    // The path item's `tenant` applies alongside the operation's `format`
+    assert_eq!(
+        details,
+        ["query.tenant: is required", "query.format: is required"]
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/get/reports?tenant=acme&format=csv")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn operation_parameters_override_the_path_item() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                PARAMETERS_SPEC,
+                validating_settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/delete/reports").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 204);
+}
+
+#[actix_web::test]
+async fn query_parameters_are_not_checked_without_validation() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(PARAMETERS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/reports").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn path_parameters_are_held_to_their_schema() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                PARAMETERS_SPEC,
+                validating_settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/items/42/ABC")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    for (uri, detail) in [
+        ("/get/items/abc/ABC", "path.id: expected an integer"),
+        ("/get/items/0/ABC", "path.id: must be at least 1"),
+        (
+            "/get/items/42/abc",
+            "path.code: must match pattern ^[A-Z]{3}$",
+        ),
+    ] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400, "{}", uri);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["details"], serde_json::json!([detail]), "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn path_parameters_match_loosely_without_validation() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(PARAMETERS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/items/abc/xyz")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
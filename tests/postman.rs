@@ -0,0 +1,75 @@
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::postman;
+use rusty_stub_api::spec_source::SpecFormat;
+use rusty_stub_api::transactions::{build_endpoints_from_spec, parse_openapi_spec};
+use serde_json::{json, Value};
+
+const POSTMAN_SPEC: &str = "tests/fixtures/postman_spec.yaml";
+
+fn collection() -> Value {
+    let content = std::fs::read_to_string(POSTMAN_SPEC).unwrap();
+    let spec = parse_openapi_spec(&content, SpecFormat::Yaml).unwrap();
+    let endpoints = build_endpoints_from_spec(&content, SpecFormat::Yaml, &Settings::default())
+        .unwrap()
+        .endpoints;
+    postman::collection(&spec, &endpoints, "http://127.0.0.1:8080/api")
+}
+
+#[test]
+fn requests_are_grouped_by_their_first_segment() {
+    let collection = collection();
+
+    assert_eq!(collection["info"]["name"], "Shop API");
+    assert_eq!(
+        collection["info"]["schema"],
+        "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+    );
+    assert_eq!(
+        collection["variable"],
+        json!([{ "key": "baseUrl", "value": "http://127.0.0.1:8080/api" }])
+    );
+
+    let items = collection["item"].as_array().unwrap();
+    let names: Vec<&str> = items
+        .iter()
+        .map(|item| item["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["GET /", "orders", "customers"]);
+
+    let orders: Vec<&str> = items[1]["item"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(orders, ["POST /orders", "GET /orders/{id}"]);
+}
+
+#[test]
+fn requests_carry_their_example_bodies() {
+    let collection = collection();
+    let orders = &collection["item"][1]["item"];
+
+    let create = &orders[0];
+    assert_eq!(create["request"]["method"], "POST");
+    let body: Value =
+        serde_json::from_str(create["request"]["body"]["raw"].as_str().unwrap()).unwrap();
+    assert_eq!(body, json!({ "sku": "ABC-1", "quantity": 2 }));
+
+    let responses = create["response"].as_array().unwrap();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["code"], 201);
+    assert_eq!(responses[0]["status"], "Created");
+    let example: Value = serde_json::from_str(responses[0]["body"].as_str().unwrap()).unwrap();
+    assert_eq!(example, json!({ "id": 7, "sku": "ABC-1" }));
+    assert_eq!(responses[1]["code"], 422);
+
+    let url = &orders[1]["request"]["url"];
+    assert_eq!(url["raw"], "{{baseUrl}}/orders/:id");
+    assert_eq!(url["path"], json!(["orders", ":id"]));
+    assert_eq!(url["variable"], json!([{ "key": "id", "value": "" }]));
+
+    let avatar = &collection["item"][2]["item"][0]["response"][0];
+    assert_eq!(avatar["header"][0]["value"], "image/png");
+    assert_eq!(avatar["body"], "");
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::Value;
+
+const ROUTING_SPEC: &str = "tests/fixtures/routing_spec.yaml";
+
+async fn get_users(settings: Settings) -> (String, String) {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                ROUTING_SPEC,
+                settings,
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/42").to_request();
+    let resp = test::call_service(&app, req).await;
+    let content_type = resp.headers().get("content-type").unwrap();
+    let content_type = content_type.to_str().unwrap().to_string();
+    let body = test::read_body(resp).await;
+    (content_type, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[actix_web::test]
+async fn json_is_compact_by_default() {
+    let (content_type, body) = get_users(Settings::default()).await;
+    assert_eq!(content_type, "application/json");
+    assert!(!body.contains('\n'), "{}", body);
+}
+
+#[actix_web::test]
+async fn json_is_indented_under_pretty() {
+    let (content_type, pretty) = get_users(Settings {
+        pretty: true,
+        ..Settings::default()
+    })
+    .await;
+    let (_, compact) = get_users(Settings::default()).await;
+
+    assert_eq!(content_type, "application/json");
+    assert!(pretty.contains("\n  "), "{}", pretty);
+    assert_eq!(
+        serde_json::from_str::<Value>(&pretty).unwrap(),
+        serde_json::from_str::<Value>(&compact).unwrap()
+    );
+}
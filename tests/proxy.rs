@@ -0,0 +1,111 @@
+mod common;
+
+use actix_web::{test, web, App, HttpRequest, HttpResponse, HttpServer};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::proxy::Upstream;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::{json, Value};
+use std::net::TcpListener;
+use std::sync::Arc;
+
+// Answers with what it was sent, and a status and header of its own
+async fn echo(req: HttpRequest, body: web::Bytes) -> HttpResponse {
+    HttpResponse::Created()
+        .insert_header(("X-Upstream", "echo"))
+        .json(json!({
+            "method": req.method().as_str(),
+            "path": req.path(),
+            "query": req.query_string(),
+            "header": req.headers().get("x-test").and_then(|value| value.to_str().ok()),
+            "body": String::from_utf8_lossy(&body),
+        }))
+}
+
+// An upstream listening on a free port, returning its base URL
+fn start_upstream() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = HttpServer::new(|| App::new().default_service(web::to(echo)))
+        .workers(1)
+        .listen(listener)
+        .unwrap()
+        .run();
+    actix_web::rt::spawn(server);
+    format!("http://{}", address)
+}
+
+fn proxying_state(upstream: &str) -> Arc<rusty_stub_api::data::app::AppState> {
+    Arc::new(
+        common::load_state("tests/fixtures/routing_spec.yaml", Settings::default())
+            .with_upstream(Upstream::new(upstream).unwrap()),
+    )
+}
+
+#[actix_web::test]
+async fn unmatched_requests_are_passed_to_the_upstream() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(proxying_state(&start_upstream())))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/post/orders/7?expand=items")
+        .insert_header(("X-Test", "forwarded"))
+        .set_payload("the body")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    assert_eq!(resp.headers().get("x-upstream").unwrap(), "echo");
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body,
+        json!({
+            "method": "POST",
+            "path": "/orders/7",
+            "query": "expand=items",
+            "header": "forwarded",
+            "body": "the body",
+        })
+    );
+}
+
+#[actix_web::test]
+async fn stubs_are_served_before_the_upstream() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(proxying_state(&start_upstream())))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/42").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(resp.headers().get("x-upstream").is_none());
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["matched"], "byId");
+}
+
+#[actix_web::test]
+async fn unreachable_upstream_is_a_bad_gateway() {
+    // Bound then released, so nothing listens there
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(proxying_state(&format!(
+                "http://{}",
+                address
+            ))))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/missing").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 502);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::Value;
+
+const QUERY_MATCH_SPEC: &str = "tests/fixtures/query_match_spec.yaml";
+
+#[actix_web::test]
+async fn query_selects_the_matching_response() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(QUERY_MATCH_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    for (uri, status, plan) in [
+        ("/get/plans", 200, "standard"),
+        ("/get/plans?type=basic", 200, "standard"),
+        ("/get/plans?type=premium", 201, "premium"),
+        ("/get/plans?type=premium&trial=true", 202, "premium-trial"),
+    ] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), status, "{}", uri);
+
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["plan"], plan, "{}", uri);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/get/plans?type=expired")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 402);
+}
+
+#[actix_web::test]
+async fn requested_status_wins_over_query_match() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(QUERY_MATCH_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/plans?type=premium&__status=200")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["plan"], "standard");
+}
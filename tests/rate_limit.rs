@@ -0,0 +1,48 @@
+mod common;
+
+use actix_web::middleware::from_fn;
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::rate_limit::rate_limit;
+use rusty_stub_api::transactions::dynamic_handler;
+
+#[actix_web::test]
+async fn burst_over_the_limit_gets_429() {
+    let settings = Settings {
+        rate_limit: Some(5),
+        trust_forwarded: true,
+        ..Settings::default()
+    };
+    let state =
+        common::state_with_settings("examples/specs/simple_get_users_spec-api.yaml", settings);
+    let app = test::init_service(
+        App::new()
+            .wrap(from_fn(rate_limit))
+            .app_data(web::Data::new(state))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let mut statuses = Vec::new();
+    for _ in 0..10 {
+        let req = test::TestRequest::get()
+            .uri("/get/users")
+            .insert_header(("X-Forwarded-For", "203.0.113.7, 10.0.0.1"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        if resp.status() == 429 {
+            assert_eq!(resp.headers().get("retry-after").unwrap(), "1");
+        }
+        statuses.push(resp.status().as_u16());
+    }
+    assert_eq!(statuses.iter().filter(|status| **status == 200).count(), 5);
+    assert_eq!(statuses.iter().filter(|status| **status == 429).count(), 5);
+
+    // Every client has a bucket of its own
+    let req = test::TestRequest::get()
+        .uri("/get/users")
+        .insert_header(("X-Forwarded-For", "198.51.100.2"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use actix_web::test;
+use rusty_stub_api::data::recorder::Recorder;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::server::build_app;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+#[actix_web::test]
+async fn served_requests_are_recorded() {
+    let path = std::env::temp_dir().join(format!("{}-record.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let state = common::load_state("tests/fixtures/form_spec.yaml", Settings::default())
+        .with_recorder(Recorder::open(&path).unwrap());
+    let app = test::init_service(build_app(Arc::new(state))).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/subscriptions?source=newsletter")
+        .insert_header(("content-type", "application/x-www-form-urlencoded"))
+        .set_payload("email=ada%40example.com")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+
+    let recording = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let entries: Vec<Value> = recording
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry["method"], "post");
+    assert_eq!(entry["path"], "/subscriptions");
+    assert_eq!(entry["query"], json!({ "source": "newsletter" }));
+    assert_eq!(entry["body"], json!({ "email": "ada@example.com" }));
+    assert_eq!(
+        entry["headers"]["content-type"],
+        "application/x-www-form-urlencoded"
+    );
+    assert_eq!(entry["status"], 201);
+}
@@ -0,0 +1,84 @@
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rusty_stub_api::replay::replay;
+use serde_json::json;
+use std::net::TcpListener;
+
+const FORM: &str = "name=Ada&tags=a+b";
+
+// Accepts the form only as it was sent, not as the JSON it was read as
+async fn forms(req: HttpRequest, body: web::Bytes) -> HttpResponse {
+    let content_type = req
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok());
+    if content_type == Some("application/x-www-form-urlencoded") && body == FORM.as_bytes() {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::BadRequest().finish()
+    }
+}
+
+async fn users(query: web::Query<std::collections::HashMap<String, String>>) -> HttpResponse {
+    match query.get("page").map(String::as_str) {
+        Some("2") => HttpResponse::Ok().finish(),
+        _ => HttpResponse::NotFound().finish(),
+    }
+}
+
+fn start_target() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = HttpServer::new(|| {
+        App::new()
+            .route("/api/forms", web::post().to(forms))
+            .route("/api/users", web::get().to(users))
+    })
+    .workers(1)
+    .listen(listener)
+    .unwrap()
+    .run();
+    actix_web::rt::spawn(server);
+    format!("http://{}/api", address)
+}
+
+#[actix_web::test]
+async fn recording_is_replayed_with_its_raw_bodies() {
+    let recording = [
+        json!({
+            "method": "post",
+            "path": "/forms",
+            "headers": { "content-type": "application/x-www-form-urlencoded" },
+            "body": { "name": "Ada", "tags": "a b" },
+            "raw_body": STANDARD.encode(FORM),
+            "status": 204,
+        }),
+        json!({
+            "method": "get",
+            "path": "/users",
+            "query": { "page": "2" },
+            "status": 200,
+        }),
+        json!({
+            "method": "get",
+            "path": "/users",
+            "status": 200,
+        }),
+    ]
+    .map(|entry| entry.to_string())
+    .join("\n");
+    let path = std::env::temp_dir().join(format!("{}-replay.jsonl", std::process::id()));
+    std::fs::write(&path, recording).unwrap();
+
+    let summary = replay(&path, &start_target()).await;
+    std::fs::remove_file(&path).unwrap();
+    let summary = summary.expect("the recording should be read");
+
+    assert_eq!(summary.matched, 2);
+    assert_eq!(
+        summary.mismatches,
+        vec!["GET /users: expected 200, got 404".to_string()]
+    );
+    assert!(!summary.is_success());
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use actix_web::{web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::request_body;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::{json, Value};
+
+const FORM_SPEC: &str = "tests/fixtures/form_spec.yaml";
+const BOUNDARY: &str = "----stub-boundary";
+
+fn multipart_body() -> String {
+    [
+        format!("--{}", BOUNDARY),
+        "Content-Disposition: form-data; name=\"user\"".to_string(),
+        String::new(),
+        "ada".to_string(),
+        format!("--{}", BOUNDARY),
+        "Content-Disposition: form-data; name=\"file\"; filename=\"avatar.png\"".to_string(),
+        "Content-Type: image/png".to_string(),
+        String::new(),
+        "\u{89}PNG\r\n\u{1a}\n".to_string(),
+        format!("--{}--", BOUNDARY),
+        String::new(),
+    ]
+    .join("\r\n")
+}
+
+#[actix_web::test]
+async fn form_posts_get_the_declared_stub() {
+    let settings = Settings {
+        validate_requests: true,
+        ..Default::default()
+    };
+    let app = actix_web::test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                FORM_SPEC, settings,
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = actix_web::test::TestRequest::get()
+        .uri("/post/avatars")
+        .insert_header((
+            "Content-Type",
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        ))
+        .set_payload(multipart_body())
+        .to_request();
+    let resp = actix_web::test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let body: Value = actix_web::test::read_body_json(resp).await;
+    assert_eq!(body["uploaded"], true);
+
+    let req = actix_web::test::TestRequest::get()
+        .uri("/post/subscriptions")
+        .insert_header(("Content-Type", "application/x-www-form-urlencoded"))
+        .set_payload("email=ada%40example.com")
+        .to_request();
+    let resp = actix_web::test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let body: Value = actix_web::test::read_body_json(resp).await;
+    assert_eq!(body["subscribed"], true);
+}
+
+#[test]
+fn form_fields_are_read_by_content_type() {
+    let fields = request_body::parse(
+        Some("application/x-www-form-urlencoded; charset=utf-8"),
+        b"tag=a&tag=b&name=Ada+Lovelace",
+    );
+    assert_eq!(
+        fields,
+        Some(json!({"tag": ["a", "b"], "name": "Ada Lovelace"}))
+    );
+
+    let content_type = format!("multipart/form-data; boundary=\"{}\"", BOUNDARY);
+    let fields = request_body::parse(Some(&content_type), multipart_body().as_bytes());
+    assert_eq!(fields, Some(json!({"user": "ada", "file": "avatar.png"})));
+
+    let fields = request_body::parse(Some("text/plain"), br#"{"still": "json"}"#);
+    assert_eq!(fields, Some(json!({"still": "json"})));
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+const ROUTING_SPEC: &str = "tests/fixtures/routing_spec.yaml";
+
+#[actix_web::test]
+async fn override_files_replace_the_generated_stub() {
+    let settings = Settings {
+        responses_dir: Some(PathBuf::from("tests/fixtures/responses")),
+        ..Default::default()
+    };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                ROUTING_SPEC,
+                settings,
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/42").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({"id": "override", "tags": ["from", "file"]}));
+
+    // Endpoints without a file keep their stub
+    let req = test::TestRequest::get().uri("/get/users/me").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_ne!(body["id"], "override");
+}
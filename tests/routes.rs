@@ -0,0 +1,205 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::routes;
+use serde_json::Value;
+
+#[actix_web::test]
+async fn openapi_document_is_served() {
+    let state = common::state_from_spec("examples/specs/simple_get_users_spec-api.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/openapi.json")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert!(body["openapi"].as_str().unwrap().starts_with("3."));
+    serde_json::from_value::<openapiv3::OpenAPI>(body).expect("a valid OpenAPI document");
+}
+
+#[actix_web::test]
+async fn openapi_document_is_served_as_yaml() {
+    let state = common::state_from_spec("examples/specs/simple_get_users_spec-api.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/openapi.yaml")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/yaml"
+    );
+
+    let body = test::read_body(resp).await;
+    let spec: openapiv3::OpenAPI = serde_yaml::from_slice(&body).expect("a valid OpenAPI document");
+    assert!(spec.openapi.starts_with("3."));
+    assert!(!spec.paths.paths.is_empty());
+}
+
+#[actix_web::test]
+async fn endpoint_listing_is_not_a_stub() {
+    let state = common::state_from_spec("examples/specs/simple_get_users_spec-api.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/api/endpoints").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(body["count"].as_u64().unwrap() > 0);
+}
+
+#[actix_web::test]
+async fn docs_are_served_from_embedded_assets() {
+    let state = common::state_from_spec("examples/specs/simple_get_users_spec-api.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/docs").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("/docs/assets/swagger-ui-bundle.js"));
+    assert!(!html.contains("unpkg.com"));
+
+    for asset in ["swagger-ui-bundle.js", "swagger-ui.css"] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/docs/assets/{}", asset))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200, "{}", asset);
+    }
+}
+
+#[actix_web::test]
+async fn routes_are_mounted_under_the_base_path() {
+    let settings = Settings {
+        base_path: "/mock".to_string(),
+        ..Settings::default()
+    };
+    let state =
+        common::state_with_settings("examples/specs/simple_get_users_spec-api.yaml", settings);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(routes::scope("/mock")),
+    )
+    .await;
+
+    for uri in ["/mock/health", "/mock/api/users", "/mock/get/users"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200, "{}", uri);
+    }
+
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    let req = test::TestRequest::get().uri("/mock/docs").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("/mock/docs/assets/swagger-ui-bundle.js"));
+    assert!(html.contains("/mock/api/openapi.json"));
+
+    let req = test::TestRequest::get()
+        .uri("/mock/api/openapi.json")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["servers"][0]["url"], "/mock/api");
+}
+
+#[actix_web::test]
+async fn empty_base_path_mounts_at_the_root() {
+    let state = common::state_from_spec("examples/specs/simple_get_users_spec-api.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(routes::scope("")),
+    )
+    .await;
+
+    for uri in ["/", "/health", "/api/users"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200, "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn docs_rewrite_requests_from_the_declared_server() {
+    let state = common::state_from_spec("tests/fixtures/servers_spec.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/docs").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains(r#"const server = "/v2";"#));
+
+    // Without servers the requests are expected under `/api`
+    let state = common::state_from_spec("tests/fixtures/default_spec.yaml");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/docs").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains(r#"const server = "/api";"#));
+}
+
+#[actix_web::test]
+async fn declared_server_is_only_rewritten_by_the_docs() {
+    let settings = Settings {
+        base_path: "/mock".to_string(),
+        ..Settings::default()
+    };
+    let state = common::state_with_settings("tests/fixtures/servers_spec.yaml", settings);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(routes::scope("/mock")),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/mock/api/openapi.json")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["servers"][0]["url"], "/mock/api");
+
+    let req = test::TestRequest::get().uri("/mock/docs").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains(r#"const server = "/v2";"#));
+    assert!(html.contains("const api = '/mock/api';"));
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::Value;
+
+const ROUTING_SPEC: &str = "tests/fixtures/routing_spec.yaml";
+
+#[actix_web::test]
+async fn literal_path_outranks_template() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(ROUTING_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    // The template is declared first, so the literal only wins through ordering
+    for _ in 0..5 {
+        let req = test::TestRequest::get().uri("/get/users/me").to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["matched"], "me");
+    }
+}
+
+#[actix_web::test]
+async fn literal_path_keeps_its_own_statuses() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(ROUTING_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    // `/accounts/{id}` declares a 200, which doesn't take over `/accounts/me`
+    let req = test::TestRequest::get()
+        .uri("/get/accounts/me")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["matched"], "me");
+
+    // Nor the 404 the template declares
+    let req = test::TestRequest::get()
+        .uri("/get/accounts/me")
+        .insert_header(("X-Mock-Status", "404"))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["matched"], "me");
+
+    let req = test::TestRequest::get()
+        .uri("/get/accounts/42")
+        .insert_header(("X-Mock-Status", "404"))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["matched"], "byIdMissing");
+}
+
+#[actix_web::test]
+async fn template_matches_other_segments() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(ROUTING_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/42").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["matched"], "byId");
+
+    let req = test::TestRequest::get()
+        .uri("/get/users/42/posts")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn dotted_paths_match_literally() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(ROUTING_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/files/report.json")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["matched"], "file");
+
+    let req = test::TestRequest::get()
+        .uri("/get/v1.0/status")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["matched"], "status");
+
+    // The dot is not a regex wildcard
+    let req = test::TestRequest::get()
+        .uri("/get/v1x0/status")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    let req = test::TestRequest::get()
+        .uri("/get/files/.json")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn trailing_slash_is_ignored() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(ROUTING_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/me/").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["matched"], "me");
+
+    let req = test::TestRequest::get().uri("/get/users/42/").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["matched"], "byId");
+}
+
+#[actix_web::test]
+async fn greedy_parameters_match_across_slashes() {
+    let settings = Settings {
+        templates: true,
+        ..Settings::default()
+    };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                ROUTING_SPEC,
+                settings,
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/blobs/readme")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["matched"], "blob");
+    assert_eq!(body["key"], "readme");
+
+    let req = test::TestRequest::get()
+        .uri("/get/blobs/docs/2024/report.pdf")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["matched"], "blob");
+    assert_eq!(body["key"], "docs/2024/report.pdf");
+
+    // The greedy parameter still takes at least one segment
+    let req = test::TestRequest::get().uri("/get/blobs/").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+}
@@ -0,0 +1,20 @@
+mod common;
+
+use rusty_stub_api::self_check;
+
+#[test]
+fn stubs_that_break_their_schema_are_reported() {
+    let state = common::state_from_spec("tests/fixtures/self_check_spec.yaml");
+    let spec = state.get_spec();
+    let endpoints = state.endpoints.read().unwrap();
+
+    let report = self_check::run(&spec, &endpoints);
+    assert_eq!(report.passed, 1);
+    assert_eq!(report.skipped, 1);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "GET /users 404");
+    assert!(report.failed[0].1[0].contains("response.code"));
+
+    assert!(report.is_fatal(true));
+    assert!(!report.is_fatal(false));
+}
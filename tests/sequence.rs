@@ -0,0 +1,56 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::{json, Value};
+
+const SEQUENCE_SPEC: &str = "tests/fixtures/sequence_spec.yaml";
+
+#[actix_web::test]
+async fn consecutive_calls_walk_through_the_sequence() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(SEQUENCE_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let mut bodies = Vec::new();
+    for _ in 0..3 {
+        let req = test::TestRequest::get().uri("/get/jobs/1").to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        bodies.push(body);
+    }
+    assert_eq!(
+        bodies,
+        vec![
+            json!({ "status": "processing" }),
+            json!({ "status": "done" }),
+            json!({ "status": "processing" }),
+        ]
+    );
+}
+
+#[actix_web::test]
+async fn operation_sequence_leaves_error_responses_alone() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(SEQUENCE_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/reports").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({ "ready": false }));
+
+    let req = test::TestRequest::get()
+        .uri("/get/reports?__status=500")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({ "error": "failed" }));
+
+    let req = test::TestRequest::get().uri("/get/reports").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({ "ready": true }));
+}
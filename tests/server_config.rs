@@ -0,0 +1,39 @@
+mod common;
+
+use clap::Parser;
+use rusty_stub_api::data::cli_args::Args;
+use rusty_stub_api::server::{socket_address, ServerConfig};
+
+#[test]
+fn workers_default_to_the_cpu_count() {
+    let config = ServerConfig::new(common::state_from_spec("tests/fixtures/default_spec.yaml"));
+    let cpus = std::thread::available_parallelism().unwrap().get();
+    assert_eq!(config.workers, cpus);
+    assert_eq!(config.keep_alive, 5);
+
+    let config = config.with_workers(2).with_keep_alive(0);
+    assert_eq!(config.workers, 2);
+    assert_eq!(config.keep_alive, 0);
+
+    let zero_workers =
+        Args::try_parse_from(["rusty-stub-api", "--spec", "api.yaml", "--workers", "0"]);
+    assert!(zero_workers.is_err());
+}
+
+#[test]
+fn hosts_are_repeatable_and_ipv6_is_bracketed() {
+    let args = Args::try_parse_from([
+        "rusty-stub-api",
+        "--server",
+        "127.0.0.1,::1",
+        "--host",
+        "0.0.0.0",
+    ])
+    .unwrap();
+    assert_eq!(args.host, vec!["127.0.0.1", "::1", "0.0.0.0"]);
+
+    assert_eq!(socket_address("::1", 8080), "[::1]:8080");
+    assert_eq!(socket_address("[::]", 8080), "[::]:8080");
+    assert_eq!(socket_address("127.0.0.1", 8080), "127.0.0.1:8080");
+    assert_eq!(socket_address("localhost", 8080), "localhost:8080");
+}
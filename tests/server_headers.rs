@@ -0,0 +1,45 @@
+mod common;
+
+use actix_web::http::header;
+use actix_web::test;
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::server::build_app;
+
+const SIMPLE_SPEC: &str = "examples/specs/simple_get_users_spec-api.yaml";
+
+#[actix_web::test]
+async fn server_and_date_headers_are_overridden() {
+    let settings = Settings {
+        server_header: Some("stub-server/1.0".to_string()),
+        freeze_date: Some("Sun, 06 Nov 1994 08:49:37 GMT".to_string()),
+        ..Default::default()
+    };
+    let app = test::init_service(build_app(common::state_with_settings(
+        SIMPLE_SPEC,
+        settings,
+    )))
+    .await;
+
+    for uri in ["/api/users", "/health", "/api/nowhere"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get(header::SERVER).unwrap(),
+            "stub-server/1.0"
+        );
+        assert_eq!(
+            resp.headers().get(header::DATE).unwrap(),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+    }
+}
+
+#[actix_web::test]
+async fn headers_are_left_alone_by_default() {
+    let app = test::init_service(build_app(common::state_from_spec(SIMPLE_SPEC))).await;
+
+    let req = test::TestRequest::get().uri("/api/users").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.headers().get(header::SERVER).is_none());
+    assert!(resp.headers().get(header::DATE).is_none());
+}
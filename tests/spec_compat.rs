@@ -0,0 +1,72 @@
+mod common;
+
+use actix_web::{web, App};
+use openapiv3::{ReferenceOr, SchemaKind, Type};
+use rusty_stub_api::spec_source::SpecFormat;
+use rusty_stub_api::transactions::{dynamic_handler, parse_openapi_spec};
+use serde_json::{json, Value};
+
+const OPENAPI_31_SPEC: &str = "tests/fixtures/openapi31_spec.yaml";
+
+#[test]
+fn type_arrays_become_nullable_types() {
+    let content = std::fs::read_to_string(OPENAPI_31_SPEC).unwrap();
+    let spec = parse_openapi_spec(&content, SpecFormat::Yaml).expect("3.1 spec should parse");
+    assert_eq!(spec.openapi, "3.0.3");
+
+    let components = spec.components.unwrap();
+    let Some(ReferenceOr::Item(user)) = components.schemas.get("User") else {
+        panic!("User schema should be inline");
+    };
+    let SchemaKind::Type(Type::Object(user)) = &user.schema_kind else {
+        panic!("User should be an object");
+    };
+    let Some(ReferenceOr::Item(nickname)) = user.properties.get("nickname") else {
+        panic!("nickname should be inline");
+    };
+    assert!(nickname.schema_data.nullable);
+    assert!(matches!(
+        nickname.schema_kind,
+        SchemaKind::Type(Type::String(_))
+    ));
+}
+
+#[test]
+fn json_specs_are_downconverted_too() {
+    let content = r#"{
+        "openapi": "3.1.0",
+        "info": {"title": "t", "version": "1"},
+        "components": {"schemas": {"Id": {"type": ["integer", "null"]}}}
+    }"#;
+    let spec = parse_openapi_spec(content, SpecFormat::Json).expect("3.1 spec should parse");
+    assert!(spec.paths.paths.is_empty());
+
+    let schemas = spec.components.unwrap().schemas;
+    let Some(ReferenceOr::Item(id)) = schemas.get("Id") else {
+        panic!("Id should be inline");
+    };
+    assert!(id.schema_data.nullable);
+    assert!(matches!(id.schema_kind, SchemaKind::Type(Type::Integer(_))));
+}
+
+#[actix_web::test]
+async fn generated_body_follows_31_schemas() {
+    let app = actix_web::test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(OPENAPI_31_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = actix_web::test::TestRequest::get()
+        .uri("/get/users/7")
+        .to_request();
+    let body: Value = actix_web::test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["id"], 1);
+    assert_eq!(body["nickname"], "string");
+    assert_eq!(body["age"], 18);
+    assert!(body["score"].as_f64().unwrap() < 10.0);
+    assert_eq!(body["role"], "admin");
+    assert_eq!(body["country"], "PT");
+    assert_eq!(body["example"], json!(true));
+}
@@ -0,0 +1,79 @@
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::spec_merge::load_merged;
+use rusty_stub_api::spec_source::SpecSource;
+use rusty_stub_api::transactions::{build_endpoints_from_document, parse_openapi_document};
+
+fn sources() -> Vec<SpecSource> {
+    vec![
+        SpecSource::from("tests/fixtures/merge_users_spec.yaml"),
+        SpecSource::from("tests/fixtures/merge_orders_spec.yaml"),
+    ]
+}
+
+#[test]
+fn specs_are_merged_into_one_document() {
+    let document = load_merged(&sources(), false).expect("specs should merge");
+    let spec = parse_openapi_document(&document).expect("a valid document");
+    assert_eq!(spec.info.title, "Users API");
+    assert!(spec.paths.paths.contains_key("/orders"));
+    let tags: Vec<&str> = spec.tags.iter().map(|tag| tag.name.as_str()).collect();
+    assert_eq!(tags, vec!["users", "orders"]);
+
+    let endpoints = build_endpoints_from_document(&document, &Settings::default())
+        .unwrap()
+        .endpoints;
+    let mut routes: Vec<String> = endpoints
+        .iter()
+        .map(|endpoint| format!("{} {}", endpoint.method.to_uppercase(), endpoint.path))
+        .collect();
+    routes.sort();
+    assert_eq!(routes, vec!["GET /orders", "GET /users", "POST /users"]);
+
+    // The later spec wins the `GET /users` collision
+    let users = endpoints
+        .iter()
+        .find(|endpoint| endpoint.method.eq_ignore_ascii_case("get") && endpoint.path == "/users")
+        .unwrap();
+    assert_eq!(users.response_body[0]["source"], "orders");
+}
+
+#[test]
+fn collisions_are_errors_under_strict() {
+    let error = load_merged(&sources(), true).expect_err("GET /users collides");
+    assert!(error.to_string().contains("GET /users"));
+}
+
+#[test]
+fn duplicate_endpoints_are_reported_with_their_sources() {
+    let sources = vec![
+        SpecSource::from("tests/fixtures/merge_users_spec.yaml"),
+        SpecSource::from("tests/fixtures/duplicate_users_spec.yaml"),
+    ];
+    let document = load_merged(&sources, false).expect("specs should merge");
+
+    // Both are served, the first one answering
+    let built = build_endpoints_from_document(&document, &Settings::default()).unwrap();
+    assert_eq!(built.endpoints.len(), 4);
+
+    let strict = Settings {
+        strict: true,
+        ..Settings::default()
+    };
+    let error = build_endpoints_from_document(&document, &strict)
+        .err()
+        .expect("duplicates are fatal under --strict")
+        .to_string();
+    assert!(
+        error.contains(
+            "GET /users/ 200 (from tests/fixtures/duplicate_users_spec.yaml) duplicates \
+             GET /users 200 (from tests/fixtures/merge_users_spec.yaml)"
+        ),
+        "{}",
+        error
+    );
+    assert!(
+        error.contains("GET /accounts/{accountId} 200 (from tests/fixtures/duplicate_users_spec.yaml) duplicates GET /accounts/{id} 200"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::Value;
+
+const SSE_SPEC: &str = "tests/fixtures/sse_spec.yaml";
+
+fn settings() -> Settings {
+    Settings {
+        sse_interval_ms: 10,
+        sse_max_events: Some(3),
+        ..Settings::default()
+    }
+}
+
+#[actix_web::test]
+async fn event_stream_repeats_the_stub_body() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                SSE_SPEC,
+                settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/prices")
+        .insert_header(("Accept", "text/event-stream"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+    assert_eq!(resp.headers().get("cache-control").unwrap(), "no-cache");
+
+    let body = test::read_body(resp).await;
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    let events: Vec<&str> = body.split_terminator("\n\n").collect();
+    assert_eq!(events.len(), 3);
+    for (index, event) in events.iter().enumerate() {
+        let (id, data) = event.split_once('\n').unwrap();
+        assert_eq!(id, format!("id: {}", index + 1));
+        let data: Value = serde_json::from_str(data.strip_prefix("data: ").unwrap()).unwrap();
+        assert_eq!(data["symbol"], "ACME");
+    }
+}
+
+#[actix_web::test]
+async fn multiline_events_have_a_data_field_per_line() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                SSE_SPEC,
+                Settings {
+                    sse_max_events: Some(1),
+                    ..settings()
+                },
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/log").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "id: 1\ndata: started\ndata: ready\n\n");
+}
+
+#[actix_web::test]
+async fn json_clients_get_a_plain_response() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                SSE_SPEC,
+                settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/prices")
+        .insert_header(("Accept", "application/json"))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["price"], 12.5);
+}
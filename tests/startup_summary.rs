@@ -0,0 +1,57 @@
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::spec_source::SpecFormat;
+use rusty_stub_api::startup_summary;
+use rusty_stub_api::transactions::{
+    build_endpoints_from_spec, parse_openapi_spec, SkippedOperation,
+};
+
+const SKIPPED_SPEC: &str = "tests/fixtures/skipped_spec.yaml";
+
+#[test]
+fn skipped_operations_are_reported_with_their_reason() {
+    let content = std::fs::read_to_string(SKIPPED_SPEC).unwrap();
+    let built =
+        build_endpoints_from_spec(&content, SpecFormat::Yaml, &Settings::default()).unwrap();
+
+    assert_eq!(built.endpoints.len(), 3);
+    assert_eq!(
+        built.skipped,
+        [
+            SkippedOperation {
+                method: Some("query".to_string()),
+                path: "/search".to_string(),
+                reason: "unsupported method".to_string(),
+            },
+            SkippedOperation {
+                method: Some("get".to_string()),
+                path: "/search".to_string(),
+                reason: "response 404: unresolved reference #/components/responses/Missing"
+                    .to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn summary_lists_endpoints_by_method_and_the_skipped_ones() {
+    let content = std::fs::read_to_string(SKIPPED_SPEC).unwrap();
+    let spec = parse_openapi_spec(&content, SpecFormat::Yaml).unwrap();
+    let built =
+        build_endpoints_from_spec(&content, SpecFormat::Yaml, &Settings::default()).unwrap();
+
+    let summary = startup_summary::render(
+        &spec,
+        "http://127.0.0.1:8080",
+        &built.endpoints,
+        &built.skipped,
+    );
+    assert_eq!(
+        summary,
+        "Spec       Partly served API 2.1.0\n\
+         Listening  http://127.0.0.1:8080\n\
+         Endpoints  3 (GET 2, POST 1)\n\
+         Skipped    2\n  \
+         QUERY /search: unsupported method\n  \
+         GET /search: response 404: unresolved reference #/components/responses/Missing\n"
+    );
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::{json, Value};
+
+const STATEFUL_SPEC: &str = "tests/fixtures/stateful_spec.yaml";
+
+fn settings() -> Settings {
+    Settings {
+        stateful: true,
+        ..Settings::default()
+    }
+}
+
+#[actix_web::test]
+async fn created_resources_are_served_back() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                STATEFUL_SPEC,
+                settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/post/users")
+        .set_json(json!({ "name": "Ada" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let created: Value = test::read_body_json(resp).await;
+    assert_eq!(created, json!({ "id": 1, "name": "Ada" }));
+
+    let req = test::TestRequest::get().uri("/get/users/1").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, created);
+}
+
+#[actix_web::test]
+async fn put_replaces_the_resource() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                STATEFUL_SPEC,
+                settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/post/users")
+        .set_json(json!({ "name": "Ada", "role": "admin" }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri("/put/users/1")
+        .set_json(json!({ "id": 1, "name": "Grace" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::get().uri("/get/users/1").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({ "id": 1, "name": "Grace" }));
+}
+
+#[actix_web::test]
+async fn deleted_resources_are_gone() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                STATEFUL_SPEC,
+                settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/post/users")
+        .set_json(json!({ "name": "Ada" }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::delete()
+        .uri("/delete/users/1")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 204);
+
+    let req = test::TestRequest::get().uri("/get/users/1").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    let req = test::TestRequest::delete()
+        .uri("/delete/users/1")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn unknown_collections_fall_back_to_the_stub() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                STATEFUL_SPEC,
+                settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/7").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body, json!({ "id": 99, "name": "Stub" }));
+}
+
+#[actix_web::test]
+async fn collections_are_listed_by_id() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                STATEFUL_SPEC,
+                settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    for name in ["Ada", "Grace", "Linus"] {
+        let req = test::TestRequest::post()
+            .uri("/post/users")
+            .set_json(json!({ "name": name }))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+    let req = test::TestRequest::post()
+        .uri("/post/users")
+        .set_json(json!({ "id": 10, "name": "Barbara" }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get().uri("/get/users").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Ada", "Grace", "Linus", "Barbara"]);
+}
+
+#[actix_web::test]
+async fn assigned_ids_skip_the_ones_clients_sent() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                STATEFUL_SPEC,
+                settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/post/users")
+        .set_json(json!({ "id": 1, "name": "Ada" }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/post/users")
+        .set_json(json!({ "name": "Grace" }))
+        .to_request();
+    let created: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(created, json!({ "id": 2, "name": "Grace" }));
+
+    let req = test::TestRequest::get().uri("/get/users/1").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({ "id": 1, "name": "Ada" }));
+}
+
+#[actix_web::test]
+async fn requested_statuses_bypass_the_store() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_with_settings(
+                STATEFUL_SPEC,
+                settings(),
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/post/users")
+        .set_json(json!({ "name": "Ada" }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/users?__status=404")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body, json!({ "error": "none" }));
+}
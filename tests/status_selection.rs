@@ -0,0 +1,163 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::Value;
+
+const STATUS_SPEC: &str = "tests/fixtures/status_spec.yaml";
+
+#[actix_web::test]
+async fn defaults_to_lowest_success_status() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(STATUS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/orders").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert!(body["orders"].is_array());
+}
+
+#[actix_web::test]
+async fn header_selects_declared_status() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(STATUS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/orders")
+        .insert_header(("X-Mock-Status", "500"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 500);
+
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "boom");
+}
+
+#[actix_web::test]
+async fn query_param_selects_declared_status() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(STATUS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/orders?__status=500")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 500);
+
+    // Unknown statuses fall back to the success response
+    let req = test::TestRequest::get()
+        .uri("/get/orders?__status=418")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn range_keys_are_served_with_a_representative_code() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(STATUS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post().uri("/post/payments").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::post()
+        .uri("/post/payments")
+        .insert_header(("X-Mock-Status", "4XX"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    // Invalid codes are dropped when loading, so this falls back to the default
+    let req = test::TestRequest::post()
+        .uri("/post/payments")
+        .insert_header(("X-Mock-Status", "600"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn default_response_is_served() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(
+                "tests/fixtures/default_spec.yaml",
+            )))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/health-report")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["healthy"], true);
+
+    // The default response takes any status the operation doesn't declare
+    let req = test::TestRequest::get()
+        .uri("/get/health-report")
+        .insert_header(("X-Mock-Status", "503"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+}
+
+#[actix_web::test]
+async fn examples_belong_to_the_selected_status() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(STATUS_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post().uri("/post/refunds").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["refunded"], "full");
+
+    let req = test::TestRequest::post()
+        .uri("/post/refunds")
+        .insert_header(("X-Mock-Status", "400"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "expired");
+
+    // Named examples are looked up among those of the selected status only
+    let req = test::TestRequest::post()
+        .uri("/post/refunds")
+        .insert_header(("X-Mock-Status", "400"))
+        .insert_header(("Prefer", "example=duplicate"))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["error"], "duplicate");
+
+    let req = test::TestRequest::post()
+        .uri("/post/refunds")
+        .insert_header(("Prefer", "example=duplicate"))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["refunded"], "full");
+}
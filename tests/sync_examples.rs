@@ -0,0 +1,55 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::routes;
+use serde_json::Value;
+
+const KEY_ORDER_SPEC: &str = "tests/fixtures/key_order_spec.yaml";
+
+fn example_of_profile(spec: &Value) -> &Value {
+    &spec["paths"]["/profile"]["get"]["responses"]["200"]["content"]["application/json"]["example"]
+}
+
+#[actix_web::test]
+async fn served_document_shows_the_stub_bodies_as_examples() {
+    let state = common::state_with_settings(
+        KEY_ORDER_SPEC,
+        Settings {
+            sync_examples: true,
+            ..Settings::default()
+        },
+    );
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/openapi.json")
+        .to_request();
+    let spec: Value = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/api/profile").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+
+    assert!(body.is_object());
+    assert_eq!(example_of_profile(&spec), &body);
+}
+
+#[actix_web::test]
+async fn examples_are_left_alone_by_default() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(KEY_ORDER_SPEC)))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/openapi.json")
+        .to_request();
+    let spec: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(example_of_profile(&spec).is_null());
+}
@@ -0,0 +1,45 @@
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::spec_source::SpecFormat;
+use rusty_stub_api::transactions::build_endpoints_from_spec;
+
+const TAGS_SPEC: &str = "tests/fixtures/tags_spec.yaml";
+
+fn endpoint_names(tags: &[&str]) -> Vec<String> {
+    let content = std::fs::read_to_string(TAGS_SPEC).unwrap();
+    let settings = Settings {
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        ..Default::default()
+    };
+    build_endpoints_from_spec(&content, SpecFormat::Yaml, &settings)
+        .unwrap()
+        .endpoints
+        .iter()
+        .map(|endpoint| {
+            format!(
+                "{} {} {}",
+                endpoint.method, endpoint.path, endpoint.response_code
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn only_operations_with_a_requested_tag_are_loaded() {
+    assert_eq!(
+        endpoint_names(&["billing"]),
+        [
+            "get /invoices 200",
+            "get /invoices 500",
+            "post /invoices 201"
+        ]
+    );
+    assert_eq!(
+        endpoint_names(&["writes", "accounts"]),
+        ["post /invoices 201", "get /users 200"]
+    );
+}
+
+#[test]
+fn every_operation_is_loaded_without_tags() {
+    assert_eq!(endpoint_names(&[]).len(), 5);
+}
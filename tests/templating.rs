@@ -0,0 +1,110 @@
+mod common;
+
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+use serde_json::{json, Value};
+
+const TEMPLATE_SPEC: &str = "tests/fixtures/template_spec.yaml";
+
+#[actix_web::test]
+async fn placeholders_are_filled_from_the_request() {
+    let state = common::state_with_settings(
+        TEMPLATE_SPEC,
+        Settings {
+            templates: true,
+            ..Settings::default()
+        },
+    );
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/users/42?name=Ada")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["id"], "42");
+    assert_eq!(body["greeting"], "Hello Ada");
+    assert_eq!(body["unknown"], "{{missing}}");
+}
+
+#[actix_web::test]
+async fn placeholders_are_kept_without_templates() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(TEMPLATE_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/get/users/42").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["id"], "{{id}}");
+}
+
+#[actix_web::test]
+async fn body_placeholders_echo_the_request_body() {
+    let state = common::state_with_settings(
+        TEMPLATE_SPEC,
+        Settings {
+            templates: true,
+            ..Settings::default()
+        },
+    );
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/post/users")
+        .set_json(json!({"user": {"id": 7, "name": "Ada"}, "roles": ["admin"]}))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body["id"], 7);
+    assert_eq!(body["name"], "Ada");
+    assert_eq!(body["roles"], json!(["admin"]));
+    assert_eq!(body["summary"], "Created Ada with null");
+    assert_eq!(body["missing"], Value::Null);
+}
+
+#[actix_web::test]
+async fn encoded_path_segments_are_decoded_once_matched() {
+    let state = common::state_with_settings(
+        TEMPLATE_SPEC,
+        Settings {
+            templates: true,
+            ..Settings::default()
+        },
+    );
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    // The encoded slash stays part of the `{id}` segment
+    let req = test::TestRequest::get()
+        .uri("/get/users/john%20doe%2Fadmin")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["id"], "john doe/admin");
+
+    let req = test::TestRequest::get()
+        .uri("/get/users/100%25")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["id"], "100%");
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use rusty_stub_api::validation::validate_spec;
+
+#[test]
+fn examples_that_break_their_schema_are_reported() {
+    let state = common::state_from_spec("tests/fixtures/self_check_spec.yaml");
+
+    let report = validate_spec(&state.get_spec());
+    let warnings: Vec<String> = report
+        .warnings
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect();
+
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("example of GET /users 404 (application/json)"));
+    assert!(warnings[0].contains("example.code"));
+    assert!(report.errors.is_empty());
+    assert!(report.is_fatal(true));
+    assert!(!report.is_fatal(false));
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use awc::error::WsProtocolError;
+use awc::ws::Frame;
+use futures_util::StreamExt;
+use rusty_stub_api::server::build_app;
+use serde_json::{json, Value};
+
+const WEBSOCKET_SPEC: &str = "tests/fixtures/websocket_spec.yaml";
+
+fn start_server() -> actix_test::TestServer {
+    let state = common::state_from_spec(WEBSOCKET_SPEC);
+    actix_test::start(move || build_app(state.clone()))
+}
+
+async fn next_text<S>(connection: &mut S) -> Value
+where
+    S: StreamExt<Item = Result<Frame, WsProtocolError>> + Unpin,
+{
+    match connection.next().await {
+        Some(Ok(Frame::Text(text))) => serde_json::from_slice(&text).unwrap(),
+        other => panic!("expected a text message, got {:?}", other),
+    }
+}
+
+#[actix_web::test]
+async fn stub_body_is_pushed_at_every_interval() {
+    let mut server = start_server();
+    let mut connection = server.ws_at("/ws/tickers/ACME").await.unwrap();
+
+    let expected = json!({ "symbol": "ACME", "price": 12.5 });
+    assert_eq!(next_text(&mut connection).await, expected);
+    assert_eq!(next_text(&mut connection).await, expected);
+}
+
+#[actix_web::test]
+async fn operations_without_the_extension_are_not_streamed() {
+    let mut server = start_server();
+    assert!(server.ws_at("/ws/quotes").await.is_err());
+
+    let resp = server.get("/ws/quotes").send().await.unwrap();
+    assert_eq!(resp.status(), 404);
+}
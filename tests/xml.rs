@@ -0,0 +1,106 @@
+mod common;
+
+use actix_web::http::header;
+use actix_web::{test, web, App};
+use rusty_stub_api::data::settings::Settings;
+use rusty_stub_api::transactions::dynamic_handler;
+
+const XML_SPEC: &str = "tests/fixtures/xml_spec.yaml";
+
+const PET: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+    r#"<pet id="12">"#,
+    "<petName>Tom &amp; Jerry</petName>",
+    "<photoUrl>https://example.com/1.png</photoUrl>",
+    "<photoUrl>https://example.com/2.png</photoUrl>",
+    "<tags><tag>cat</tag><tag>mouse</tag></tags>",
+    "</pet>",
+);
+
+#[actix_web::test]
+async fn xml_bodies_round_trip() {
+    let state = common::state_with_settings(
+        XML_SPEC,
+        Settings {
+            templates: true,
+            validate_requests: true,
+            ..Settings::default()
+        },
+    );
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    // The attribute is read back as the integer the schema declares, which
+    // validation accepts
+    let req = test::TestRequest::post()
+        .uri("/post/pets")
+        .insert_header((header::CONTENT_TYPE, "application/xml"))
+        .insert_header((header::ACCEPT, "application/xml"))
+        .set_payload(PET)
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 201);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/xml"
+    );
+    let body = test::read_body(response).await;
+    assert_eq!(std::str::from_utf8(&body).unwrap(), PET);
+}
+
+#[actix_web::test]
+async fn generated_xml_follows_the_schema_annotations() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::state_from_spec(XML_SPEC)))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/get/pets/7")
+        .insert_header((header::ACCEPT, "application/xml"))
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    let body = std::str::from_utf8(&body).unwrap();
+
+    assert!(body.contains(r#"<pet id="7">"#), "{}", body);
+    assert!(body.contains("<petName>Rex</petName>"), "{}", body);
+    assert!(body.contains("<tags><tag>good</tag>"), "{}", body);
+    assert!(
+        body.contains("<photoUrl>https://example.com/rex.png</photoUrl>"),
+        "{}",
+        body
+    );
+}
+
+#[actix_web::test]
+async fn xml_bodies_are_validated_against_the_schema() {
+    let state = common::state_with_settings(
+        XML_SPEC,
+        Settings {
+            validate_requests: true,
+            ..Settings::default()
+        },
+    );
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .route("/{method}/{path:.*}", web::to(dynamic_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/post/pets")
+        .insert_header((header::CONTENT_TYPE, "application/xml"))
+        .set_payload(r#"<pet id="twelve"><petName>Tom</petName></pet>"#)
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 400);
+}